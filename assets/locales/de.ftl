@@ -0,0 +1,78 @@
+# Deutsch
+
+## Placeholders, until a widget fills itself in
+not-available = k. A.
+tab-header = Kopfzeile
+tab-details = Details
+
+## Time widget
+time-date = Datum:
+time-speed = Tempo:
+time-fps = FPS:
+speed-paused = Pausiert
+speed-slow = Langsam
+speed-normal = Normal
+speed-fast = Schnell
+speed-fastest = Sehr schnell
+
+## Outliners
+empire-systems = REICHSSYSTEME
+body-planet = Planet
+body-star = Stern
+body-colony = ({ $population }, { $type })
+body-size = Größe: { $size } | Einstrahlung: { $insolation }
+body-placeholder = Details zu { $name }
+system-centrality = Zentralität: { $centrality }
+system-chokepoint = Engpass
+system-role-frontier = Grenzland
+system-role-core = Kernland
+system-border-distance = { $jumps ->
+    [one] ({ $jumps } Sprung von der Grenze)
+   *[other] ({ $jumps } Sprünge von der Grenze)
+}
+
+## History chart
+chart-population = Bevölkerung
+chart-output = Produktion
+chart-infrastructure = Infrastruktur
+chart-colonies = Kolonien
+chart-systems = Systeme
+chart-title = { $metric }: { $latest } (max. { $max })
+
+## Policies
+policy-title = WIRTSCHAFTSPOLITIK
+policy-investment = Investitionen
+policy-education = Bildung
+policy-taxation = Steuern
+policy-conscription = Wehrpflicht
+policy-level-low = Niedrig
+policy-level-standard = Normal
+policy-level-high = Hoch
+policy-priority-balanced = Ausgewogen
+policy-priority-light = Leichtindustrie
+policy-priority-heavy = Schwerindustrie
+policy-priority-advanced = Hochtechnologie
+policy-current = { $policy }: { $option }
+policy-reform = { $policy }: { $option } -> { $next } ({ $days ->
+    [one] { $days } Tag
+   *[other] { $days } Tage
+})
+
+## Colony details
+population-details = Kapazität: { $capacity }
+    Geburtenrate: { $birth_rate }/Jahr | Sterberate: { $death_rate }/Jahr
+    Geburten: { $births } | Todesfälle: { $deaths } | Saldo: { $net }
+economy-output = Produktion:
+economy-infrastructure = Infrastruktur:
+economy-light = Leicht
+economy-heavy = Schwer
+economy-advanced = Hochtech
+economy-amount = { $sector }: { $amount }
+economy-growth = { $sector }: { $amount } (+{ $growth })
+economy-rates = Abschreibungsrate: { $depreciation }
+    Sparquote: { $savings }
+    Ausbildungsrate: { $training } | Steuern: { $taxes } | Investiert in: { $priority }
+economy-prices = Preise: Leicht { $light } | Schwer { $heavy } | Hochtech { $advanced }
+economy-wages = Löhne (je Million Arbeiter): Arbeiter { $basic } | Ingenieure { $engineers }
+economy-workers = Arbeiter: { $workers }
+economy-engineers = Ingenieure: { $engineers } (+{ $growth })
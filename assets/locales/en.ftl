@@ -0,0 +1,78 @@
+# English. Also built into the game, as the fallback for every other language
+
+## Placeholders, until a widget fills itself in
+not-available = N/A
+tab-header = Header text
+tab-details = Tab details text
+
+## Time widget
+time-date = Date:
+time-speed = Speed:
+time-fps = FPS:
+speed-paused = Paused
+speed-slow = Slow
+speed-normal = Normal
+speed-fast = Fast
+speed-fastest = Fastest
+
+## Outliners
+empire-systems = IMPERIAL SYSTEMS
+body-planet = Planet
+body-star = Star
+body-colony = ({ $population }, { $type })
+body-size = Size: { $size } | Insolation: { $insolation }
+body-placeholder = Panel details for { $name }
+system-centrality = Centrality: { $centrality }
+system-chokepoint = Chokepoint
+system-role-frontier = Frontier
+system-role-core = Core
+system-border-distance = { $jumps ->
+    [one] ({ $jumps } jump from the border)
+   *[other] ({ $jumps } jumps from the border)
+}
+
+## History chart
+chart-population = Population
+chart-output = Output
+chart-infrastructure = Infrastructure
+chart-colonies = Colonies
+chart-systems = Systems
+chart-title = { $metric }: { $latest } (max { $max })
+
+## Policies
+policy-title = ECONOMIC POLICY
+policy-investment = Investment
+policy-education = Education
+policy-taxation = Taxation
+policy-conscription = Conscription
+policy-level-low = Low
+policy-level-standard = Standard
+policy-level-high = High
+policy-priority-balanced = Balanced
+policy-priority-light = Light
+policy-priority-heavy = Heavy
+policy-priority-advanced = Advanced
+policy-current = { $policy }: { $option }
+policy-reform = { $policy }: { $option } -> { $next } ({ $days ->
+    [one] { $days } day
+   *[other] { $days } days
+})
+
+## Colony details
+population-details = Capacity: { $capacity }
+    Birth rate: { $birth_rate }/year | Death rate: { $death_rate }/year
+    Births: { $births } | Deaths: { $deaths } | Net: { $net }
+economy-output = Output:
+economy-infrastructure = Infrastructure:
+economy-light = Light
+economy-heavy = Heavy
+economy-advanced = Advanced
+economy-amount = { $sector }: { $amount }
+economy-growth = { $sector }: { $amount } (+{ $growth })
+economy-rates = Depreciation rate: { $depreciation }
+    Savings rate: { $savings }
+    Training rate: { $training } | Taxes: { $taxes } | Investing in: { $priority }
+economy-prices = Prices: Light { $light } | Heavy { $heavy } | Advanced { $advanced }
+economy-wages = Wages (per million workers): Basic { $basic } | Engineers { $engineers }
+economy-workers = Basic workers: { $workers }
+economy-engineers = Engineers: { $engineers } (+{ $growth })
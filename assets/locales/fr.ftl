@@ -0,0 +1,78 @@
+# Français
+
+## Placeholders, until a widget fills itself in
+not-available = N/D
+tab-header = En-tête
+tab-details = Détails
+
+## Time widget
+time-date = Date :
+time-speed = Vitesse :
+time-fps = IPS :
+speed-paused = En pause
+speed-slow = Lente
+speed-normal = Normale
+speed-fast = Rapide
+speed-fastest = Très rapide
+
+## Outliners
+empire-systems = SYSTÈMES IMPÉRIAUX
+body-planet = Planète
+body-star = Étoile
+body-colony = ({ $population }, { $type })
+body-size = Taille : { $size } | Ensoleillement : { $insolation }
+body-placeholder = Détails de { $name }
+system-centrality = Centralité : { $centrality }
+system-chokepoint = Goulet
+system-role-frontier = Frontière
+system-role-core = Cœur
+system-border-distance = { $jumps ->
+    [one] ({ $jumps } saut de la frontière)
+   *[other] ({ $jumps } sauts de la frontière)
+}
+
+## History chart
+chart-population = Population
+chart-output = Production
+chart-infrastructure = Infrastructures
+chart-colonies = Colonies
+chart-systems = Systèmes
+chart-title = { $metric } : { $latest } (max { $max })
+
+## Policies
+policy-title = POLITIQUE ÉCONOMIQUE
+policy-investment = Investissement
+policy-education = Éducation
+policy-taxation = Fiscalité
+policy-conscription = Conscription
+policy-level-low = Faible
+policy-level-standard = Normale
+policy-level-high = Élevée
+policy-priority-balanced = Équilibré
+policy-priority-light = Industrie légère
+policy-priority-heavy = Industrie lourde
+policy-priority-advanced = Haute technologie
+policy-current = { $policy } : { $option }
+policy-reform = { $policy } : { $option } -> { $next } ({ $days ->
+    [one] { $days } jour
+   *[other] { $days } jours
+})
+
+## Colony details
+population-details = Capacité : { $capacity }
+    Natalité : { $birth_rate }/an | Mortalité : { $death_rate }/an
+    Naissances : { $births } | Décès : { $deaths } | Solde : { $net }
+economy-output = Production :
+economy-infrastructure = Infrastructures :
+economy-light = Légère
+economy-heavy = Lourde
+economy-advanced = Avancée
+economy-amount = { $sector } : { $amount }
+economy-growth = { $sector } : { $amount } (+{ $growth })
+economy-rates = Taux d'amortissement : { $depreciation }
+    Taux d'épargne : { $savings }
+    Taux de formation : { $training } | Impôts : { $taxes } | Investissement : { $priority }
+economy-prices = Prix : Légère { $light } | Lourde { $heavy } | Avancée { $advanced }
+economy-wages = Salaires (par million de travailleurs) : Ouvriers { $basic } | Ingénieurs { $engineers }
+economy-workers = Ouvriers : { $workers }
+economy-engineers = Ingénieurs : { $engineers } (+{ $growth })
@@ -11,7 +11,8 @@ struct StarFormat {
 }
 struct LaneFormat {
     enabled : u32,
-    col : vec3<f32>
+    col : vec3<f32>,
+    lane_type : u32
 }
 @group(2) @binding(1) var<storage> star_data_array: array<StarFormat>;
 @group(2) @binding(2) var<storage> lane_data_array: array<LaneFormat>;
@@ -250,7 +251,7 @@ fn fragment(input: FragmentInput) -> @location(0) vec4<f32> {
 
     var hyperlane_dist = 10000.f;
 
-    var lane = LaneFormat(0,vec3(0.0));    
+    var lane = LaneFormat(0,vec3(0.0),0);    
 
     let hyperlane_w = 0.1;
     let hyperlane_offset = 12.0;
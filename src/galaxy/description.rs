@@ -11,6 +11,8 @@ use super::{Colony, Empire};
 pub fn update_descriptions_system(
     mut colony_query: Query<(&mut Description, &Colony), Changed<Colony>>,
     mut star_query: Query<(&mut Description, &StarClaim), (Changed<StarClaim>, Without<Colony>)>,
+    mut abandoned_query: Query<&mut Description, (Without<Colony>, Without<StarClaim>)>,
+    mut removed_colonies: RemovedComponents<Colony>,
     empire_query: Query<&Empire>,
 ) {
     for (mut description, colony) in colony_query.iter_mut() {
@@ -19,7 +21,11 @@ pub fn update_descriptions_system(
         } else {
             description.empire_color = None;
         }
-        // todo.. account for colony abandonment
+    }
+    for entity in removed_colonies.read() {
+        if let Ok(mut description) = abandoned_query.get_mut(entity) {
+            description.empire_color = None;
+        }
     }
     for (mut description, starclaim) in star_query.iter_mut() {
         if let Some(owner) = starclaim.owner {
@@ -29,8 +35,6 @@ pub fn update_descriptions_system(
         } else {
             description.empire_color = None;
         }
-
-        // todo.. account for colony abandonment
     }
 }
 
@@ -57,10 +61,11 @@ impl Description {
         }
     }
 
-    pub fn type_name(&self) -> &str {
+    // Message id for the name of the type
+    pub fn type_name(&self) -> &'static str {
         match self.describable_type {
-            DescribableType::Planet => "Planet",
-            DescribableType::Star => "Star",
+            DescribableType::Planet => "body-planet",
+            DescribableType::Star => "body-star",
         }
     }
     pub fn type_color(&self) -> Color {
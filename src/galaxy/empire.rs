@@ -1,10 +1,16 @@
 use super::indexes::EmpireIndex;
 use super::navigation_filter::NavigationMask;
 use crate::prelude::*;
+use crate::simulation::economy::market::Market;
+use crate::simulation::history::History;
+use crate::simulation::mission::survey::SurveyedSystems;
+use crate::simulation::policy::EconomicPolicies;
+use crate::simulation::supply::SupplyNetwork;
+use crate::simulation::threat::ThreatMap;
 use bevy::prelude::*;
 use rand::prelude::*;
 
-use crate::generators::markov_chain::{PlanetNameGenerator, UsedPlanetNames};
+use crate::generators::markov_chain::{NameRng, PlanetNameGenerator, UsedPlanetNames};
 
 #[derive(Component)]
 pub struct Empire {
@@ -13,10 +19,24 @@ pub struct Empire {
     pub namegen: PlanetNameGenerator,
 }
 
+// Relations with other empires, used to work out which of their systems our fleets can cross
+#[derive(Component, Default)]
+pub struct Diplomacy {
+    pub at_war: Vec<Entity>,
+    pub grants_access: Vec<Entity>, // Empires whose fleets are allowed through our territory
+}
+
 #[derive(Bundle)]
 pub struct EmpireBundle {
     empire: Empire,
+    diplomacy: Diplomacy,
     nav_mask: NavigationMask,
+    supply: SupplyNetwork,
+    threat: ThreatMap,
+    surveyed: SurveyedSystems,
+    market: Market,
+    policies: EconomicPolicies,
+    history: History,
     empire_index: EmpireIndex,
 }
 
@@ -24,22 +44,40 @@ impl Empire {
     pub fn random(
         rng: &mut ThreadRng,
         hypernet: &Hypernet,
+        mut namegen: PlanetNameGenerator,
         used_planet_names: &mut UsedPlanetNames,
+        name_rng: &mut NameRng,
     ) -> EmpireBundle {
-        let mut namegen = PlanetNameGenerator::new(used_planet_names);
-
         EmpireBundle {
             empire: Self {
                 color: Color::srgb(rng.random(), rng.random(), rng.random()),
-                name: namegen.next(used_planet_names),
+                name: namegen.next(used_planet_names, &mut name_rng.0),
                 namegen,
             },
+            diplomacy: default(),
             nav_mask: NavigationMask::new(hypernet, true),
+            supply: default(),
+            threat: default(),
+            surveyed: default(),
+            market: default(),
+            policies: default(),
+            history: default(),
             empire_index: default(),
         }
     }
 }
 
+#[cfg(test)]
+impl Empire {
+    pub fn named(name: &str) -> Self {
+        Self {
+            color: Color::WHITE,
+            name: name.to_string(),
+            namegen: PlanetNameGenerator::test(),
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct PlayerEmpire {
     pub empire: Option<Entity>,
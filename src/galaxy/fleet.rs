@@ -6,6 +6,7 @@ use bevy::prelude::*;
 #[derive(Component)]
 pub struct Fleet {
     pub owner: Entity,
+    pub name: String, // Given by the owner's name generator once spawned
     pub time_since_last_jump: u32,
 }
 
@@ -13,6 +14,47 @@ pub struct Fleet {
 pub struct FleetColonyCrew {
     pub colonists: i64,
     pub destination: Option<Entity>,
+    // Targets the ship gave up on, with the (owner, claimed tick) of their system at the time
+    // They aren't picked again until that claim changes
+    pub rejected: Vec<(Entity, Option<Entity>, i64)>,
+}
+
+#[derive(Component)]
+pub struct FleetTroops {
+    pub troops: i64,
+    pub destination: Option<Entity>, // Enemy colony
+}
+
+// Scouts and patrols, raised for a single mission and kept in one system until it's done
+#[derive(Component)]
+pub struct FleetStation {
+    pub system: u32,
+    pub duty: StationDuty,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StationDuty {
+    Survey,
+    Patrol,
+}
+
+// Fleets wear down while outside their empire's supply network
+#[derive(Component)]
+pub struct FleetSupply {
+    pub readiness: i32, // 0 to MAX_READINESS
+}
+
+impl FleetSupply {
+    pub const MAX_READINESS: i32 = 1000;
+
+    // Worn down fleets travel at down to half speed
+    pub fn speed_percent(&self) -> i32 {
+        50 + self.readiness * 50 / Self::MAX_READINESS
+    }
+
+    pub fn combat_effectiveness(&self) -> IPercent {
+        IPercent::new(250 + self.readiness * 3 / 4)
+    }
 }
 
 #[derive(Component, Default)]
@@ -25,6 +67,7 @@ pub struct FleetBundle {
     fleet: Fleet,
     nav_position: NavPosition,
     navigator: Navigator,
+    supply: FleetSupply,
     selectable: SystemSelectable,
 }
 
@@ -34,6 +77,7 @@ impl FleetBundle {
             fleet: Fleet {
                 time_since_last_jump: 0,
                 owner,
+                name: String::new(),
             },
             nav_position: NavPosition {
                 root_system: system,
@@ -41,11 +85,15 @@ impl FleetBundle {
             },
             navigator: Navigator {
                 plan_queue: Vec::new(),
+                route: None,
                 action: Action::Idle,
                 stranded_go_home: false,
                 speed: GalaxyConfig::AU_SCALE * 0.5,
                 hyperspeed: 10000,
             },
+            supply: FleetSupply {
+                readiness: FleetSupply::MAX_READINESS,
+            },
             selectable: SystemSelectable {
                 radius: GalaxyConfig::SOLAR_RADIUS * 5.0,
             },
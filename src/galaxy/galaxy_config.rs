@@ -1,10 +1,17 @@
+use super::hypernet_generation::{HypernetPreset, LaneGenerationSettings};
+use crate::simulation::vizier::{AiDifficulty, AiOverride, AiPersonality};
 use bevy::prelude::*;
+use rand::prelude::*;
 
 #[derive(Resource)]
 pub struct GalaxyConfig {
     pub radius: f32,
     pub max_stars: i32,
     pub spacing: f32,
+    pub lanes: LaneGenerationSettings,
+    pub hierarchical_pathfinding: bool,
+    pub ai_difficulty: AiDifficulty,
+    pub ai_overrides: Vec<AiOverride>,
 }
 
 // NOTE
@@ -23,6 +30,60 @@ impl GalaxyConfig {
     pub const HYPERLANE_VISUAL_STAR_CLEARANCE: f32 = 10.0;
 
     pub const GALACTIC_INTEGER_SCALE: i32 = 10000;
+
+    // Applies galaxy settings passed on the command line
+    // --hypernet <classic|open|sparse|labyrinth|chokepoints>
+    // --lane-degree <target average number of lanes per system>
+    // --hierarchical-pathfinding
+    // --ai-difficulty <easy|normal|hard>
+    // --ai <empire>=<easy|normal|hard>[,<balanced|expansionist|mercantile|cautious|aggressive>], repeatable
+    pub fn with_args(mut self, args: &[String]) -> Self {
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--hypernet" => {
+                    if let Some(preset) = iter.next().and_then(|x| HypernetPreset::from_name(x)) {
+                        self.lanes = preset.settings();
+                    } else {
+                        warn!("--hypernet: unknown preset, using default");
+                    }
+                }
+                "--lane-degree" => {
+                    if let Some(degree) = iter.next().and_then(|x| x.parse::<f32>().ok()) {
+                        self.lanes.target_average_degree = Some(degree);
+                    }
+                }
+                "--hierarchical-pathfinding" => self.hierarchical_pathfinding = true,
+                "--ai-difficulty" => {
+                    if let Some(difficulty) = iter.next().and_then(|x| AiDifficulty::from_name(x)) {
+                        self.ai_difficulty = difficulty;
+                    } else {
+                        warn!("--ai-difficulty: unknown difficulty, using normal");
+                    }
+                }
+                "--ai" => {
+                    if let Some(ai) = iter.next().and_then(|x| AiOverride::from_arg(x)) {
+                        self.ai_overrides.retain(|x| x.empire != ai.empire);
+                        self.ai_overrides.push(ai);
+                    } else {
+                        warn!("--ai: expected <empire>=<difficulty>[,<personality>], ignored");
+                    }
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+
+    // Difficulty and personality of the "empire"th empire placed, falling back to the global difficulty and a random personality
+    pub fn ai_settings(&self, empire: usize, rng: &mut impl Rng) -> (AiDifficulty, AiPersonality) {
+        let ai = self.ai_overrides.iter().find(|x| x.empire == empire);
+        (
+            ai.map_or(self.ai_difficulty, |x| x.difficulty),
+            ai.and_then(|x| x.personality)
+                .unwrap_or_else(|| AiPersonality::random(rng)),
+        )
+    }
 }
 impl Default for GalaxyConfig {
     fn default() -> Self {
@@ -30,6 +91,70 @@ impl Default for GalaxyConfig {
             radius: 500.0, // in parsecs
             max_stars: 1000,
             spacing: 40.0,
+            lanes: LaneGenerationSettings::default(),
+            hierarchical_pathfinding: false,
+            ai_difficulty: AiDifficulty::Normal,
+            ai_overrides: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn overrides_ai_per_empire() {
+        let config = GalaxyConfig::default().with_args(&args(
+            "--ai-difficulty easy --ai 2=hard --ai 3=normal,aggressive --ai 2=hard,cautious --ai 4=tough --ai 5",
+        ));
+        assert_eq!(config.ai_difficulty, AiDifficulty::Easy);
+        assert_eq!(
+            config.ai_overrides,
+            [
+                AiOverride {
+                    empire: 3,
+                    difficulty: AiDifficulty::Normal,
+                    personality: AiPersonality::from_name("aggressive"),
+                },
+                AiOverride {
+                    empire: 2,
+                    difficulty: AiDifficulty::Hard,
+                    personality: AiPersonality::from_name("cautious"),
+                },
+            ]
+        );
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            config.ai_settings(3, &mut rng),
+            (
+                AiDifficulty::Normal,
+                AiPersonality::from_name("aggressive").unwrap()
+            )
+        );
+        let (difficulty, personality) = config.ai_settings(1, &mut rng);
+        assert_eq!(difficulty, AiDifficulty::Easy);
+        assert!((IPercent::new(500)..=IPercent::new(1500)).contains(&personality.expansion));
+    }
+
+    #[test]
+    fn rejects_malformed_overrides() {
+        for arg in [
+            "",
+            "1",
+            "x=hard",
+            "1=",
+            "1=hard,",
+            "1=hard,reckless",
+            "=hard",
+        ] {
+            assert_eq!(AiOverride::from_arg(arg), None, "{}", arg);
         }
     }
 }
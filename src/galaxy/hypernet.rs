@@ -1,3 +1,4 @@
+use super::hypernet_generation::{LaneGenerationSettings, LaneTopology};
 use super::hypernet_pathfinding::Pathfinding;
 use crate::prelude::*;
 use bevy::prelude::*;
@@ -18,22 +19,105 @@ impl Hypernode {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LaneType {
+    Standard,
+    Wormhole, // Long-range link between distant systems, crossing the regular lane network
+    Gateway(Entity), // Built by an empire. Only the builder's fleets get the speed bonus
+    Hazardous, // Slow, and wears down anything travelling through it
+}
+
 #[derive(Clone)]
 pub struct Hyperlane {
     pub length: i32,
+    pub lane_type: LaneType,
+}
+
+impl Hyperlane {
+    // The fastest a fleet can travel through any lane type. Keeps the pathfinding heuristic admissible
+    pub const MAX_SPEED_PERCENT: i32 = 1000;
+
+    pub fn new(length: i32) -> Self {
+        Self {
+            length,
+            lane_type: LaneType::Standard,
+        }
+    }
+
+    // Travel speed through the lane relative to a standard lane, for a fleet belonging to "empire"
+    pub fn speed_percent(&self, empire: Option<Entity>) -> i32 {
+        match self.lane_type {
+            LaneType::Standard => 100,
+            LaneType::Wormhole => Self::MAX_SPEED_PERCENT,
+            LaneType::Gateway(owner) => {
+                if Some(owner) == empire {
+                    300
+                } else {
+                    100
+                }
+            }
+            LaneType::Hazardous => 50,
+        }
+    }
+
+    // Effective length of the lane for pathfinding, accounting for travel speed
+    pub fn traversal_cost(&self, empire: Option<Entity>) -> i32 {
+        self.length * 100 / self.speed_percent(empire)
+    }
+
+    // Share of a fleet's crew lost crossing the lane, however long that takes
+    pub fn attrition_rate(&self) -> IPercent {
+        match self.lane_type {
+            LaneType::Hazardous => IPercent::new(2),
+            _ => IPercent::new(0),
+        }
+    }
 }
 
 #[derive(Resource)]
 pub struct Hypernet {
     pub graph: StableGraph<Hypernode, Hyperlane, Undirected, u32>,
+    fastest_lane_percent: i32,
 }
 impl Hypernet {
     pub fn new() -> Self {
         Self {
             graph: StableGraph::<_, _, Undirected>::default(),
+            fastest_lane_percent: 100,
         }
     }
 
+    // Lower bound on the traversal cost between two points, used as the A* heuristic
+    // (Straight line distance, at the speed of the fastest lane present in the network)
+    pub fn estimate_cost(&self, a: Vec3, b: Vec3) -> i32 {
+        let d = (a.distance(b) * GalaxyConfig::GALACTIC_INTEGER_SCALE as f32) as i64;
+        (d * 100 / self.fastest_lane_percent as i64) as i32
+    }
+
+    pub fn set_lane_type(&mut self, lane: u32, lane_type: LaneType) {
+        let Some(weight) = self.graph.edge_weight_mut(lane.into()) else {
+            return;
+        };
+        weight.lane_type = lane_type;
+        self.fastest_lane_percent = self
+            .fastest_lane_percent
+            .max(weight.speed_percent(None))
+            .max(match lane_type {
+                LaneType::Gateway(owner) => weight.speed_percent(Some(owner)),
+                _ => 100,
+            });
+    }
+
+    // Turns an existing lane into a gateway owned by the empire, or links the systems with a new one
+    pub fn build_gateway(&mut self, a: u32, b: u32, empire: Entity) -> u32 {
+        let lane = match self.graph.find_edge(a.into(), b.into()) {
+            Some(lane) => lane.index() as u32,
+            None => self.add_lane(a, b),
+        };
+        self.set_lane_type(lane, LaneType::Gateway(empire));
+        lane
+    }
+
     pub fn num_lanes(&self) -> i32 {
         self.graph.edge_count() as i32
     }
@@ -41,15 +125,30 @@ impl Hypernet {
         self.graph.node_weight(id.into()).unwrap().star.unwrap()
     }
 
-    pub fn build_from_points(
-        &mut self,
-        points: &Vec<Point>,
-        length_remove_threshold: f32,
-        removal_rate: f32,
-    ) {
+    pub fn build_from_points(&mut self, points: &Vec<Point>, settings: &LaneGenerationSettings) {
         self.import(points);
-        self.remove_over_length(length_remove_threshold);
-        self.remove_random((self.graph.edge_count() as f32 * removal_rate) as u32, 12);
+        let mut candidates = self.lane_candidates();
+
+        match settings.topology {
+            LaneTopology::DelaunayPruned {
+                length_remove_threshold,
+                removal_rate,
+            } => {
+                self.remove_over_length(length_remove_threshold);
+                self.remove_random(
+                    (self.graph.edge_count() as f32 * removal_rate) as u32,
+                    settings.max_new_distance,
+                );
+            }
+            topology => candidates = self.rebuild_lanes(topology, candidates),
+        }
+
+        if let Some(target) = settings.target_average_degree {
+            self.adjust_average_degree(target, settings.max_new_distance, &candidates);
+        }
+
+        self.place_wormholes(settings.wormholes);
+        self.place_hazards(settings.hazardous_lane_rate);
 
         // clear out the dead nodes
         // lazy method but it's not a hot loop
@@ -57,6 +156,20 @@ impl Hypernet {
         self.graph = StableGraph::<Hypernode, Hyperlane, Undirected, u32>::from(temp);
     }
 
+    pub(super) fn add_lane(&mut self, a: u32, b: u32) -> u32 {
+        let a_pos = self.graph.node_weight(a.into()).unwrap().pos;
+        let b_pos = self.graph.node_weight(b.into()).unwrap().pos;
+        self.graph
+            .add_edge(
+                a.into(),
+                b.into(),
+                Hyperlane::new(
+                    (GalaxyConfig::GALACTIC_INTEGER_SCALE as f32 * a_pos.distance(b_pos)) as i32,
+                ),
+            )
+            .index() as u32
+    }
+
     fn import(&mut self, points: &Vec<Point>) {
         let del = triangulate(points);
 
@@ -75,16 +188,7 @@ impl Hypernet {
             if hb != EMPTY {
                 let b = del.triangles[hb] as u32;
                 if a < b {
-                    let a_pos = self.graph.node_weight(a.into()).unwrap().pos;
-                    let b_pos = self.graph.node_weight(b.into()).unwrap().pos;
-                    self.graph.add_edge(
-                        a.into(),
-                        b.into(),
-                        Hyperlane {
-                            length: (GalaxyConfig::GALACTIC_INTEGER_SCALE as f32
-                                * a_pos.distance(b_pos)) as i32,
-                        },
-                    );
+                    self.add_lane(a, b);
                 }
             }
         }
@@ -104,7 +208,7 @@ impl Hypernet {
         });
     }
 
-    fn get_distance_without_link(&self, a: u32, b: u32) -> Option<usize> {
+    pub(super) fn get_distance_without_link(&self, a: u32, b: u32) -> Option<usize> {
         let path = self.find_path_without_direct_edge(a, b);
 
         if let Some(path) = path {
@@ -150,7 +254,7 @@ impl Hypernet {
     // Remove a number of random edges without splitting the graph
     // at each iteration, the removed edge cannot result in a new distance between its vertices > max_new_distance
     // The distance restriction isn't perfect (future iterations can & will increase the distance between previously split vertices) but it's better than nothing
-    fn remove_random(&mut self, number: u32, max_new_distance: u32) {
+    pub(super) fn remove_random(&mut self, number: u32, max_new_distance: u32) {
         let mut candidate_edges = self.graph.edge_indices().collect::<Vec<_>>();
 
         let mut i = 0;
@@ -175,3 +279,52 @@ impl Hypernet {
         }
     }
 }
+
+#[cfg(test)]
+impl Hypernet {
+    // Systems at the given positions on the galactic plane, in parsecs, joined by standard lanes
+    pub fn from_lanes(positions: &[(f32, f32)], lanes: &[(u32, u32)]) -> Self {
+        let mut hypernet = Self::new();
+        for (x, z) in positions {
+            hypernet
+                .graph
+                .add_node(Hypernode::new(Vec3::new(*x, 0.0, *z)));
+        }
+        for (a, b) in lanes {
+            hypernet.add_lane(*a, *b);
+        }
+        hypernet
+    }
+
+    // Unclaimed, unpopulated stars for every system, registered like the galaxy generator does
+    pub fn spawn_stars(&mut self, world: &mut World) -> Vec<Entity> {
+        let mut galaxy_index = GalaxyIndex::default();
+        let nodes = self.graph.node_indices().collect::<Vec<_>>();
+        nodes
+            .into_iter()
+            .map(|node| {
+                let entity = world
+                    .spawn((
+                        Star {
+                            pos: self.graph[node].pos,
+                            node_id: node.index() as u32,
+                            orbiters: Vec::new(),
+                            mass: 1.0,
+                            name: format!("Star {}", node.index()),
+                        },
+                        StarClaim {
+                            owner: None,
+                            claimed_tick: 0,
+                            contested_by: None,
+                        },
+                        SystemIndex::default(),
+                    ))
+                    .id();
+                let handle = galaxy_index.register_star(entity, node.index());
+                world.entity_mut(entity).insert(handle);
+                self.graph[node].star = Some(handle);
+                entity
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,306 @@
+use super::hypernet_metrics::{adjacency, cut_structure};
+use crate::prelude::*;
+use bevy::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+// Systems at least this central count as chokepoints, even if the network doesn't split without them
+const CHOKEPOINT_BETWEENNESS: i32 = 400;
+// Owned systems within this many jumps of a system the empire doesn't own are frontier, the rest are core
+const FRONTIER_DEPTH: u32 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SystemRole {
+    Frontier,
+    Core,
+}
+
+// Strategic shape of the hypernet, for the AI, overlays and interface
+// The topology part is computed once after generation. The territorial part follows the star claims
+// Like the metrics, path lengths are counted in jumps
+#[derive(Resource)]
+pub struct HypernetAnalysis {
+    adjacency: Vec<Vec<u32>>,
+    articulation: Vec<bool>,
+    bridges: Vec<u32>,     // Lane ids
+    betweenness: Vec<i32>, // 0 to 1000, relative to the most central system
+    // Per empire, jumps from each owned system to the nearest system it doesn't own
+    // 0 for systems it doesn't own, u32::MAX for owned systems cut off from everything else
+    border_distance: HashMap<Entity, Vec<u32>>,
+}
+
+impl HypernetAnalysis {
+    pub fn compute(hypernet: &Hypernet) -> Self {
+        let adjacency = adjacency(hypernet);
+        let (articulation_points, bridge_pairs) = cut_structure(&adjacency);
+
+        let mut articulation = vec![false; adjacency.len()];
+        for x in articulation_points {
+            articulation[x as usize] = true;
+        }
+        let bridges = bridge_pairs
+            .iter()
+            .filter_map(|(a, b)| hypernet.graph.find_edge((*a).into(), (*b).into()))
+            .map(|x| x.index() as u32)
+            .collect();
+        let betweenness = betweenness(&adjacency);
+
+        Self {
+            adjacency,
+            articulation,
+            bridges,
+            betweenness,
+            border_distance: HashMap::new(),
+        }
+    }
+
+    pub fn is_articulation(&self, system: u32) -> bool {
+        self.articulation[system as usize]
+    }
+
+    pub fn bridges(&self) -> &[u32] {
+        &self.bridges
+    }
+
+    pub fn betweenness(&self, system: u32) -> IPercent {
+        IPercent::new(self.betweenness[system as usize])
+    }
+
+    pub fn is_chokepoint(&self, system: u32) -> bool {
+        self.is_articulation(system) || self.betweenness[system as usize] >= CHOKEPOINT_BETWEENNESS
+    }
+
+    // None if the empire doesn't own the system, or it's cut off from anything it doesn't own
+    pub fn border_distance(&self, empire: Entity, system: u32) -> Option<u32> {
+        let d = *self.border_distance.get(&empire)?.get(system as usize)?;
+        (d != 0 && d != u32::MAX).then_some(d)
+    }
+
+    // None if the empire doesn't own the system
+    pub fn role(&self, empire: Entity, system: u32) -> Option<SystemRole> {
+        let d = *self.border_distance.get(&empire)?.get(system as usize)?;
+        match d {
+            0 => None,
+            d if d <= FRONTIER_DEPTH => Some(SystemRole::Frontier),
+            _ => Some(SystemRole::Core),
+        }
+    }
+
+    // "owners" is the owner of each system, indexed by node id
+    fn update_territory(&mut self, owners: &[Option<Entity>]) {
+        self.border_distance.clear();
+        let mut empires = owners.iter().flatten().copied().collect::<Vec<_>>();
+        empires.sort();
+        empires.dedup();
+
+        for empire in empires {
+            // Multi-source BFS from every system the empire doesn't own
+            // Owned systems that are never reached keep u32::MAX
+            let n = self.adjacency.len();
+            let mut dist = vec![u32::MAX; n];
+            let mut queue = VecDeque::new();
+            for x in 0..n {
+                if owners[x] != Some(empire) {
+                    dist[x] = 0;
+                    if !self.adjacency[x].is_empty() {
+                        queue.push_back(x as u32);
+                    }
+                }
+            }
+            while let Some(top) = queue.pop_front() {
+                let d = dist[top as usize];
+                for next in &self.adjacency[top as usize] {
+                    if dist[*next as usize] == u32::MAX {
+                        dist[*next as usize] = d + 1;
+                        queue.push_back(*next);
+                    }
+                }
+            }
+            self.border_distance.insert(empire, dist);
+        }
+    }
+}
+
+// Brandes' algorithm on the unweighted network. O(systems * lanes), fine at generation time
+fn betweenness(adjacency: &[Vec<u32>]) -> Vec<i32> {
+    let n = adjacency.len();
+    let mut centrality = vec![0.0f64; n];
+
+    let mut dist = vec![u32::MAX; n];
+    let mut paths = vec![0u64; n];
+    let mut dependency = vec![0.0f64; n];
+    let mut order = Vec::with_capacity(n);
+    let mut queue = VecDeque::new();
+
+    for source in (0..n).filter(|x| !adjacency[*x].is_empty()) {
+        dist.fill(u32::MAX);
+        paths.fill(0);
+        dependency.fill(0.0);
+        order.clear();
+
+        dist[source] = 0;
+        paths[source] = 1;
+        queue.push_back(source as u32);
+        while let Some(top) = queue.pop_front() {
+            order.push(top);
+            for next in &adjacency[top as usize] {
+                let next = *next as usize;
+                if dist[next] == u32::MAX {
+                    dist[next] = dist[top as usize] + 1;
+                    queue.push_back(next as u32);
+                }
+                if dist[next] == dist[top as usize] + 1 {
+                    paths[next] = paths[next].saturating_add(paths[top as usize]);
+                }
+            }
+        }
+
+        // Walk back from the furthest systems, handing each one's share of paths to its predecessors
+        for top in order.iter().rev() {
+            let top = *top as usize;
+            for prev in &adjacency[top] {
+                let prev = *prev as usize;
+                if dist[prev] != u32::MAX && dist[prev] + 1 == dist[top] {
+                    dependency[prev] +=
+                        paths[prev] as f64 / paths[top] as f64 * (1.0 + dependency[top]);
+                }
+            }
+            if top != source {
+                centrality[top] += dependency[top];
+            }
+        }
+    }
+
+    let max = centrality.iter().copied().fold(0.0, f64::max);
+    centrality
+        .iter()
+        .map(|x| {
+            if max > 0.0 {
+                (x / max * 1000.0) as i32
+            } else {
+                0
+            }
+        })
+        .collect()
+}
+
+// Territory changes on the tick star claims do. A full update is a BFS per empire, cheap enough not to bother doing it incrementally
+pub fn update_hypernet_analysis_system(
+    star_query: Query<(&Star, Ref<StarClaim>)>,
+    analysis: Option<ResMut<HypernetAnalysis>>,
+) {
+    let Some(mut analysis) = analysis else {
+        return;
+    };
+    if !analysis.is_added() && !star_query.iter().any(|(_, claim)| claim.is_changed()) {
+        return;
+    }
+
+    let mut owners = vec![None; analysis.adjacency.len()];
+    for (star, claim) in &star_query {
+        owners[star.node_id as usize] = claim.owner;
+    }
+    analysis.update_territory(&owners);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(n: u32) -> Hypernet {
+        let positions = (0..n).map(|i| (i as f32 * 10.0, 0.0)).collect::<Vec<_>>();
+        let lanes = (1..n).map(|i| (i - 1, i)).collect::<Vec<_>>();
+        Hypernet::from_lanes(&positions, &lanes)
+    }
+
+    fn all_betweenness(analysis: &HypernetAnalysis, n: u32) -> Vec<i32> {
+        (0..n).map(|x| analysis.betweenness(x).value()).collect()
+    }
+
+    #[test]
+    fn measures_centrality() {
+        let analysis = HypernetAnalysis::compute(&line(5));
+        assert_eq!(all_betweenness(&analysis, 5), [0, 750, 1000, 750, 0]);
+
+        // A hub and its spokes
+        let positions = (0..5).map(|i| (i as f32 * 10.0, 0.0)).collect::<Vec<_>>();
+        let analysis = HypernetAnalysis::compute(&Hypernet::from_lanes(
+            &positions,
+            &[(0, 1), (0, 2), (0, 3), (0, 4)],
+        ));
+        assert_eq!(all_betweenness(&analysis, 5), [1000, 0, 0, 0, 0]);
+
+        // Nothing stands out on a loop
+        let ring = (0..6).map(|i| (i, (i + 1) % 6)).collect::<Vec<_>>();
+        let analysis = HypernetAnalysis::compute(&Hypernet::from_lanes(&[(0.0, 0.0); 6], &ring));
+        assert_eq!(all_betweenness(&analysis, 6), [1000; 6]);
+        assert!((0..6).all(|x| !analysis.is_articulation(x)));
+    }
+
+    // Two triangles joined by a lane, a dead end off the second, and a lone system
+    #[test]
+    fn finds_chokepoints() {
+        let lanes = [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 5),
+            (3, 4),
+            (4, 5),
+            (5, 3),
+            (5, 6),
+        ];
+        let analysis = HypernetAnalysis::compute(&Hypernet::from_lanes(&[(0.0, 0.0); 8], &lanes));
+
+        // 11 of the routes between other systems go through 5, 8 through 2
+        assert_eq!(all_betweenness(&analysis, 8), [0, 0, 727, 0, 0, 1000, 0, 0]);
+        let chokepoints = (0..8)
+            .filter(|x| analysis.is_chokepoint(*x))
+            .collect::<Vec<_>>();
+        assert_eq!(chokepoints, [2, 5]);
+        let mut bridges = analysis.bridges().to_vec();
+        bridges.sort();
+        assert_eq!(bridges, [3, 7]);
+    }
+
+    // Owners of the first systems, the rest of the graph's capacity unclaimed
+    fn claim(analysis: &mut HypernetAnalysis, claims: &[Option<Entity>]) {
+        let mut owners = vec![None; analysis.adjacency.len()];
+        owners[..claims.len()].copy_from_slice(claims);
+        analysis.update_territory(&owners);
+    }
+
+    #[test]
+    fn tells_frontier_from_core() {
+        let mut analysis = HypernetAnalysis::compute(&line(6));
+        let mut world = World::new();
+        let (us, them) = (world.spawn_empty().id(), world.spawn_empty().id());
+        claim(
+            &mut analysis,
+            &[Some(them), Some(us), Some(us), Some(us), Some(us), None],
+        );
+
+        let distances = (0..6)
+            .map(|x| analysis.border_distance(us, x))
+            .collect::<Vec<_>>();
+        assert_eq!(distances, [None, Some(1), Some(2), Some(2), Some(1), None]);
+        let roles = (0..6).map(|x| analysis.role(us, x)).collect::<Vec<_>>();
+        use SystemRole::*;
+        assert_eq!(
+            roles,
+            [
+                None,
+                Some(Frontier),
+                Some(Core),
+                Some(Core),
+                Some(Frontier),
+                None
+            ]
+        );
+        assert_eq!(analysis.role(them, 0), Some(Frontier));
+
+        // Owning everything leaves no border to measure from
+        claim(&mut analysis, &[Some(us); 6]);
+        assert_eq!(analysis.border_distance(us, 2), None);
+        assert_eq!(analysis.border_distance(them, 0), None);
+    }
+}
@@ -0,0 +1,530 @@
+use super::hypernet::LaneType;
+use super::hypernet_pathfinding::Pathfinding;
+use super::Hypernet;
+use bevy::prelude::*;
+use petgraph::unionfind::UnionFind;
+use rand::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+// HYPERLANE TOPOLOGY
+//
+// Every strategy starts from the Delaunay triangulation of the star positions (minus the hull, see Hypernet::import)
+// and picks a subset of its edges as hyperlanes. Because they only ever select from the triangulation, lanes never cross.
+//
+// Connectivity guarantee:
+//  - DelaunayPruned only removes an edge if there is still another route between its endpoints
+//  - Every other strategy finishes with a reconnection pass, which adds back the shortest candidate edges that join separate components
+//    (ie. Kruskal over the leftover candidates), so the result always spans every star that the triangulation connected.
+//
+// Special lanes:
+//  - Wormholes are added last, linking random pairs of systems that are far apart on the lane network
+//  - A share of the regular lanes is then marked hazardous
+//
+// Target average degree:
+//  - If the strategy produces too few lanes, the shortest unused candidates are added back
+//  - If it produces too many, random lanes are removed without splitting the graph (same method as DelaunayPruned, and the same max_new_distance)
+
+type Lane = (u32, u32);
+// (node a, node b, length)
+pub(super) type LaneCandidate = (u32, u32, i32);
+
+#[derive(Clone, Copy, Debug)]
+pub enum LaneTopology {
+    /// The original generator: the Delaunay triangulation, with over-long and then random lanes pruned
+    DelaunayPruned {
+        length_remove_threshold: f32, // As a factor of the mean lane length
+        removal_rate: f32,            // Fraction of remaining lanes to try to remove
+    },
+    /// Lanes a-b where no other star is closer to both a and b than they are to each other
+    RelativeNeighbourhood,
+    /// Lanes a-b where no other star lies in the circle with a-b as its diameter
+    Gabriel,
+    /// Minimum spanning tree, plus a number of random extra lanes to create loops
+    SpanningTreePlus { extra_edges: u32 },
+    /// Well-connected clusters joined by only a few lanes
+    Chokepoints {
+        clusters: u32,
+        links_per_border: u32, // Number of lanes kept between each pair of adjacent clusters
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LaneGenerationSettings {
+    pub topology: LaneTopology,
+    pub target_average_degree: Option<f32>,
+    pub max_new_distance: u32, // A random lane removal can't make the jump count between the endpoints exceed this
+    pub wormholes: u32,
+    pub hazardous_lane_rate: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HypernetPreset {
+    Classic,
+    Open,
+    Sparse,
+    Labyrinth,
+    Chokepoints,
+}
+
+impl HypernetPreset {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "classic" => Some(Self::Classic),
+            "open" => Some(Self::Open),
+            "sparse" => Some(Self::Sparse),
+            "labyrinth" => Some(Self::Labyrinth),
+            "chokepoints" => Some(Self::Chokepoints),
+            _ => None,
+        }
+    }
+
+    pub fn settings(self) -> LaneGenerationSettings {
+        match self {
+            Self::Classic => LaneGenerationSettings {
+                topology: LaneTopology::DelaunayPruned {
+                    length_remove_threshold: 1.5,
+                    removal_rate: 0.6,
+                },
+                target_average_degree: None,
+                max_new_distance: 12,
+                wormholes: 0,
+                hazardous_lane_rate: 0.0,
+            },
+            Self::Open => LaneGenerationSettings {
+                topology: LaneTopology::Gabriel,
+                target_average_degree: Some(3.5),
+                max_new_distance: 12,
+                wormholes: 4,
+                hazardous_lane_rate: 0.05,
+            },
+            Self::Sparse => LaneGenerationSettings {
+                topology: LaneTopology::RelativeNeighbourhood,
+                target_average_degree: None,
+                max_new_distance: 12,
+                wormholes: 2,
+                hazardous_lane_rate: 0.05,
+            },
+            Self::Labyrinth => LaneGenerationSettings {
+                topology: LaneTopology::SpanningTreePlus { extra_edges: 40 },
+                target_average_degree: None,
+                max_new_distance: 12,
+                wormholes: 0,
+                hazardous_lane_rate: 0.15,
+            },
+            Self::Chokepoints => LaneGenerationSettings {
+                topology: LaneTopology::Chokepoints {
+                    clusters: 10,
+                    links_per_border: 1,
+                },
+                target_average_degree: Some(2.6),
+                max_new_distance: 12,
+                wormholes: 2,
+                hazardous_lane_rate: 0.1,
+            },
+        }
+    }
+}
+
+impl Default for LaneGenerationSettings {
+    fn default() -> Self {
+        HypernetPreset::Classic.settings()
+    }
+}
+
+impl Hypernet {
+    // All lanes of the freshly imported triangulation, shortest first
+    pub(super) fn lane_candidates(&self) -> Vec<LaneCandidate> {
+        let mut candidates = self
+            .graph
+            .edge_indices()
+            .map(|e| {
+                let (a, b) = self.graph.edge_endpoints(e).unwrap();
+                let length = self.graph.edge_weight(e).unwrap().length;
+                (a.index() as u32, b.index() as u32, length)
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(_, _, length)| *length);
+        candidates
+    }
+
+    fn enabled_nodes(&self) -> Vec<u32> {
+        self.graph
+            .node_indices()
+            .filter(|n| self.graph.edges(*n).count() > 0)
+            .map(|n| n.index() as u32)
+            .collect()
+    }
+
+    fn pos(&self, node: u32) -> Vec3 {
+        self.graph.node_weight(node.into()).unwrap().pos
+    }
+
+    // Replaces the imported triangulation with a subset of it chosen by the topology
+    // Returns the candidates that may be added back when topping up the average degree
+    pub(super) fn rebuild_lanes(
+        &mut self,
+        topology: LaneTopology,
+        candidates: Vec<LaneCandidate>,
+    ) -> Vec<LaneCandidate> {
+        let enabled = self.enabled_nodes();
+        let mut cluster_of = None;
+
+        let lanes: Vec<Lane> = match topology {
+            LaneTopology::DelaunayPruned { .. } => {
+                candidates.iter().map(|(a, b, _)| (*a, *b)).collect()
+            }
+            LaneTopology::RelativeNeighbourhood => candidates
+                .iter()
+                .filter(|(a, b, _)| self.is_relative_neighbour(*a, *b, &enabled))
+                .map(|(a, b, _)| (*a, *b))
+                .collect(),
+            LaneTopology::Gabriel => candidates
+                .iter()
+                .filter(|(a, b, _)| self.is_gabriel_neighbour(*a, *b, &enabled))
+                .map(|(a, b, _)| (*a, *b))
+                .collect(),
+            LaneTopology::SpanningTreePlus { extra_edges } => {
+                let (mut tree, mut rest) = self.spanning_tree(&candidates);
+                let mut rng = rand::rng();
+                rest.shuffle(&mut rng);
+                tree.extend(rest.into_iter().take(extra_edges as usize));
+                tree
+            }
+            LaneTopology::Chokepoints {
+                clusters,
+                links_per_border,
+            } => {
+                let clusters = self.assign_clusters(clusters, &enabled);
+                let mut border_links: HashMap<(u32, u32), u32> = HashMap::new();
+                let mut lanes = Vec::new();
+
+                // candidates are sorted, so the shortest links across each border are kept
+                for (a, b, _) in &candidates {
+                    let (ca, cb) = (clusters[*a as usize], clusters[*b as usize]);
+                    if ca == cb {
+                        if self.is_gabriel_neighbour(*a, *b, &enabled) {
+                            lanes.push((*a, *b));
+                        }
+                    } else {
+                        let links = border_links.entry((ca.min(cb), ca.max(cb))).or_insert(0);
+                        if *links < links_per_border {
+                            *links += 1;
+                            lanes.push((*a, *b));
+                        }
+                    }
+                }
+                cluster_of = Some(clusters);
+                lanes
+            }
+        };
+
+        self.graph.clear_edges();
+        for (a, b) in lanes {
+            self.add_lane(a, b);
+        }
+
+        // Guarantee connectivity
+        let (n, _) = self.graph.capacity();
+        let mut components = UnionFind::<u32>::new(n);
+        for e in self.graph.edge_indices() {
+            let (a, b) = self.graph.edge_endpoints(e).unwrap();
+            components.union(a.index() as u32, b.index() as u32);
+        }
+        for (a, b, _) in &candidates {
+            if components.union(*a, *b) {
+                self.add_lane(*a, *b);
+            }
+        }
+
+        // Lanes added by the degree adjustment shouldn't undo the point of the chokepoint topology
+        match cluster_of {
+            Some(cluster_of) => candidates
+                .into_iter()
+                .filter(|(a, b, _)| cluster_of[*a as usize] == cluster_of[*b as usize])
+                .collect(),
+            None => candidates,
+        }
+    }
+
+    pub(super) fn place_wormholes(&mut self, count: u32) {
+        let enabled = self.enabled_nodes();
+        let mut rng = rand::rng();
+        let mut used = HashSet::new();
+
+        for _ in 0..count {
+            let Some(a) = enabled
+                .iter()
+                .filter(|x| !used.contains(*x))
+                .choose(&mut rng)
+                .copied()
+            else {
+                return;
+            };
+
+            // Link to a random system among the furthest quarter of the network
+            let dist = self.dijkstra(&[a]);
+            let mut reachable = enabled
+                .iter()
+                .filter(|x| dist[**x as usize].is_some() && !used.contains(*x))
+                .copied()
+                .collect::<Vec<_>>();
+            reachable.sort_by_key(|x| dist[*x as usize]);
+            let Some(b) = reachable[reachable.len() * 3 / 4..]
+                .choose(&mut rng)
+                .copied()
+            else {
+                return;
+            };
+            if a == b || self.graph.find_edge(a.into(), b.into()).is_some() {
+                continue;
+            }
+
+            used.insert(a);
+            used.insert(b);
+            let lane = self.add_lane(a, b);
+            self.set_lane_type(lane, LaneType::Wormhole);
+        }
+    }
+
+    pub(super) fn place_hazards(&mut self, rate: f32) {
+        let mut rng = rand::rng();
+        for lane in self.graph.edge_indices().collect::<Vec<_>>() {
+            if self.graph.edge_weight(lane).unwrap().lane_type == LaneType::Standard
+                && rng.random::<f32>() < rate
+            {
+                self.set_lane_type(lane.index() as u32, LaneType::Hazardous);
+            }
+        }
+    }
+
+    pub(super) fn adjust_average_degree(
+        &mut self,
+        target: f32,
+        max_new_distance: u32,
+        candidates: &[LaneCandidate],
+    ) {
+        let enabled = self.enabled_nodes().len() as f32;
+        let target_lanes = (target * enabled * 0.5) as usize;
+
+        if self.graph.edge_count() < target_lanes {
+            for (a, b, _) in candidates {
+                if self.graph.edge_count() >= target_lanes {
+                    break;
+                }
+                if self.graph.find_edge((*a).into(), (*b).into()).is_none() {
+                    self.add_lane(*a, *b);
+                }
+            }
+        } else {
+            let excess = self.graph.edge_count() - target_lanes;
+            self.remove_random(excess as u32, max_new_distance);
+        }
+    }
+
+    // Kruskal. Returns (tree edges, unused edges)
+    fn spanning_tree(&self, candidates: &[LaneCandidate]) -> (Vec<Lane>, Vec<Lane>) {
+        let (n, _) = self.graph.capacity();
+        let mut components = UnionFind::<u32>::new(n);
+        let mut tree = Vec::new();
+        let mut rest = Vec::new();
+        for (a, b, _) in candidates {
+            if components.union(*a, *b) {
+                tree.push((*a, *b));
+            } else {
+                rest.push((*a, *b));
+            }
+        }
+        (tree, rest)
+    }
+
+    fn is_relative_neighbour(&self, a: u32, b: u32, witnesses: &[u32]) -> bool {
+        let (pa, pb) = (self.pos(a), self.pos(b));
+        let d = pa.distance_squared(pb);
+        !witnesses.iter().any(|c| {
+            let pc = self.pos(*c);
+            *c != a && *c != b && pc.distance_squared(pa) < d && pc.distance_squared(pb) < d
+        })
+    }
+
+    fn is_gabriel_neighbour(&self, a: u32, b: u32, witnesses: &[u32]) -> bool {
+        let (pa, pb) = (self.pos(a), self.pos(b));
+        let mid = (pa + pb) * 0.5;
+        let r = pa.distance_squared(pb) * 0.25;
+        !witnesses
+            .iter()
+            .any(|c| *c != a && *c != b && self.pos(*c).distance_squared(mid) < r)
+    }
+
+    // Splits the graph into clusters around seeds picked by farthest-point sampling
+    // Returns the cluster index of every node (disabled nodes get u32::MAX)
+    // Must be called while the full triangulation is still present
+    fn assign_clusters(&self, clusters: u32, enabled: &[u32]) -> Vec<u32> {
+        let (n, _) = self.graph.capacity();
+        let mut cluster_of = vec![u32::MAX; n];
+        if enabled.is_empty() {
+            return cluster_of;
+        }
+
+        let mut rng = rand::rng();
+        let mut seeds = vec![*enabled.choose(&mut rng).unwrap()];
+        while (seeds.len() as u32) < clusters.max(1) {
+            let dist = self.dijkstra(&seeds);
+            let Some(next) = enabled
+                .iter()
+                .filter(|x| dist[**x as usize].is_some())
+                .max_by_key(|x| dist[**x as usize])
+            else {
+                break;
+            };
+            if dist[*next as usize] == Some(0) {
+                break;
+            }
+            seeds.push(*next);
+        }
+
+        let mut best = vec![i32::MAX; n];
+        for (i, seed) in seeds.iter().enumerate() {
+            for (node, d) in self.dijkstra(&[*seed]).iter().enumerate() {
+                if let Some(d) = d {
+                    if *d < best[node] {
+                        best[node] = *d;
+                        cluster_of[node] = i as u32;
+                    }
+                }
+            }
+        }
+        cluster_of
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hypernet_metrics::{adjacency, cut_structure};
+    use super::super::HypernetMetrics;
+    use super::*;
+    use delaunator::Point;
+
+    const PRESETS: [HypernetPreset; 5] = [
+        HypernetPreset::Classic,
+        HypernetPreset::Open,
+        HypernetPreset::Sparse,
+        HypernetPreset::Labyrinth,
+        HypernetPreset::Chokepoints,
+    ];
+
+    // Stars scattered over a disc with some clearance between them, the way galaxy generation places them
+    fn scatter(rng: &mut impl Rng) -> Vec<Point> {
+        let mut points: Vec<Point> = Vec::new();
+        while points.len() < 300 {
+            let r = 300.0 * rng.random::<f64>().sqrt();
+            let theta = std::f64::consts::TAU * rng.random::<f64>();
+            let point = Point {
+                x: r * theta.cos(),
+                y: r * theta.sin(),
+            };
+            if points
+                .iter()
+                .all(|x| (x.x - point.x).powi(2) + (x.y - point.y).powi(2) > 20.0 * 20.0)
+            {
+                points.push(point);
+            }
+        }
+        points
+    }
+
+    fn build(points: &[Point], settings: &LaneGenerationSettings) -> Hypernet {
+        let mut hypernet = Hypernet::new();
+        hypernet.build_from_points(&points.to_vec(), settings);
+        hypernet
+    }
+
+    #[test]
+    fn every_preset_is_connected() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for preset in PRESETS {
+            for _ in 0..4 {
+                let metrics =
+                    HypernetMetrics::compute(&build(&scatter(&mut rng), &preset.settings()));
+                assert!(metrics.connected, "{:?}", preset);
+                assert!(metrics.systems > 200, "{:?}", preset);
+            }
+        }
+    }
+
+    // The reconnection pass is all that holds a bare spanning tree or a chokepoint galaxy together
+    #[test]
+    fn reconnects_every_topology() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let points = scatter(&mut rng);
+        let bare = |topology| LaneGenerationSettings {
+            topology,
+            target_average_degree: None,
+            max_new_distance: 12,
+            wormholes: 0,
+            hazardous_lane_rate: 0.0,
+        };
+
+        let tree = build(
+            &points,
+            &bare(LaneTopology::SpanningTreePlus { extra_edges: 0 }),
+        );
+        let metrics = HypernetMetrics::compute(&tree);
+        assert!(metrics.connected);
+        assert_eq!(metrics.lanes, metrics.systems - 1);
+        assert_eq!(cut_structure(&adjacency(&tree)).1.len(), metrics.lanes);
+
+        for topology in [
+            LaneTopology::Chokepoints {
+                clusters: 30,
+                links_per_border: 0,
+            },
+            LaneTopology::RelativeNeighbourhood,
+            LaneTopology::Gabriel,
+        ] {
+            let metrics = HypernetMetrics::compute(&build(&points, &bare(topology)));
+            assert!(metrics.connected, "{:?}", topology);
+        }
+    }
+
+    #[test]
+    fn removals_follow_the_max_new_distance() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let points = scatter(&mut rng);
+        let gabriel = |target_average_degree, max_new_distance| LaneGenerationSettings {
+            topology: LaneTopology::Gabriel,
+            target_average_degree,
+            max_new_distance,
+            wormholes: 0,
+            hazardous_lane_rate: 0.0,
+        };
+
+        let full = HypernetMetrics::compute(&build(&points, &gabriel(None, 12)));
+        // No removal can keep the endpoints under 0 jumps apart
+        let blocked = HypernetMetrics::compute(&build(&points, &gabriel(Some(2.0), 0)));
+        assert_eq!(blocked.lanes, full.lanes);
+
+        let pruned = HypernetMetrics::compute(&build(&points, &gabriel(Some(2.5), 12)));
+        assert!(pruned.connected);
+        assert!(pruned.lanes < full.lanes);
+        assert!(pruned.average_degree < full.average_degree);
+    }
+
+    #[test]
+    fn tops_up_to_the_target_degree() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let points = scatter(&mut rng);
+        let settings = LaneGenerationSettings {
+            topology: LaneTopology::RelativeNeighbourhood,
+            target_average_degree: Some(4.0),
+            max_new_distance: 12,
+            wormholes: 0,
+            hazardous_lane_rate: 0.0,
+        };
+        let metrics = HypernetMetrics::compute(&build(&points, &settings));
+        assert!(
+            (metrics.average_degree - 4.0).abs() < 0.1,
+            "{}",
+            metrics.average_degree
+        );
+    }
+}
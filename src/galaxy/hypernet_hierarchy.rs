@@ -0,0 +1,218 @@
+use super::Hypernet;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+// Cluster-level abstraction of the hypernet, used to narrow down long range searches
+// Systems are grouped into small connected regions, and a route is first planned through the regions,
+// then solved at the system level only inside the regions along that corridor.
+// The result isn't guaranteed to be the shortest path, so this is opt-in (--hierarchical-pathfinding)
+// Rebuilt whenever the hypernet changes (eg. a new gateway), the path cache is flushed at the same time
+#[derive(Resource, Default)]
+pub struct HypernetClusters {
+    cluster_of: Vec<u32>,
+    adjacency: Vec<Vec<u32>>,
+}
+
+impl HypernetClusters {
+    // Target number of systems per cluster
+    const CLUSTER_SIZE: usize = 24;
+
+    pub fn build(hypernet: &Hypernet) -> Self {
+        let (n, _) = hypernet.graph.capacity();
+        let mut cluster_of = vec![u32::MAX; n];
+        let mut num_clusters = 0;
+        let mut queue = VecDeque::new();
+
+        // Grow regions breadth first from the first unassigned system until they reach the target size
+        for seed in 0..n {
+            if cluster_of[seed] != u32::MAX || !hypernet.graph.contains_node((seed as u32).into()) {
+                continue;
+            }
+            let cluster = num_clusters;
+            num_clusters += 1;
+
+            let mut size = 1;
+            cluster_of[seed] = cluster;
+            queue.clear();
+            queue.push_back(seed as u32);
+            while let Some(top) = queue.pop_front() {
+                for next in hypernet.graph.neighbors(top.into()) {
+                    if size >= Self::CLUSTER_SIZE {
+                        break;
+                    }
+                    if cluster_of[next.index()] == u32::MAX {
+                        cluster_of[next.index()] = cluster;
+                        size += 1;
+                        queue.push_back(next.index() as u32);
+                    }
+                }
+            }
+        }
+
+        let mut adjacency = vec![Vec::new(); num_clusters as usize];
+        for lane in hypernet.graph.edge_indices() {
+            let (a, b) = hypernet.graph.edge_endpoints(lane).unwrap();
+            let (ca, cb) = (cluster_of[a.index()], cluster_of[b.index()]);
+            if ca != cb && !adjacency[ca as usize].contains(&cb) {
+                adjacency[ca as usize].push(cb);
+                adjacency[cb as usize].push(ca);
+            }
+        }
+
+        Self {
+            cluster_of,
+            adjacency,
+        }
+    }
+
+    pub fn cluster(&self, star: u32) -> u32 {
+        self.cluster_of[star as usize]
+    }
+
+    pub fn num_clusters(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    // Clusters a search between "a" and "b" is allowed to enter, indexed by cluster
+    // That's the fewest-hops route through the cluster graph, widened by one cluster on each side to leave the search some room
+    // Returns None when a and b are close enough that a plain search is cheaper anyway
+    pub fn corridor(&self, a: u32, b: u32) -> Option<Vec<bool>> {
+        let (ca, cb) = (self.cluster(a), self.cluster(b));
+        if ca == cb || self.adjacency[ca as usize].contains(&cb) {
+            return None;
+        }
+
+        let mut parent = vec![u32::MAX; self.num_clusters()];
+        let mut queue = VecDeque::from([ca]);
+        parent[ca as usize] = ca;
+        while let Some(top) = queue.pop_front() {
+            if top == cb {
+                break;
+            }
+            for next in &self.adjacency[top as usize] {
+                if parent[*next as usize] == u32::MAX {
+                    parent[*next as usize] = top;
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        if parent[cb as usize] == u32::MAX {
+            return None;
+        }
+
+        let mut allowed = vec![false; self.num_clusters()];
+        let mut curr = cb;
+        loop {
+            allowed[curr as usize] = true;
+            for next in &self.adjacency[curr as usize] {
+                allowed[*next as usize] = true;
+            }
+            if curr == ca {
+                break;
+            }
+            curr = parent[curr as usize];
+        }
+        Some(allowed)
+    }
+}
+
+// Runs before the path cache is invalidated, which drops every path solved against the old clusters
+pub fn rebuild_hypernet_clusters_system(
+    hypernet: Res<Hypernet>,
+    clusters: Option<ResMut<HypernetClusters>>,
+) {
+    if let Some(mut clusters) = clusters {
+        if hypernet.is_changed() {
+            *clusters = HypernetClusters::build(&hypernet);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::galaxy::hypernet_pathfinding::Pathfinding;
+    use crate::galaxy::navigation_filter::NavigationMask;
+
+    const SIZE: u32 = 20;
+
+    // A square grid of systems 10 parsecs apart
+    fn grid() -> Hypernet {
+        let positions = (0..SIZE * SIZE)
+            .map(|i| ((i % SIZE) as f32 * 10.0, (i / SIZE) as f32 * 10.0))
+            .collect::<Vec<_>>();
+        let mut lanes = Vec::new();
+        for i in 0..SIZE * SIZE {
+            if i % SIZE + 1 < SIZE {
+                lanes.push((i, i + 1));
+            }
+            if i + SIZE < SIZE * SIZE {
+                lanes.push((i, i + SIZE));
+            }
+        }
+        Hypernet::from_lanes(&positions, &lanes)
+    }
+
+    #[test]
+    fn clusters_are_small_and_connected() {
+        let hypernet = grid();
+        let clusters = HypernetClusters::build(&hypernet);
+        for cluster in 0..clusters.num_clusters() as u32 {
+            let members = (0..SIZE * SIZE)
+                .filter(|x| clusters.cluster(*x) == cluster)
+                .collect::<Vec<_>>();
+            assert!((1..=HypernetClusters::CLUSTER_SIZE).contains(&members.len()));
+
+            let mut reached = vec![members[0]];
+            let mut queue = VecDeque::from([members[0]]);
+            while let Some(top) = queue.pop_front() {
+                for next in hypernet.graph.neighbors(top.into()) {
+                    let next = next.index() as u32;
+                    if clusters.cluster(next) == cluster && !reached.contains(&next) {
+                        reached.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            assert_eq!(reached.len(), members.len());
+        }
+    }
+
+    // Corridor routes aren't guaranteed to be the shortest, but they get there and stay close to it
+    #[test]
+    fn corridors_lead_to_the_destination() {
+        let hypernet = grid();
+        let clusters = HypernetClusters::build(&hypernet);
+        let mask = NavigationMask::new(&hypernet, true);
+        let filter = mask.to_filter(&hypernet);
+
+        assert!(clusters.corridor(0, 1).is_none());
+        let corridor = clusters.corridor(0, SIZE * SIZE - 1).unwrap();
+        assert!(corridor[clusters.cluster(0) as usize]);
+        assert!(corridor[clusters.cluster(SIZE * SIZE - 1) as usize]);
+        assert!(corridor.contains(&false));
+
+        for (a, b) in [
+            (0, SIZE * SIZE - 1),
+            (SIZE - 1, SIZE * (SIZE - 1)),
+            (5, 394),
+        ] {
+            let path = filter.find_path_hierarchical(&clusters, a, b).unwrap();
+            assert_eq!((path.nodes[0], *path.nodes.last().unwrap()), (a, b));
+            let cost = path
+                .edges
+                .iter()
+                .map(|x| {
+                    hypernet
+                        .graph
+                        .edge_weight((*x).into())
+                        .unwrap()
+                        .traversal_cost(None)
+                })
+                .sum::<i32>();
+            let shortest = filter.dijkstra(&[a])[b as usize].unwrap();
+            assert!(cost >= shortest && cost <= shortest * 5 / 4);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use super::Hypernet;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::fmt;
+
+// Summary of the hypernet's shape, computed once after generation
+// Path lengths are counted in jumps rather than lane length
+#[derive(Resource, Default, Clone)]
+pub struct HypernetMetrics {
+    pub systems: usize,
+    pub lanes: usize,
+    pub average_degree: f32,
+    pub connected: bool,
+    pub diameter: u32,
+    pub average_path_length: f32,
+    pub articulation_points: Vec<u32>,
+}
+
+impl HypernetMetrics {
+    pub fn compute(hypernet: &Hypernet) -> Self {
+        let (n, _) = hypernet.graph.capacity();
+        let adjacency = adjacency(hypernet);
+
+        let systems = adjacency.iter().filter(|x| !x.is_empty()).count();
+        let lanes = hypernet.graph.edge_count();
+
+        // BFS from every system. Fine at generation time, it's O(systems * lanes)
+        let mut diameter = 0;
+        let mut total_path_length: u64 = 0;
+        let mut num_paths: u64 = 0;
+        let mut connected = true;
+        let mut dist = vec![u32::MAX; n];
+        let mut queue = VecDeque::new();
+
+        for source in (0..n).filter(|x| !adjacency[*x].is_empty()) {
+            dist.fill(u32::MAX);
+            dist[source] = 0;
+            queue.push_back(source as u32);
+            let mut reached = 1;
+
+            while let Some(top) = queue.pop_front() {
+                let d = dist[top as usize];
+                for next in &adjacency[top as usize] {
+                    if dist[*next as usize] == u32::MAX {
+                        dist[*next as usize] = d + 1;
+                        diameter = diameter.max(d + 1);
+                        total_path_length += (d + 1) as u64;
+                        num_paths += 1;
+                        reached += 1;
+                        queue.push_back(*next);
+                    }
+                }
+            }
+
+            if reached < systems {
+                connected = false;
+            }
+        }
+
+        Self {
+            systems,
+            lanes,
+            average_degree: if systems > 0 {
+                2.0 * lanes as f32 / systems as f32
+            } else {
+                0.0
+            },
+            connected,
+            diameter,
+            average_path_length: if num_paths > 0 {
+                total_path_length as f32 / num_paths as f32
+            } else {
+                0.0
+            },
+            articulation_points: cut_structure(&adjacency).0,
+        }
+    }
+}
+
+// Neighbours of every node, indexed by node id. Removed nodes have none
+pub(super) fn adjacency(hypernet: &Hypernet) -> Vec<Vec<u32>> {
+    let (n, _) = hypernet.graph.capacity();
+    (0..n)
+        .map(|i| {
+            if hypernet.graph.contains_node((i as u32).into()) {
+                hypernet
+                    .graph
+                    .neighbors((i as u32).into())
+                    .map(|x| x.index() as u32)
+                    .collect::<Vec<_>>()
+            } else {
+                Vec::new()
+            }
+        })
+        .collect()
+}
+
+// Articulation points (systems whose loss splits the network) and bridges (lanes whose loss does), as node pairs
+// Iterative Tarjan, so long chains of systems can't blow the stack
+pub(super) fn cut_structure(adjacency: &[Vec<u32>]) -> (Vec<u32>, Vec<(u32, u32)>) {
+    let n = adjacency.len();
+    let mut discovery = vec![u32::MAX; n];
+    let mut low = vec![0; n];
+    let mut parent = vec![u32::MAX; n];
+    let mut is_articulation = vec![false; n];
+    let mut bridges = Vec::new();
+    let mut time = 0;
+
+    for root in 0..n {
+        if discovery[root] != u32::MAX || adjacency[root].is_empty() {
+            continue;
+        }
+
+        let mut root_children = 0;
+        // (node, index of the next neighbour to visit)
+        let mut stack = vec![(root as u32, 0usize)];
+        discovery[root] = time;
+        low[root] = time;
+        time += 1;
+
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node as usize;
+            if let Some(&neighbour) = adjacency[node].get(*next) {
+                *next += 1;
+                let neighbour = neighbour as usize;
+                if discovery[neighbour] == u32::MAX {
+                    parent[neighbour] = node as u32;
+                    discovery[neighbour] = time;
+                    low[neighbour] = time;
+                    time += 1;
+                    if node == root {
+                        root_children += 1;
+                    }
+                    stack.push((neighbour as u32, 0));
+                } else if parent[node] != neighbour as u32 {
+                    low[node] = low[node].min(discovery[neighbour]);
+                }
+            } else {
+                stack.pop();
+                let p = parent[node];
+                if p != u32::MAX {
+                    let p = p as usize;
+                    low[p] = low[p].min(low[node]);
+                    if p != root && low[node] >= discovery[p] {
+                        is_articulation[p] = true;
+                    }
+                    if low[node] > discovery[p] {
+                        bridges.push((p as u32, node as u32));
+                    }
+                }
+            }
+        }
+
+        if root_children > 1 {
+            is_articulation[root] = true;
+        }
+    }
+
+    let articulation_points = (0..n as u32)
+        .filter(|x| is_articulation[*x as usize])
+        .collect();
+    (articulation_points, bridges)
+}
+
+impl fmt::Display for HypernetMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Hypernet: ")?;
+        writeln!(f, "   Systems:             {}", self.systems)?;
+        writeln!(f, "   Lanes:               {}", self.lanes)?;
+        writeln!(f, "   Average Degree:      {:.2}", self.average_degree)?;
+        writeln!(f, "   Connected:           {}", self.connected)?;
+        writeln!(f, "   Diameter:            {} jumps", self.diameter)?;
+        writeln!(
+            f,
+            "   Average Path Length: {:.2} jumps",
+            self.average_path_length
+        )?;
+        write!(
+            f,
+            "   Articulation Points: {}",
+            self.articulation_points.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two triangles joined by a lane, with a system hanging off the second and one left out entirely
+    //   0 - 1   3 - 4
+    //    \ /     \ /
+    //     2 ----- 5 - 6     7
+    const TRIANGLES: [(u32, u32); 8] = [
+        (0, 1),
+        (1, 2),
+        (2, 0),
+        (2, 5),
+        (3, 4),
+        (4, 5),
+        (5, 3),
+        (5, 6),
+    ];
+
+    fn positions(n: usize) -> Vec<(f32, f32)> {
+        (0..n).map(|i| (i as f32 * 10.0, 0.0)).collect()
+    }
+
+    fn sorted_bridges(adjacency: &[Vec<u32>]) -> Vec<(u32, u32)> {
+        let mut bridges = cut_structure(adjacency)
+            .1
+            .into_iter()
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect::<Vec<_>>();
+        bridges.sort();
+        bridges
+    }
+
+    #[test]
+    fn finds_articulation_points_and_bridges() {
+        let hypernet = Hypernet::from_lanes(&positions(8), &TRIANGLES);
+        let neighbours = adjacency(&hypernet);
+        assert_eq!(cut_structure(&neighbours).0, [2, 5]);
+        assert_eq!(sorted_bridges(&neighbours), [(2, 5), (5, 6)]);
+
+        // A loop has neither
+        let ring = (0..6).map(|i| (i, (i + 1) % 6)).collect::<Vec<_>>();
+        let hypernet = Hypernet::from_lanes(&positions(6), &ring);
+        assert_eq!(cut_structure(&adjacency(&hypernet)), (vec![], vec![]));
+    }
+
+    #[test]
+    fn long_chains_dont_overflow_the_stack() {
+        let n: u32 = 100_000;
+        let chain = (0..n)
+            .map(|i| {
+                [i.checked_sub(1), (i + 1 < n).then_some(i + 1)]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            })
+            .collect::<Vec<Vec<u32>>>();
+        let (articulation_points, bridges) = cut_structure(&chain);
+        assert_eq!(articulation_points, (1..n - 1).collect::<Vec<_>>());
+        assert_eq!(bridges.len(), n as usize - 1);
+    }
+
+    #[test]
+    fn measures_the_network() {
+        let metrics = HypernetMetrics::compute(&Hypernet::from_lanes(&positions(8), &TRIANGLES));
+        assert_eq!(metrics.systems, 7);
+        assert_eq!(metrics.lanes, 8);
+        assert_eq!(metrics.average_degree, 16.0 / 7.0);
+        assert!(metrics.connected);
+        assert_eq!(metrics.diameter, 3);
+        assert_eq!(metrics.articulation_points, [2, 5]);
+
+        // Jumps between every ordered pair of 0 - 1 - 2 - 3: 1, 2, 3, 1, 2, 1 each way
+        let line = HypernetMetrics::compute(&Hypernet::from_lanes(
+            &positions(4),
+            &[(0, 1), (1, 2), (2, 3)],
+        ));
+        assert_eq!(line.diameter, 3);
+        assert_eq!(line.average_path_length, 20.0 / 12.0);
+
+        let apart = Hypernet::from_lanes(&positions(8), &[(0, 1), (2, 3)]);
+        assert!(!HypernetMetrics::compute(&apart).connected);
+    }
+}
@@ -17,6 +17,7 @@ impl Plugin for IndexPlugin {
             (
                 empires_index::update_empire_index_system,
                 system_index::update_system_index_system,
+                crate::galaxy::hypernet_analysis::update_hypernet_analysis_system,
             ),
         );
     }
@@ -15,10 +15,15 @@ pub mod galaxy_config;
 pub use galaxy_config::GalaxyConfig;
 
 mod hypernet_pathfinding;
-pub use hypernet_pathfinding::Pathfinding;
+pub use hypernet_pathfinding::{Path, Pathfinding};
 
+pub mod navigation_cost;
 pub mod navigation_filter;
 
+pub mod hypernet_hierarchy;
+pub mod path_cache;
+pub use path_cache::PathCache;
+
 pub mod description;
 pub use description::Description;
 
@@ -26,15 +31,21 @@ mod star;
 pub use star::{OverlaysTriangulationVertex, Star};
 
 mod hypernet;
-pub use hypernet::Hypernet;
+pub use hypernet::{Hyperlane, Hypernet, LaneType};
+
+pub mod hypernet_analysis;
+pub use hypernet_analysis::HypernetAnalysis;
+pub mod hypernet_generation;
+pub mod hypernet_metrics;
+pub use hypernet_metrics::HypernetMetrics;
 
 mod planet;
 pub use planet::colony::{Colony, StarClaim};
-pub use planet::economy::Economy;
+pub use planet::economy::{Economy, Goods, Prices};
 pub use planet::planet::Planet;
 
 pub mod empire;
-pub use empire::Empire;
+pub use empire::{Diplomacy, Empire};
 
 mod galaxy_density;
 
@@ -47,9 +58,10 @@ impl Plugin for GalaxySetupPlugin {
             picking_backend::PickingBackendPlugin,
             indexes::IndexPlugin,
         ))
-        .insert_resource(GalaxyConfig::default())
+        .insert_resource(GalaxyConfig::default().with_args(&std::env::args().collect::<Vec<_>>()))
         .insert_resource(GalaxyIndex::default())
         .insert_resource(Hypernet::new())
+        .insert_resource(PathCache::default())
         .insert_resource(empire::PlayerEmpire { empire: None })
         //.insert_resource(SelectedObject{hovered_star : None})
         .add_systems(Update, description::update_descriptions_system);
@@ -0,0 +1,143 @@
+use super::navigation_filter::SystemAccess;
+use crate::prelude::*;
+use crate::simulation::fleet_behaviour::navigation::NavPosition;
+use crate::simulation::SimTime;
+use bevy::prelude::*;
+
+// How a fleet weighs the systems it travels through
+// Every profile only ever makes a lane more expensive than its raw traversal cost, which keeps the A* heuristic admissible
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum CostProfile {
+    #[default]
+    Shortest,
+    Safe,         // Steers clear of systems with hostile fleets around
+    OwnTerritory, // Sticks to our own systems, then allied ones, where possible
+    Supplied,     // Avoids leaving our supply network
+    Offensive,    // Like Shortest, but willing to push through hostile territory
+}
+
+impl CostProfile {
+    // Cost of entering a system, as a percentage of the lane's traversal cost. None if it can't be entered at all
+    pub fn entry_cost_percent(
+        self,
+        access: SystemAccess,
+        danger: i32,
+        supplied: bool,
+    ) -> Option<i32> {
+        if access == SystemAccess::Hostile && self == CostProfile::Offensive {
+            // passable, but expensive
+            return Some(200 + 25 * danger);
+        }
+        if !access.is_passable() {
+            return None;
+        }
+
+        Some(match self {
+            CostProfile::Shortest | CostProfile::Offensive => 100,
+            CostProfile::Safe => 100 + 100 * danger,
+            CostProfile::OwnTerritory => match access {
+                SystemAccess::Owned => 100,
+                SystemAccess::AlliedAccess => 150,
+                _ => 250,
+            },
+            CostProfile::Supplied => {
+                if supplied {
+                    100
+                } else {
+                    400
+                }
+            }
+        })
+    }
+}
+
+// Counts the fleets of every empire we're at war with, per system
+// Only refreshed monthly, since each refresh that changes something invalidates the empire's cached paths
+pub fn update_navigation_danger_system(
+    mut masks: Query<(&mut NavigationMask, &Diplomacy)>,
+    fleets: Query<(&NavPosition, &Fleet)>,
+    sim_time: Res<SimTime>,
+    hypernet: Res<Hypernet>,
+) {
+    if !sim_time.is_new_month() {
+        return;
+    }
+
+    let (n, _) = hypernet.graph.capacity();
+    let mut danger = vec![0; n];
+    for (mut mask, diplomacy) in masks.iter_mut() {
+        danger.fill(0);
+        if !diplomacy.at_war.is_empty() {
+            for (nav_pos, fleet) in &fleets {
+                if diplomacy.at_war.contains(&fleet.owner) {
+                    danger[nav_pos.root_system as usize] += 1;
+                }
+            }
+        }
+        mask.set_danger(&danger);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILES: [CostProfile; 5] = [
+        CostProfile::Shortest,
+        CostProfile::Safe,
+        CostProfile::OwnTerritory,
+        CostProfile::Supplied,
+        CostProfile::Offensive,
+    ];
+    const ACCESS: [SystemAccess; 6] = [
+        SystemAccess::Owned,
+        SystemAccess::Neutral,
+        SystemAccess::AlliedAccess,
+        SystemAccess::Contested,
+        SystemAccess::Hostile,
+        SystemAccess::ClosedBorders,
+    ];
+
+    #[test]
+    fn weighs_systems_per_profile() {
+        use CostProfile::*;
+        use SystemAccess::*;
+
+        assert_eq!(Shortest.entry_cost_percent(Neutral, 3, false), Some(100));
+        assert_eq!(Safe.entry_cost_percent(Neutral, 0, false), Some(100));
+        assert_eq!(Safe.entry_cost_percent(Neutral, 3, false), Some(400));
+        assert_eq!(OwnTerritory.entry_cost_percent(Owned, 0, false), Some(100));
+        assert_eq!(
+            OwnTerritory.entry_cost_percent(AlliedAccess, 0, false),
+            Some(150)
+        );
+        assert_eq!(
+            OwnTerritory.entry_cost_percent(Neutral, 0, false),
+            Some(250)
+        );
+        assert_eq!(Supplied.entry_cost_percent(Neutral, 0, true), Some(100));
+        assert_eq!(Supplied.entry_cost_percent(Neutral, 0, false), Some(400));
+        assert_eq!(Offensive.entry_cost_percent(Neutral, 3, false), Some(100));
+        assert_eq!(Offensive.entry_cost_percent(Hostile, 4, false), Some(300));
+        assert_eq!(Offensive.entry_cost_percent(ClosedBorders, 0, false), None);
+    }
+
+    // Only Offensive fleets enter hostile systems, and no profile makes a lane cheaper than its traversal cost
+    #[test]
+    fn never_discounts_a_lane() {
+        for profile in PROFILES {
+            for access in ACCESS {
+                for danger in 0..5 {
+                    for supplied in [false, true] {
+                        let cost = profile.entry_cost_percent(access, danger, supplied);
+                        let passable = access.is_passable()
+                            || (profile == CostProfile::Offensive
+                                && access == SystemAccess::Hostile);
+                        assert_eq!(cost.is_some(), passable, "{:?} {:?}", profile, access);
+                        assert!(cost.is_none_or(|x| x >= 100));
+                    }
+                }
+            }
+        }
+    }
+}
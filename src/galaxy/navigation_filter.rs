@@ -1,41 +1,152 @@
+use super::hypernet_hierarchy::HypernetClusters;
 use super::hypernet_pathfinding::{Path, Pathfinding};
+use super::navigation_cost::CostProfile;
 use crate::prelude::*;
+use crate::util::graph::SearchGraph;
 use bevy::prelude::*;
-use std::collections::BinaryHeap;
 
+// Masks are maintained incrementally from claim changes
+// Any change in diplomatic relations (or a mask that was never built) means a full rebuild
+// That includes other empires' relations, since whether they grant us access decides how their systems look to us
 pub fn update_empire_navigation_masks(
-    mut query: Query<(Entity, &mut NavigationMask), With<Empire>>,
+    mut query: Query<(Entity, &mut NavigationMask, &Diplomacy), With<Empire>>,
+    changed_systems: Query<(&Star, &StarClaim), Changed<StarClaim>>,
     systems: Query<(&Star, &StarClaim)>,
+    diplomacy_query: Query<&Diplomacy>,
+    changed_diplomacy: Query<(), (With<Empire>, Changed<Diplomacy>)>,
 ) {
-    for (empire_entity, mut mask) in query.iter_mut() {
-        mask.owned_systems.clear();
-        for (star, starclaim) in systems.iter() {
-            match starclaim.owner {
-                None => mask.passable_systems_mask[star.node_id as usize] = true,
-                Some(entity) => {
-                    if entity == empire_entity {
-                        mask.passable_systems_mask[star.node_id as usize] = true;
-                        mask.owned_systems.push(star.node_id);
+    let diplomacy_changed = !changed_diplomacy.is_empty();
+    for (empire_entity, mut mask, diplomacy) in query.iter_mut() {
+        let rebuild = mask.empire.is_none() || diplomacy_changed;
+        mask.empire = Some(empire_entity);
+
+        let mut changed = false;
+        let mut update = |star: &Star, starclaim: &StarClaim| {
+            let access = match starclaim.owner {
+                None => SystemAccess::Neutral,
+                Some(owner) if owner == empire_entity => SystemAccess::Owned,
+                Some(_) if starclaim.contested_by == Some(empire_entity) => SystemAccess::Contested,
+                Some(owner) => {
+                    if diplomacy.at_war.contains(&owner) {
+                        SystemAccess::Hostile
+                    } else if diplomacy_query
+                        .get(owner)
+                        .is_ok_and(|x| x.grants_access.contains(&empire_entity))
+                    {
+                        SystemAccess::AlliedAccess
                     } else {
-                        mask.passable_systems_mask[star.node_id as usize] = false;
+                        SystemAccess::ClosedBorders
                     }
                 }
-            }
+            };
+            changed |= mask.set_access(star.node_id, access);
+        };
+
+        if rebuild {
+            systems.iter().for_each(|(star, claim)| update(star, claim));
+        } else {
+            changed_systems
+                .iter()
+                .for_each(|(star, claim)| update(star, claim));
+        }
+
+        if changed {
+            mask.version += 1;
+        }
+    }
+}
+
+// How an empire's fleets see a system
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SystemAccess {
+    Owned,
+    Neutral,
+    AlliedAccess, // Owned by an empire that lets us through
+    Contested,    // Owned by another empire, but our influence is stronger there
+    Hostile, // Owned by an empire we are at war with. Fleets can enter, but normal traffic avoids it
+    ClosedBorders,
+}
+
+impl SystemAccess {
+    pub fn is_passable(self) -> bool {
+        match self {
+            SystemAccess::Owned
+            | SystemAccess::Neutral
+            | SystemAccess::AlliedAccess
+            | SystemAccess::Contested => true,
+            SystemAccess::Hostile | SystemAccess::ClosedBorders => false,
         }
     }
 }
 
 #[derive(Component)]
 pub struct NavigationMask {
-    passable_systems_mask: Vec<bool>,
+    system_access: Vec<SystemAccess>,
     pub owned_systems: Vec<u32>,
+    pub empire: Option<Entity>, // Used for lanes whose traversal depends on the empire (eg. gateways)
+    danger: Vec<i32>,           // Number of hostile fleets in each system
+    supplied: Vec<bool>,        // Whether the system is inside the empire's supply network
+    version: u32, // Bumped whenever any of the above changes, so anything derived from an older version can be thrown out
 }
 
 impl<'a> NavigationMask {
     pub fn new(hypernet: &Hypernet, default_passable: bool) -> Self {
+        let access = if default_passable {
+            SystemAccess::Neutral
+        } else {
+            SystemAccess::ClosedBorders
+        };
         Self {
-            passable_systems_mask: vec![default_passable; hypernet.graph.node_count()],
+            system_access: vec![access; hypernet.graph.node_count()],
             owned_systems: Vec::new(),
+            empire: None,
+            danger: vec![0; hypernet.graph.node_count()],
+            supplied: vec![false; hypernet.graph.node_count()],
+            version: 0,
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn access(&self, system: u32) -> SystemAccess {
+        self.system_access[system as usize]
+    }
+
+    pub fn danger(&self, system: u32) -> i32 {
+        self.danger[system as usize]
+    }
+
+    // Returns whether anything changed
+    fn set_access(&mut self, system: u32, access: SystemAccess) -> bool {
+        let previous = self.system_access[system as usize];
+        if previous == access {
+            return false;
+        }
+        if previous == SystemAccess::Owned {
+            self.owned_systems.retain(|x| *x != system);
+        } else if access == SystemAccess::Owned {
+            self.owned_systems.push(system);
+        }
+        self.system_access[system as usize] = access;
+        true
+    }
+
+    pub fn set_danger(&mut self, danger: &[i32]) {
+        if self.danger[..] != danger[..self.danger.len()] {
+            let n = self.danger.len();
+            self.danger.copy_from_slice(&danger[..n]);
+            self.version += 1;
+        }
+    }
+
+    // Set by the supply system
+    pub fn set_supplied(&mut self, supplied: &[bool]) {
+        if self.supplied[..] != supplied[..self.supplied.len()] {
+            let n = self.supplied.len();
+            self.supplied.copy_from_slice(&supplied[..n]);
+            self.version += 1;
         }
     }
 
@@ -43,6 +154,8 @@ impl<'a> NavigationMask {
         NavigationFilter {
             mask: self,
             hypernet,
+            profile: CostProfile::Shortest,
+            corridor: None,
         }
     }
 }
@@ -50,361 +163,245 @@ impl<'a> NavigationMask {
 pub struct NavigationFilter<'a> {
     hypernet: &'a Hypernet,
     mask: &'a NavigationMask,
+    profile: CostProfile,
+    corridor: Option<(&'a HypernetClusters, &'a [bool])>, // Clusters the search is kept inside of, see find_path_hierarchical
 }
 
-impl NavigationFilter<'_> {
-    fn is_passable(&self, index: usize) -> bool {
-        self.mask.passable_systems_mask[index]
+impl<'a> NavigationFilter<'a> {
+    pub fn with_profile(mut self, profile: CostProfile) -> Self {
+        self.profile = profile;
+        self
     }
-}
 
-use std::cmp::Ordering;
-#[derive(Eq)]
-struct DijkstraNode {
-    star: u32,
-    cost: i32,
-}
-impl DijkstraNode {
-    fn score(&self) -> i32 {
-        -self.cost
-    }
-}
-impl PartialEq for DijkstraNode {
-    fn eq(&self, other: &Self) -> bool {
-        self.score().eq(&other.score())
-    }
-}
-impl PartialOrd for DijkstraNode {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    // Only systems in the clusters marked in "allowed" can be entered
+    pub fn within(mut self, clusters: &'a HypernetClusters, allowed: &'a [bool]) -> Self {
+        self.corridor = Some((clusters, allowed));
+        self
     }
-}
 
-impl Ord for DijkstraNode {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.score().cmp(&other.score())
+    pub fn profile(&self) -> CostProfile {
+        self.profile
     }
-}
 
-#[derive(Eq)]
-struct PathfindingNode {
-    star: u32,
-    parent: u32,
-    edge_to_parent: u32,
-    origin_dist: i32,
-    heuristic_val: i32,
-}
-impl PathfindingNode {
-    fn score(&self) -> i32 {
-        0 - (self.origin_dist + self.heuristic_val)
-    }
-    fn new(star: u32, parent: &PathfindingNode, dest_pos: Vec3, hypernet: &Hypernet) -> Self {
-        let edge_id = hypernet
-            .graph
-            .find_edge(star.into(), parent.star.into())
-            .unwrap();
-        let edge_w = hypernet.graph.edge_weight(edge_id).unwrap();
-        let pos = hypernet.graph.node_weight(star.into()).unwrap().pos;
-        Self {
-            star,
-            parent: parent.star,
-            edge_to_parent: edge_id.index() as u32,
-            origin_dist: parent.origin_dist + edge_w.length,
-            heuristic_val: (pos.distance(dest_pos) * GalaxyConfig::GALACTIC_INTEGER_SCALE as f32)
-                as i32,
+    // Cost of entering "system" as a percentage of the lane cost, or None if it can't be entered
+    fn entry_cost_percent(&self, system: u32) -> Option<i32> {
+        if let Some((clusters, allowed)) = self.corridor {
+            if !allowed[clusters.cluster(system) as usize] {
+                return None;
+            }
         }
+        self.profile.entry_cost_percent(
+            self.mask.access(system),
+            self.mask.danger[system as usize],
+            self.mask.supplied[system as usize],
+        )
     }
-}
-impl PartialEq for PathfindingNode {
-    fn eq(&self, other: &Self) -> bool {
-        self.score().eq(&other.score())
-    }
-}
-impl PartialOrd for PathfindingNode {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+
+    pub fn empire(&self) -> Option<Entity> {
+        self.mask.empire
     }
-}
 
-impl Ord for PathfindingNode {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.score().cmp(&other.score())
+    pub fn version(&self) -> u32 {
+        self.mask.version
     }
-}
 
-impl Pathfinding for NavigationFilter<'_> {
-    fn find_path_without_direct_edge(&self, star_a: u32, star_b: u32) -> Option<Path> {
-        if star_a == star_b {
-            return Some(Path {
-                nodes: vec![star_a],
-                edges: Vec::new(),
-            });
+    // Searches only the systems inside the cluster corridor between a and b, falling back to a full search if that fails
+    pub fn find_path_hierarchical(
+        &self,
+        clusters: &HypernetClusters,
+        star_a: u32,
+        star_b: u32,
+    ) -> Option<Path> {
+        let Some(corridor) = clusters.corridor(star_a, star_b) else {
+            return self.find_path(star_a, star_b);
         };
-        let (n, _) = self.hypernet.graph.capacity();
-        let mut parent_id = vec![None; n];
-        let mut closed = vec![false; n];
-        let mut open = BinaryHeap::new();
-
-        let dest_pos = self.hypernet.graph.node_weight(star_b.into()).unwrap().pos;
-
-        open.push(PathfindingNode {
-            star: star_a,
-            parent: star_a,
-            edge_to_parent: u32::MAX,
-            origin_dist: 0,
-            heuristic_val: (self
-                .hypernet
-                .graph
-                .node_weight(star_a.into())
-                .unwrap()
-                .pos
-                .distance(dest_pos)
-                * GalaxyConfig::GALACTIC_INTEGER_SCALE as f32) as i32,
-        });
-        closed[star_a as usize] = true;
-
-        while let Some(top) = open.pop() {
-            parent_id[top.star as usize] = Some((top.parent, top.edge_to_parent));
-
-            if top.star == star_b {
-                break;
-            }
-
-            for n in self.hypernet.graph.neighbors(top.star.into()) {
-                if top.star == star_a && n.index() as u32 == star_b {
-                    continue;
-                }
-                if !self.is_passable(n.index()) {
-                    continue;
-                }
-                if !closed[n.index()] {
-                    closed[n.index()] = true;
-                    open.push(PathfindingNode::new(
-                        n.index() as u32,
-                        &top,
-                        dest_pos,
-                        self.hypernet,
-                    ));
-                }
-            }
-        }
 
-        let mut path = Path::new();
-        let mut curr = star_b;
-
-        loop {
-            path.nodes.push(curr);
-            if curr == star_a {
-                path.nodes.reverse();
-                path.edges.reverse();
-                assert!(
-                    path.nodes.len() == path.edges.len() + 1,
-                    "mismaTched number of path nodes ({}) and edges ({})",
-                    path.nodes.len(),
-                    path.edges.len()
-                );
-                return Some(path);
-            }
-            if let Some(c) = parent_id[curr as usize] {
-                path.edges.push(c.1);
-                curr = c.0;
-            } else {
-                return None;
-            }
-        }
+        self.mask
+            .to_filter(self.hypernet)
+            .with_profile(self.profile)
+            .within(clusters, &corridor)
+            .find_path(star_a, star_b)
+            .or_else(|| self.find_path(star_a, star_b))
     }
-    fn find_path(&self, star_a: u32, star_b: u32) -> Option<Path> {
-        if star_a == star_b {
-            return Some(Path {
-                nodes: vec![star_a],
-                edges: Vec::new(),
-            });
-        };
-        let (n, _) = self.hypernet.graph.capacity();
-        let mut parent_id = vec![None; n];
-        let mut closed = vec![false; n];
-        let mut open = BinaryHeap::new();
-
-        let dest_pos = self.hypernet.graph.node_weight(star_b.into()).unwrap().pos;
-
-        open.push(PathfindingNode {
-            star: star_a,
-            parent: star_a,
-            edge_to_parent: u32::MAX,
-            origin_dist: 0,
-            heuristic_val: (self
-                .hypernet
-                .graph
-                .node_weight(star_a.into())
-                .unwrap()
-                .pos
-                .distance(dest_pos)
-                * GalaxyConfig::GALACTIC_INTEGER_SCALE as f32) as i32,
-        });
-        closed[star_a as usize] = true;
-
-        while let Some(top) = open.pop() {
-            parent_id[top.star as usize] = Some((top.parent, top.edge_to_parent));
-
-            if top.star == star_b {
-                break;
-            }
+}
 
-            for n in self.hypernet.graph.neighbors(top.star.into()) {
-                if !self.is_passable(n.index()) {
-                    continue;
-                }
-                if !closed[n.index()] {
-                    closed[n.index()] = true;
-                    open.push(PathfindingNode::new(
-                        n.index() as u32,
-                        &top,
-                        dest_pos,
-                        self.hypernet,
-                    ));
-                }
-            }
-        }
+impl SearchGraph for NavigationFilter<'_> {
+    fn node_capacity(&self) -> usize {
+        self.hypernet.node_capacity()
+    }
 
-        let mut path = Path::new();
-        let mut curr = star_b;
-
-        loop {
-            path.nodes.push(curr);
-            if curr == star_a {
-                path.nodes.reverse();
-                path.edges.reverse();
-                assert!(
-                    path.nodes.len() == path.edges.len() + 1,
-                    "mismaTched number of path nodes ({}) and edges ({})",
-                    path.nodes.len(),
-                    path.edges.len()
-                );
-                return Some(path);
-            }
-            if let Some(c) = parent_id[curr as usize] {
-                path.edges.push(c.1);
-                curr = c.0;
-            } else {
-                return None;
+    fn for_each_neighbour(&self, node: u32, mut visit: impl FnMut(u32, u32, i32)) {
+        self.hypernet.for_each_neighbour(node, |next, lane, cost| {
+            if let Some(entry_percent) = self.entry_cost_percent(next) {
+                let lane_cost = match self.mask.empire {
+                    // the hypernet only knows the empire-agnostic cost
+                    Some(empire) => self
+                        .hypernet
+                        .graph
+                        .edge_weight(lane.into())
+                        .unwrap()
+                        .traversal_cost(Some(empire)),
+                    None => cost,
+                };
+                // in i64, a long lane into a dangerous system overflows i32 under the Safe profile
+                let entry_cost =
+                    (lane_cost as i64 * entry_percent as i64 / 100).min(i32::MAX as i64);
+                visit(next, lane, entry_cost as i32);
             }
-        }
+        });
     }
 
-    fn find_path_multi_source(&self, sources: &[u32], star_b: u32) -> Option<Path> {
-        let sources_set: std::collections::HashSet<u32> =
-            std::collections::HashSet::from_iter(sources.iter().cloned());
-        if sources_set.contains(&star_b) {
-            return Some(Path {
-                nodes: vec![star_b],
-                edges: Vec::new(),
-            });
-        };
-        let (n, _) = self.hypernet.graph.capacity();
-        let mut parent_id = vec![None; n];
-        let mut closed = vec![false; n];
-        let mut open = BinaryHeap::new();
-
-        let dest_pos = self.hypernet.graph.node_weight(star_b.into()).unwrap().pos;
-
-        for source_star in sources {
-            open.push(PathfindingNode {
-                star: *source_star,
-                parent: *source_star,
-                edge_to_parent: u32::MAX,
-                origin_dist: 0,
-                heuristic_val: (self
-                    .hypernet
-                    .graph
-                    .node_weight((*source_star).into())
-                    .unwrap()
-                    .pos
-                    .distance(dest_pos)
-                    * GalaxyConfig::GALACTIC_INTEGER_SCALE as f32)
-                    as i32,
-            });
-            closed[*source_star as usize] = true;
-        }
+    fn heuristic(&self, node: u32, dest: u32) -> i32 {
+        self.hypernet.heuristic(node, dest)
+    }
+}
 
-        while let Some(top) = open.pop() {
-            parent_id[top.star as usize] = Some((top.parent, top.edge_to_parent));
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            if top.star == star_b {
-                break;
-            }
+    // A straight route from 0 to 2 through 1, and a longer detour through 3
+    fn detour() -> Hypernet {
+        Hypernet::from_lanes(
+            &[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0), (10.0, 10.0)],
+            &[(0, 1), (1, 2), (0, 3), (3, 2)],
+        )
+    }
 
-            for n in self.hypernet.graph.neighbors(top.star.into()) {
-                if !self.is_passable(n.index()) {
-                    continue;
-                }
-                if !closed[n.index()] {
-                    closed[n.index()] = true;
-                    open.push(PathfindingNode::new(
-                        n.index() as u32,
-                        &top,
-                        dest_pos,
-                        self.hypernet,
-                    ));
-                }
-            }
-        }
+    #[test]
+    fn safe_routes_around_danger() {
+        let hypernet = detour();
+        let mut mask = NavigationMask::new(&hypernet, true);
+        mask.set_danger(&[0, 2, 0, 0]);
+
+        let shortest = mask.to_filter(&hypernet).find_path(0, 2).unwrap();
+        assert_eq!(shortest.nodes, vec![0, 1, 2]);
+        let safe = mask
+            .to_filter(&hypernet)
+            .with_profile(CostProfile::Safe)
+            .find_path(0, 2)
+            .unwrap();
+        assert_eq!(safe.nodes, vec![0, 3, 2]);
+
+        // Without a detour, it still goes through
+        mask.set_danger(&[0, 2, 2, 2]);
+        let safe = mask
+            .to_filter(&hypernet)
+            .with_profile(CostProfile::Safe)
+            .find_path(0, 2)
+            .unwrap();
+        assert_eq!(safe.nodes, vec![0, 1, 2]);
+    }
 
-        let mut path = Path::new();
-        let mut curr = star_b;
-
-        loop {
-            path.nodes.push(curr);
-            if sources_set.contains(&curr) {
-                path.nodes.reverse();
-                path.edges.reverse();
-                assert!(
-                    path.nodes.len() == path.edges.len() + 1,
-                    "mismaTched number of path nodes ({}) and edges ({})",
-                    path.nodes.len(),
-                    path.edges.len()
-                );
-                return Some(path);
-            }
-            if let Some(c) = parent_id[curr as usize] {
-                path.edges.push(c.1);
-                curr = c.0;
-            } else {
-                return None;
-            }
-        }
+    // A long hazardous lane into a system full of hostile fleets costs more than fits in an i32
+    #[test]
+    fn caps_entry_costs_instead_of_overflowing() {
+        let mut hypernet = Hypernet::from_lanes(&[(0.0, 0.0), (1000.0, 0.0)], &[(0, 1)]);
+        hypernet.set_lane_type(0, LaneType::Hazardous);
+        let mut mask = NavigationMask::new(&hypernet, true);
+        mask.set_danger(&[0, 200]);
+
+        let filter = mask.to_filter(&hypernet).with_profile(CostProfile::Safe);
+        let mut costs = Vec::new();
+        filter.for_each_neighbour(0, |_, _, cost| costs.push(cost));
+        assert_eq!(costs, vec![i32::MAX]);
+        assert!(filter.find_path(0, 1).is_some());
+        assert_eq!(filter.dijkstra(&[0])[1], Some(i32::MAX));
     }
-    ///
-    /// Returns vec of distances corresponding to hypernet node ids
-    fn dijkstra(&self, input_points: &[u32]) -> Vec<Option<i32>> {
-        let (n, _) = self.hypernet.graph.capacity();
-        let mut open = BinaryHeap::new();
-        let mut result = vec![None; n];
-
-        for p in input_points {
-            open.push(DijkstraNode { star: *p, cost: 0 });
-            result[*p as usize] = Some(0);
-        }
 
-        while let Some(top) = open.pop() {
-            for n in self.hypernet.graph.neighbors(top.star.into()) {
-                if !self.is_passable(n.index()) {
-                    continue;
-                }
-                if result[n.index()].is_none() {
-                    let e = self
-                        .hypernet
-                        .graph
-                        .edge_weight(self.hypernet.graph.find_edge(top.star.into(), n).unwrap())
-                        .unwrap();
-                    result[n.index()] = Some(top.cost + e.length);
-                    open.push(DijkstraNode {
-                        star: n.index() as u32,
-                        cost: top.cost + e.length,
-                    });
-                }
-            }
-        }
+    fn spawn_system(world: &mut World, node_id: u32, owner: Option<Entity>) -> Entity {
+        world
+            .spawn((
+                Star {
+                    pos: Vec3::ZERO,
+                    node_id,
+                    orbiters: Vec::new(),
+                    mass: 1.0,
+                    name: String::new(),
+                },
+                StarClaim {
+                    owner,
+                    claimed_tick: 0,
+                    contested_by: None,
+                },
+            ))
+            .id()
+    }
 
-        result
+    #[test]
+    fn follows_claims_and_diplomacy() {
+        let hypernet = Hypernet::from_lanes(&[(0.0, 0.0); 5], &[]);
+        let mut world = World::new();
+        let mut spawn_empire = |name| {
+            world
+                .spawn((
+                    Empire::named(name),
+                    Diplomacy::default(),
+                    NavigationMask::new(&hypernet, true),
+                ))
+                .id()
+        };
+        let (us, enemy, ally, neighbour) = (
+            spawn_empire("Us"),
+            spawn_empire("Enemy"),
+            spawn_empire("Ally"),
+            spawn_empire("Neighbour"),
+        );
+        world.get_mut::<Diplomacy>(us).unwrap().at_war.push(enemy);
+        world.get_mut::<Diplomacy>(enemy).unwrap().at_war.push(us);
+        world
+            .get_mut::<Diplomacy>(ally)
+            .unwrap()
+            .grants_access
+            .push(us);
+
+        spawn_system(&mut world, 0, Some(us));
+        spawn_system(&mut world, 1, Some(enemy));
+        spawn_system(&mut world, 2, Some(ally));
+        let neutral = spawn_system(&mut world, 3, None);
+        let contested = spawn_system(&mut world, 4, Some(neighbour));
+        world.get_mut::<StarClaim>(contested).unwrap().contested_by = Some(us);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(update_empire_navigation_masks);
+        schedule.run(&mut world);
+
+        let access = |world: &World| {
+            let mask = world.get::<NavigationMask>(us).unwrap();
+            (
+                (0..5).map(|x| mask.access(x)).collect::<Vec<_>>(),
+                mask.owned_systems.clone(),
+                mask.version(),
+            )
+        };
+        let (systems, owned, version) = access(&world);
+        use SystemAccess::*;
+        assert_eq!(systems, [Owned, Hostile, AlliedAccess, Neutral, Contested]);
+        assert_eq!(owned, [0]);
+        assert_eq!(
+            world.get::<NavigationMask>(neighbour).unwrap().access(4),
+            Owned
+        );
+
+        // Nothing changed, nothing bumped
+        schedule.run(&mut world);
+        assert_eq!(access(&world).2, version);
+
+        // Only the changed claim is picked up
+        world.get_mut::<StarClaim>(neutral).unwrap().owner = Some(us);
+        schedule.run(&mut world);
+        let (systems, owned, new_version) = access(&world);
+        assert_eq!(systems[3], Owned);
+        assert_eq!(owned, [0, 3]);
+        assert!(new_version > version);
+
+        // Someone else's diplomacy changing rebuilds our mask too
+        world
+            .get_mut::<Diplomacy>(ally)
+            .unwrap()
+            .grants_access
+            .clear();
+        schedule.run(&mut world);
+        assert_eq!(access(&world).0[2], ClosedBorders);
     }
 }
@@ -0,0 +1,186 @@
+use super::hypernet_hierarchy::HypernetClusters;
+use super::hypernet_pathfinding::{Path, Pathfinding};
+use super::navigation_cost::CostProfile;
+use super::navigation_filter::{NavigationFilter, NavigationMask};
+use super::Hypernet;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// (empire, mask version, cost profile, origin, destination)
+type PathKey = (Option<Entity>, u32, CostProfile, u32, u32);
+// (empire, mask version, cost profile, origin)
+type DistancesKey = (Option<Entity>, u32, CostProfile, u32);
+
+// Shared cache of solved paths and distance fields
+// Entries are keyed by the version of the navigation mask they were solved against, so a stale entry can never be hit.
+// They get pruned once their mask moves on, and everything is dropped when the hypernet itself changes (eg. a new gateway)
+// Behind a mutex so it can be used from parallel queries
+#[derive(Resource, Default)]
+pub struct PathCache {
+    paths: Mutex<HashMap<PathKey, Option<Arc<Path>>>>,
+    distances: Mutex<HashMap<DistancesKey, Arc<Vec<Option<i32>>>>>,
+    mask_versions: HashMap<Entity, u32>,
+}
+
+impl PathCache {
+    // Hard caps, the cache is simply flushed when they are reached
+    const MAX_PATHS: usize = 1 << 16;
+    const MAX_DISTANCES: usize = 256;
+
+    pub fn find_path(
+        &self,
+        filter: &NavigationFilter,
+        clusters: Option<&HypernetClusters>,
+        star_a: u32,
+        star_b: u32,
+    ) -> Option<Arc<Path>> {
+        let solve = || {
+            match clusters {
+                Some(clusters) => filter.find_path_hierarchical(clusters, star_a, star_b),
+                None => filter.find_path(star_a, star_b),
+            }
+            .map(Arc::new)
+        };
+
+        let key = (
+            filter.empire(),
+            filter.version(),
+            filter.profile(),
+            star_a,
+            star_b,
+        );
+        if let Some(path) = self.paths.lock().unwrap().get(&key) {
+            return path.clone();
+        }
+
+        // Solve outside the lock, two threads occasionally solving the same path is cheaper than serialising every search
+        let path = solve();
+
+        let mut paths = self.paths.lock().unwrap();
+        if paths.len() >= Self::MAX_PATHS {
+            paths.clear();
+        }
+        paths.insert(key, path.clone());
+        path
+    }
+
+    // Dijkstra distance field from "origin", as returned by Pathfinding::dijkstra
+    pub fn distances(&self, filter: &NavigationFilter, origin: u32) -> Arc<Vec<Option<i32>>> {
+        let key = (filter.empire(), filter.version(), filter.profile(), origin);
+        if let Some(distances) = self.distances.lock().unwrap().get(&key) {
+            return distances.clone();
+        }
+
+        let distances = Arc::new(filter.dijkstra(&[origin]));
+
+        let mut cache = self.distances.lock().unwrap();
+        if cache.len() >= Self::MAX_DISTANCES {
+            cache.clear();
+        }
+        cache.insert(key, distances.clone());
+        distances
+    }
+
+    pub fn clear(&mut self) {
+        self.paths.get_mut().unwrap().clear();
+        self.distances.get_mut().unwrap().clear();
+    }
+}
+
+// Runs after the navigation masks are updated
+pub fn invalidate_path_cache_system(
+    mut cache: ResMut<PathCache>,
+    masks: Query<(Entity, &NavigationMask)>,
+    hypernet: Res<Hypernet>,
+) {
+    if hypernet.is_changed() {
+        cache.clear();
+    }
+
+    let mut stale = false;
+    for (empire, mask) in &masks {
+        if cache.mask_versions.insert(empire, mask.version()) != Some(mask.version()) {
+            stale = true;
+        }
+    }
+    if !stale {
+        return;
+    }
+
+    let current = |empire: Option<Entity>, version: u32| {
+        empire.is_some_and(|x| {
+            masks
+                .get(x)
+                .is_ok_and(|(_, mask)| mask.version() == version)
+        })
+    };
+    cache
+        .paths
+        .get_mut()
+        .unwrap()
+        .retain(|key, _| current(key.0, key.1));
+    cache
+        .distances
+        .get_mut()
+        .unwrap()
+        .retain(|key, _| current(key.0, key.1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_paths_solved_against_an_old_mask() {
+        let hypernet =
+            Hypernet::from_lanes(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)], &[(0, 1), (1, 2)]);
+        let mut world = World::new();
+        let empire = world.spawn(NavigationMask::new(&hypernet, true)).id();
+        world.get_mut::<NavigationMask>(empire).unwrap().empire = Some(empire);
+        world.insert_resource(hypernet);
+        world.init_resource::<PathCache>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(invalidate_path_cache_system);
+        schedule.run(&mut world);
+
+        let solve = |world: &World| {
+            let mask = world.get::<NavigationMask>(empire).unwrap();
+            let filter = mask.to_filter(world.resource::<Hypernet>());
+            let cache = world.resource::<PathCache>();
+            (
+                cache.find_path(&filter, None, 0, 2),
+                cache.distances(&filter, 0),
+            )
+        };
+        let cached = |world: &World| {
+            let cache = world.resource::<PathCache>();
+            (
+                cache.paths.lock().unwrap().len(),
+                cache.distances.lock().unwrap().len(),
+            )
+        };
+
+        let (path, distances) = solve(&world);
+        let (again, distances_again) = solve(&world);
+        assert!(Arc::ptr_eq(&path.unwrap(), &again.unwrap()));
+        assert!(Arc::ptr_eq(&distances, &distances_again));
+
+        // Nothing changed
+        schedule.run(&mut world);
+        assert_eq!(cached(&world), (1, 1));
+
+        world
+            .get_mut::<NavigationMask>(empire)
+            .unwrap()
+            .set_danger(&[0, 1, 0]);
+        schedule.run(&mut world);
+        assert_eq!(cached(&world), (0, 0));
+
+        solve(&world);
+        assert_eq!(cached(&world), (1, 1));
+        world.resource_mut::<Hypernet>().set_changed();
+        schedule.run(&mut world);
+        assert_eq!(cached(&world), (0, 0));
+    }
+}
@@ -24,4 +24,5 @@ impl Colony {
 pub struct StarClaim {
     pub owner: Option<Entity>,
     pub claimed_tick: i64,
+    pub contested_by: Option<Entity>, // Another empire whose influence outweighs the owner's
 }
@@ -1,6 +1,59 @@
+use crate::localisation::{Arg, Localisation};
 use crate::prelude::*;
+use crate::simulation::policy::{InvestmentPriority, PolicyChange, PolicyEffects};
 use crate::util::number::IPercent;
-use std::fmt;
+
+// Share of the output's value paid out as wages, at base prices
+// The rest is profit, and gets invested in more infrastructure
+const LABOUR_SHARE: IPercent = IPercent::new(750);
+// Engineers earn this many times what basic workers do
+const ADVANCED_WAGE_RATIO: i64 = 5;
+// Engineer training without any education spending
+const BASE_TRAINING_RATE: IPercent = IPercent::new(100);
+// Share of all investment that goes to the sector the empire prioritises
+const PRIORITY_SHARE: IPercent = IPercent::new(400);
+// Infrastructure wearing out, at base prices. Upkeep is paid in heavy goods, so it wears out faster the pricier they get
+const BASE_DEPRECIATION: IPercent = IPercent::new(100);
+const MAX_DEPRECIATION: IPercent = IPercent::new(250);
+
+// Amounts of each kind of goods
+#[derive(Clone, Copy, Default)]
+pub struct Goods {
+    pub light: i64,
+    pub heavy: i64,
+    pub advanced: i64,
+}
+
+// Price of each kind of goods, relative to its base price
+#[derive(Clone, Copy)]
+pub struct Prices {
+    pub light: IPercent,
+    pub heavy: IPercent,
+    pub advanced: IPercent,
+}
+
+impl std::ops::AddAssign for Goods {
+    fn add_assign(&mut self, rhs: Self) {
+        self.light += rhs.light;
+        self.heavy += rhs.heavy;
+        self.advanced += rhs.advanced;
+    }
+}
+
+impl Default for Prices {
+    fn default() -> Self {
+        Self {
+            light: IPercent::new(1000),
+            heavy: IPercent::new(1000),
+            advanced: IPercent::new(1000),
+        }
+    }
+}
+
+// Value of an amount of goods at base price, converted into an amount at the given price
+fn buys(value: i64, price: IPercent) -> i64 {
+    value / price.max(IPercent::new(1))
+}
 
 pub struct Economy {
     light_infra: i64,
@@ -36,11 +89,17 @@ pub struct Economy {
     heavy_output: i64,
     advanced_output: i64,
 
-    infra_depreciation_rate: IPercent,
-    savings_rate: IPercent,
+    infra_depreciation_rate: IPercent, // Follows the price of heavy goods
+    savings_rate: IPercent, // Share of the output's value left as profit, follows the prices
+
+    // Per million workers
+    basic_wage: Credits,
+    advanced_wage: Credits,
 
-    basic_wage: i32,
-    advanced_wage: i32,
+    // Set by the owner's market and policies every month
+    prices: Prices,
+    policy: PolicyEffects,
+    demand: Goods,
 }
 
 impl Economy {
@@ -71,17 +130,75 @@ impl Economy {
             infra_depreciation_rate: IPercent::new(0),
             savings_rate: IPercent::new(0),
 
-            basic_wage: 0,
-            advanced_wage: 0,
+            basic_wage: Credits::ZERO,
+            advanced_wage: Credits::ZERO,
+
+            prices: Prices::default(),
+            policy: PolicyEffects::default(),
+            demand: Goods::default(),
         }
     }
 
-    pub fn update_dynamic_params(&mut self, population: i64) {
-        self.savings_rate = IPercent::new(250);
-        self.infra_depreciation_rate = IPercent::new(100);
+    // Infrastructure lost to fighting, eg. during an invasion
+    pub fn damage(&mut self, loss: IPercent) {
+        self.light_infra -= self.light_infra * loss;
+        self.heavy_infra -= self.heavy_infra * loss;
+        self.advanced_infra -= self.advanced_infra * loss;
+    }
+
+    pub fn set_prices(&mut self, prices: Prices) {
+        self.prices = prices;
+    }
+
+    pub fn set_policy(&mut self, policy: PolicyEffects) {
+        self.policy = policy;
+    }
 
-        self.basic_wage = 100;
-        self.advanced_wage = 500;
+    pub fn supply(&self) -> Goods {
+        Goods {
+            light: self.light_output,
+            heavy: self.heavy_output,
+            advanced: self.advanced_output,
+        }
+    }
+
+    pub fn infrastructure(&self) -> Goods {
+        Goods {
+            light: self.light_infra,
+            heavy: self.heavy_infra,
+            advanced: self.advanced_infra,
+        }
+    }
+
+    // Named stocks and flows, for exporting. Rates are on the 0-1000 scale
+    pub fn stats(&self) -> [(&'static str, i64); 16] {
+        [
+            ("basic_workers", self.basic_workers),
+            ("engineers", self.advanced_workers),
+            ("engineer_growth", self.engineer_growth),
+            ("light_infra", self.light_infra),
+            ("heavy_infra", self.heavy_infra),
+            ("advanced_infra", self.advanced_infra),
+            ("light_growth", self.light_growth),
+            ("heavy_growth", self.heavy_growth),
+            ("advanced_growth", self.advanced_growth),
+            ("light_output", self.light_output),
+            ("heavy_output", self.heavy_output),
+            ("advanced_output", self.advanced_output),
+            ("savings_rate", self.savings_rate.value() as i64),
+            ("training_rate", self.training_rate.value() as i64),
+            ("basic_wage", self.basic_wage.value()),
+            ("advanced_wage", self.advanced_wage.value()),
+        ]
+    }
+
+    pub fn demand(&self) -> Goods {
+        self.demand
+    }
+
+    pub fn update_dynamic_params(&mut self, population: i64) {
+        self.infra_depreciation_rate =
+            (BASE_DEPRECIATION * self.prices.heavy).min(MAX_DEPRECIATION);
 
         self.light_output =
             (self.advanced_workers + self.basic_workers).isqrt() * self.light_infra.isqrt().max(1);
@@ -90,9 +207,39 @@ impl Economy {
         self.advanced_output = (self.advanced_workers / 10).isqrt() * self.advanced_infra.isqrt();
 
         self.worker_potential = IPercent::new(200);
-        self.training_rate = IPercent::new(150);
 
-        self.basic_workers = population - self.advanced_workers;
+        // Conscripts are taken out of the workforce
+        let conscripts = population * self.policy.conscription;
+        self.basic_workers = (population - conscripts - self.advanced_workers).max(0);
+
+        // Wages follow the cost of living, ie. the price of light goods
+        // Each industry keeps what's left of its sales as profit, so the pricier its goods, the more it invests
+        let prices = self.prices;
+        let output = self.supply();
+        let wages = |output: i64| output * LABOUR_SHARE * prices.light;
+        let profit = |output: i64, price: IPercent| (output * price - wages(output)).max(0);
+        let sector_profits = Goods {
+            light: profit(output.light, prices.light),
+            heavy: profit(output.heavy, prices.heavy),
+            advanced: profit(output.advanced, prices.advanced),
+        };
+
+        let output_value = output.light * prices.light
+            + output.heavy * prices.heavy
+            + output.advanced * prices.advanced;
+        let wage_bill = wages(output.light) + wages(output.heavy) + wages(output.advanced);
+        let profits = sector_profits.light + sector_profits.heavy + sector_profits.advanced;
+
+        // Taxes pay for education. Underfunded schools train engineers at the base rate
+        let taxes = profits * self.policy.tax_rate;
+        let education_cost = profits * self.policy.education_cost;
+        let funding = if education_cost > 0 {
+            IPercent::ratio(taxes, education_cost).min(IPercent::FULL)
+        } else {
+            IPercent::FULL
+        };
+        self.training_rate =
+            BASE_TRAINING_RATE + (self.policy.training_rate - BASE_TRAINING_RATE) * funding;
 
         let retirees = self.advanced_workers * self.retiring_rate;
         // part of the population who are potential to be promoted to engineers (advanced workers)
@@ -105,13 +252,52 @@ impl Economy {
             self.engineer_growth = 0;
         }
 
-        self.light_growth = (self.light_output * self.savings_rate)
-            - self.light_infra * self.infra_depreciation_rate;
+        let wage_shares = self.basic_workers + self.advanced_workers * ADVANCED_WAGE_RATIO;
+        self.basic_wage = Credits::new(
+            Fixed::ratio(wage_bill, wage_shares.max(1))
+                .mul_div(1_000_000, 1)
+                .round(),
+        );
+        self.advanced_wage = self.basic_wage * ADVANCED_WAGE_RATIO;
+
+        // What's left after taxes is invested. The priority sector takes a share of every sector's investment
+        let keep = IPercent::FULL - self.policy.tax_rate;
+        let mut investment = Goods {
+            light: sector_profits.light * keep,
+            heavy: sector_profits.heavy * keep,
+            advanced: sector_profits.advanced * keep,
+        };
+        let invested = investment.light + investment.heavy + investment.advanced;
+        self.savings_rate = IPercent::ratio(invested, output_value.max(1));
+
+        if self.policy.investment != InvestmentPriority::Balanced {
+            investment.light -= investment.light * PRIORITY_SHARE;
+            investment.heavy -= investment.heavy * PRIORITY_SHARE;
+            investment.advanced -= investment.advanced * PRIORITY_SHARE;
+            let redirected = invested * PRIORITY_SHARE;
+            match self.policy.investment {
+                InvestmentPriority::Light => investment.light += redirected,
+                InvestmentPriority::Heavy => investment.heavy += redirected,
+                _ => investment.advanced += redirected,
+            }
+        }
+
+        // Everyone needs light goods to get by, whatever's left of the wages goes on advanced goods
+        // New infrastructure is built out of heavy goods
+        let needs = population.isqrt();
+        self.demand = Goods {
+            light: needs,
+            heavy: buys(invested, prices.heavy),
+            advanced: buys((wage_bill - needs * prices.light).max(0), prices.advanced),
+        };
 
-        self.heavy_growth = (self.heavy_output * self.savings_rate)
-            - self.heavy_infra * self.infra_depreciation_rate;
+        self.light_growth =
+            buys(investment.light, prices.heavy) - self.light_infra * self.infra_depreciation_rate;
 
-        self.advanced_growth = (self.advanced_output * self.savings_rate)
+        self.heavy_growth =
+            buys(investment.heavy, prices.heavy) - self.heavy_infra * self.infra_depreciation_rate;
+
+        self.advanced_growth = buys(investment.advanced, prices.heavy)
             - self.advanced_infra * self.infra_depreciation_rate;
     }
 
@@ -130,49 +316,136 @@ impl Economy {
     }
 }
 
-impl fmt::Display for Economy {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "Output: ")?;
-        writeln!(f, "   Light:    {} ", self.light_output.format_big_number())?;
-        writeln!(f, "   Heavy:    {} ", self.heavy_output.format_big_number())?;
-        writeln!(
-            f,
-            "   Advanced: {} ",
-            self.advanced_output.format_big_number()
-        )?;
-
-        writeln!(f, "Infrastructure: ")?;
-        writeln!(
-            f,
-            "   Light:    {:<8} (+{}) ",
-            self.light_infra.format_big_number(),
-            self.light_growth.format_big_number()
-        )?;
-        writeln!(
-            f,
-            "   Heavy:    {:<8} (+{}) ",
-            self.heavy_infra.format_big_number(),
-            self.heavy_growth.format_big_number()
-        )?;
-        writeln!(
-            f,
-            "   Advanced: {:<8} (+{}) ",
-            self.advanced_infra.format_big_number(),
-            self.advanced_growth.format_big_number()
-        )?;
-
-        writeln!(f, "\nDepreciation Rate:{} ", self.infra_depreciation_rate)?;
-        writeln!(f, "Savings Rate:  {} ", self.savings_rate)?;
-        writeln!(
-            f,
-            "Basic Workers: {} ",
-            self.basic_workers.format_big_number()
-        )?;
-        writeln!(
-            f,
-            "Engineers:     {:<8} (+{}) ",
-            self.advanced_workers.format_big_number(),
-            self.engineer_growth.format_big_number()
-        )
+impl Economy {
+    // The economy written out for the interface, a few lines per part
+    pub fn details(&self, loc: &Localisation) -> String {
+        let sectors = [
+            (
+                "economy-light",
+                self.light_output,
+                self.light_infra,
+                self.light_growth,
+            ),
+            (
+                "economy-heavy",
+                self.heavy_output,
+                self.heavy_infra,
+                self.heavy_growth,
+            ),
+            (
+                "economy-advanced",
+                self.advanced_output,
+                self.advanced_infra,
+                self.advanced_growth,
+            ),
+        ];
+        let priority = PolicyChange::Investment(self.policy.investment).option_message();
+
+        let mut lines = vec![loc.get("economy-output")];
+        for (sector, output, _, _) in sectors {
+            let line = loc.format(
+                "economy-amount",
+                &[
+                    ("sector", Arg::Text(loc.get(sector))),
+                    ("amount", Arg::Number(output)),
+                ],
+            );
+            lines.push(format!("   {}", line));
+        }
+        lines.push(loc.get("economy-infrastructure"));
+        for (sector, _, infra, growth) in sectors {
+            let line = loc.format(
+                "economy-growth",
+                &[
+                    ("sector", Arg::Text(loc.get(sector))),
+                    ("amount", Arg::Number(infra)),
+                    ("growth", Arg::Number(growth)),
+                ],
+            );
+            lines.push(format!("   {}", line));
+        }
+        lines.push(String::new());
+        lines.push(loc.format(
+            "economy-rates",
+            &[
+                ("depreciation", Arg::Percent(self.infra_depreciation_rate)),
+                ("savings", Arg::Percent(self.savings_rate)),
+                ("training", Arg::Percent(self.training_rate)),
+                ("taxes", Arg::Percent(self.policy.tax_rate)),
+                ("priority", Arg::Text(loc.get(priority))),
+            ],
+        ));
+        lines.push(loc.format(
+            "economy-prices",
+            &[
+                ("light", Arg::Percent(self.prices.light)),
+                ("heavy", Arg::Percent(self.prices.heavy)),
+                ("advanced", Arg::Percent(self.prices.advanced)),
+            ],
+        ));
+        lines.push(loc.format(
+            "economy-wages",
+            &[
+                (
+                    "basic",
+                    Arg::Text(self.basic_wage.format_with(&loc.numbers)),
+                ),
+                (
+                    "engineers",
+                    Arg::Text(self.advanced_wage.format_with(&loc.numbers)),
+                ),
+            ],
+        ));
+        lines.push(loc.format(
+            "economy-workers",
+            &[("workers", Arg::Number(self.basic_workers))],
+        ));
+        lines.push(loc.format(
+            "economy-engineers",
+            &[
+                ("engineers", Arg::Number(self.advanced_workers)),
+                ("growth", Arg::Number(self.engineer_growth)),
+            ],
+        ));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(economy: &Economy, name: &str) -> i64 {
+        economy.stats().iter().find(|(x, _)| *x == name).unwrap().1
+    }
+
+    fn economy(prices: Prices) -> Economy {
+        let mut economy = Economy::new();
+        economy.update_dynamic_params(1_000_000);
+        economy.set_prices(prices);
+        economy.update_dynamic_params(1_000_000);
+        economy
+    }
+
+    // Sectors whose goods sell dear invest more, and everyone's wages follow the cost of living
+    #[test]
+    fn investment_follows_prices() {
+        let base = economy(Prices::default());
+        let dear = economy(Prices {
+            light: IPercent::new(2000),
+            ..Prices::default()
+        });
+        assert!(stat(&dear, "light_growth") > stat(&base, "light_growth"));
+        assert!(stat(&dear, "basic_wage") > stat(&base, "basic_wage"));
+        // Which eat the other sectors' profits
+        assert!(stat(&dear, "heavy_growth") < stat(&base, "heavy_growth"));
+
+        // Upkeep is paid in heavy goods
+        let upkeep = economy(Prices {
+            heavy: IPercent::new(3000),
+            ..Prices::default()
+        });
+        assert_eq!(upkeep.infra_depreciation_rate, MAX_DEPRECIATION);
+        assert!(upkeep.demand().heavy < base.demand().heavy);
     }
 }
@@ -1,8 +1,12 @@
 use bevy::prelude::*;
-use rand::rng;
+use rand::{prelude::*, rng};
 use std::collections::HashSet;
 
+use super::markov_chain::{
+    CultureUse, NameCultures, NameModels, NameRng, PlanetNameGenerator, UsedPlanetNames,
+};
 use crate::galaxy::Description;
+use crate::simulation::vizier::AiBundle;
 
 use crate::prelude::*;
 
@@ -10,24 +14,77 @@ use crate::prelude::*;
 pub fn finish_create_colony(
     mut empire_query: Query<&mut Empire, Without<Description>>,
     mut colony_query: Query<(&mut Description, &Colony), Added<Colony>>,
-    mut used_planet_names: ResMut<super::markov_chain::UsedPlanetNames>,
+    mut used_planet_names: ResMut<UsedPlanetNames>,
+    mut name_rng: ResMut<NameRng>,
 ) {
     for (mut desc, colony) in colony_query.iter_mut() {
         let Ok(mut empire) = empire_query.get_mut(colony.owner) else {
             continue;
         };
 
-        desc.name = empire.namegen.next(&mut used_planet_names).clone();
+        desc.name = empire.namegen.next(&mut used_planet_names, &mut name_rng.0);
     }
 }
 
+// Ships are named by their owner's culture too
+pub fn name_new_fleets(
+    empire_query: Query<&Empire>,
+    mut fleet_query: Query<&mut Fleet, Added<Fleet>>,
+    used_planet_names: Res<UsedPlanetNames>,
+    mut name_rng: ResMut<NameRng>,
+) {
+    for mut fleet in fleet_query.iter_mut() {
+        let Ok(empire) = empire_query.get(fleet.owner) else {
+            continue;
+        };
+
+        fleet.name = empire
+            .namegen
+            .next_ship(&used_planet_names, &mut name_rng.0);
+    }
+}
+
+// Conquered colonies are renamed by their new owner
+pub fn rename_conquered_colonies(
+    mut empire_query: Query<&mut Empire, Without<Description>>,
+    mut colony_query: Query<&mut Description, With<Colony>>,
+    mut ev_conquered: EventReader<
+        crate::simulation::fleet_behaviour::invasion::ColonyConqueredEvent,
+    >,
+    mut used_planet_names: ResMut<UsedPlanetNames>,
+    mut name_rng: ResMut<NameRng>,
+) {
+    for ev in ev_conquered.read() {
+        let Ok(mut desc) = colony_query.get_mut(ev.planet_entity) else {
+            continue;
+        };
+        let Ok(mut empire) = empire_query.get_mut(ev.new_owner) else {
+            continue;
+        };
+
+        let old_name = std::mem::replace(
+            &mut desc.name,
+            empire.namegen.next(&mut used_planet_names, &mut name_rng.0),
+        );
+        info!(
+            "{} taken from {:?}, now known as {}",
+            old_name, ev.previous_owner, desc.name
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn place_star_empires(
     mut commands: Commands,
     mut star_query: Query<(Entity, &Star, &mut StarClaim)>,
     mut planet_query: Query<&mut Planet, Without<Star>>,
-    mut used_planet_names: ResMut<super::markov_chain::UsedPlanetNames>,
+    mut used_planet_names: ResMut<UsedPlanetNames>,
     mut player_empire: ResMut<crate::galaxy::empire::PlayerEmpire>,
+    name_cultures: Res<NameCultures>,
+    name_models: Res<NameModels>,
+    mut name_rng: ResMut<NameRng>,
     hypernet: Res<Hypernet>,
+    galaxy_config: Res<GalaxyConfig>,
 ) {
     let num_empires = 24;
 
@@ -36,7 +93,11 @@ pub fn place_star_empires(
 
     let mut rng = rng();
 
-    for _i in 0..num_empires {
+    let cultures = name_cultures
+        .for_use(CultureUse::Empires)
+        .collect::<Vec<_>>();
+
+    for i in 0..num_empires {
         let mut best: Option<(Entity, Entity, i32)> = None;
 
         let dijkstra = hypernet.dijkstra(&claimed_ids);
@@ -68,14 +129,34 @@ pub fn place_star_empires(
 
         if let Some((planet_entity, star_entity, score)) = best {
             if score > 0 {
+                let culture = cultures
+                    .choose(&mut rng)
+                    .expect("there should be a name culture for empires");
+                let namegen = PlanetNameGenerator::new(
+                    culture,
+                    &name_models,
+                    &mut used_planet_names,
+                    &mut name_rng.0,
+                );
                 let new_empire = commands
-                    .spawn(Empire::random(&mut rng, &hypernet, &mut used_planet_names))
+                    .spawn(Empire::random(
+                        &mut rng,
+                        &hypernet,
+                        namegen,
+                        &mut used_planet_names,
+                        &mut name_rng,
+                    ))
                     .id();
 
                 // ~~ temp
                 // give the player an empire
                 if player_empire.empire.is_none() {
                     player_empire.empire = Some(new_empire);
+                } else {
+                    let (difficulty, personality) = galaxy_config.ai_settings(i, &mut rng);
+                    commands
+                        .entity(new_empire)
+                        .insert(AiBundle::new(difficulty, personality));
                 }
 
                 let (_, star, mut star_claim) = star_query.get_mut(star_entity).unwrap();
@@ -4,6 +4,7 @@ use bevy::prelude::*;
 use crate::galaxy::selection::{GalaxySelectable, SystemSelectable};
 use rand::prelude::*;
 
+use super::markov_chain::{CultureUse, NameCultures, NameModels, NameRng, StarNameGenerator};
 use crate::galaxy::Description;
 
 use delaunator::Point;
@@ -16,6 +17,9 @@ pub fn setup_stars(
     galaxy_config: Res<GalaxyConfig>,
     mut hypernet: ResMut<Hypernet>,
     mut galaxy_index: ResMut<GalaxyIndex>,
+    name_cultures: Res<NameCultures>,
+    name_models: Res<NameModels>,
+    mut name_rng: ResMut<NameRng>,
 ) {
     let mut rng = rand::rng();
     let mut points: Vec<Point> = Vec::with_capacity(galaxy_config.max_stars as usize);
@@ -50,9 +54,28 @@ pub fn setup_stars(
         }
     }
 
-    hypernet.build_from_points(&points, 1.5, 0.6);
+    hypernet.build_from_points(&points, &galaxy_config.lanes);
 
-    let mut starname_gen = super::markov_chain::StarNameGenerator::new();
+    let metrics = crate::galaxy::HypernetMetrics::compute(&hypernet);
+    info!("{}", metrics);
+    commands.insert_resource(metrics);
+    commands.insert_resource(crate::galaxy::HypernetAnalysis::compute(&hypernet));
+
+    if galaxy_config.hierarchical_pathfinding {
+        let clusters = crate::galaxy::hypernet_hierarchy::HypernetClusters::build(&hypernet);
+        info!(
+            "Hierarchical pathfinding: {} clusters",
+            clusters.num_clusters()
+        );
+        commands.insert_resource(clusters);
+    }
+
+    let star_culture = name_cultures
+        .for_use(CultureUse::Stars)
+        .next()
+        .expect("there should be a name culture for stars");
+    let mut starname_gen =
+        StarNameGenerator::new(star_culture, &name_models, name_cultures.filters.clone());
 
     for node_id in hypernet.graph.node_indices().collect::<Vec<_>>() {
         let node = hypernet.graph.node_weight(node_id).unwrap();
@@ -65,7 +88,7 @@ pub fn setup_stars(
 
         if is_enabled {
             let mut star = Star::new(
-                &mut starname_gen,
+                starname_gen.next(&mut name_rng.0),
                 node_id.index() as u32,
                 star_pos,
                 Star::random_star_mass(&mut rng),
@@ -99,7 +122,9 @@ pub fn setup_stars(
                     StarClaim {
                         claimed_tick: 0,
                         owner: None,
+                        contested_by: None,
                     },
+                    crate::simulation::influence::SystemInfluence::default(),
                     SystemIndex::default(),
                     OverlaysTriangulationVertex {
                         node_id: node_id.index() as u32,
@@ -136,4 +161,6 @@ pub fn setup_stars(
             ));
         }
     }
+
+    info!("Star names: {}", starname_gen.stats());
 }
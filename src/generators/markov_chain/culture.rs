@@ -0,0 +1,563 @@
+use super::Rejection;
+use crate::prelude::*;
+use bevy::prelude::*;
+use rand::prelude::*;
+use std::collections::HashSet;
+
+// NAME CULTURES
+// What each name generator is trained on, and the rules its names have to pass. See assets/names/cultures.txt
+// Galaxy generation runs before any asset could load, so the file is built in
+
+const CULTURES_SOURCE: &str = include_str!("../../../assets/names/cultures.txt");
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CultureUse {
+    Empires,
+    Stars,
+}
+
+#[derive(Clone, Debug)]
+pub struct NameCulture {
+    pub name: String,
+    pub usage: CultureUse,
+    pub order: usize,
+    pub prior: f32,
+    pub min_length: usize,
+    pub max_length: usize,
+    pub ascii_only: bool,
+    pub subset: IPercent,
+    pub skip_initial: bool,
+    pub variants: usize,
+    pub reject: Vec<String>, // lowercase
+    pub max_copied: Option<usize>,
+    pub max_consonants: Option<usize>,
+    pub max_vowels: Option<usize>,
+    pub max_repeats: Option<usize>,
+    pub reject_echoes: bool,
+    pub known_clusters: bool,
+    pub corpus: Vec<String>,
+    sources: HashSet<String>,          // The corpus, lowercase
+    copied_chunks: HashSet<String>, // Every max_copied + 1 letters in a row of the corpus, lowercase
+    clusters: HashSet<(bool, String)>, // Runs of consonants in the corpus, and whether they start a word
+}
+
+// Rules that go for every culture
+#[derive(Clone, Debug, Default)]
+pub struct NameFilters {
+    pub blocklist: Vec<String>, // lowercase
+    pub reject_near_duplicates: bool,
+}
+
+enum Item {
+    Section {
+        line: usize,
+        header: String,
+    },
+    Entry {
+        line: usize,
+        key: String,
+        value: String,
+    },
+}
+
+#[derive(Resource)]
+pub struct NameCultures {
+    cultures: Vec<NameCulture>,
+    pub filters: NameFilters,
+}
+
+impl NameCulture {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            usage: CultureUse::Empires,
+            order: 3,
+            prior: 0.00001,
+            min_length: 1,
+            max_length: 15,
+            ascii_only: false,
+            subset: IPercent::FULL,
+            skip_initial: false,
+            variants: 1,
+            reject: Vec::new(),
+            max_copied: None,
+            max_consonants: None,
+            max_vowels: None,
+            max_repeats: None,
+            reject_echoes: false,
+            known_clusters: false,
+            corpus: Vec::new(),
+            sources: HashSet::new(),
+            copied_chunks: HashSet::new(),
+            clusters: HashSet::new(),
+        }
+    }
+
+    fn is_vowel(c: char) -> bool {
+        "aeiouyàáâãäåæèéêëìíîïòóôõöøùúûüýÿ".contains(c)
+    }
+
+    // Each run of consonants, and whether it starts the word
+    fn consonant_clusters(letters: &[char]) -> Vec<(bool, String)> {
+        let mut clusters = Vec::new();
+        let mut start = 0;
+        for i in 0..=letters.len() {
+            let ends =
+                i == letters.len() || !letters[i].is_alphabetic() || Self::is_vowel(letters[i]);
+            if ends {
+                if i > start {
+                    let at_start = start == 0 || !letters[start - 1].is_alphabetic();
+                    clusters.push((at_start, letters[start..i].iter().collect()));
+                }
+                start = i + 1;
+            }
+        }
+        clusters
+    }
+
+    fn chunks(letters: &[char], size: usize) -> impl Iterator<Item = String> + '_ {
+        letters.windows(size).map(|x| x.iter().collect())
+    }
+
+    // Once the corpus and max_copied are known
+    fn index_corpus(&mut self) {
+        self.sources = self.corpus.iter().map(|x| x.to_lowercase()).collect();
+        self.copied_chunks.clear();
+        self.clusters.clear();
+        for word in &self.sources {
+            let letters = word.chars().collect::<Vec<_>>();
+            if let Some(max_copied) = self.max_copied {
+                self.copied_chunks
+                    .extend(Self::chunks(&letters, max_copied + 1));
+            }
+            self.clusters.extend(Self::consonant_clusters(&letters));
+        }
+    }
+
+    pub fn is_source(&self, name: &str) -> bool {
+        self.sources.contains(&name.to_lowercase())
+    }
+
+    // Length, copying, pronounceability and the culture's own reject rules
+    // The blocklist and duplicates are checked by the NameRegistry
+    pub fn check(&self, name: &str) -> Result<(), Rejection> {
+        let lowercase = name.to_lowercase();
+        let letters = lowercase.chars().collect::<Vec<_>>();
+        if letters.len() < self.min_length || letters.len() > self.max_length {
+            return Err(Rejection::Length);
+        }
+        if self.reject.iter().any(|x| lowercase.contains(x.as_str())) {
+            return Err(Rejection::Rule);
+        }
+        if let Some(max_copied) = self.max_copied {
+            if Self::chunks(&letters, max_copied + 1).any(|x| self.copied_chunks.contains(&x)) {
+                return Err(Rejection::Copied);
+            }
+        }
+        if !self.pronounceable(&letters) || !Self::capitalised(name) {
+            return Err(Rejection::Unpronounceable);
+        }
+        Ok(())
+    }
+
+    // Capitals only start words, eg. Al-Lat, the chain can pick one up mid-word from the prior
+    fn capitalised(name: &str) -> bool {
+        let chars = name.chars().collect::<Vec<_>>();
+        (1..chars.len()).all(|i| !chars[i].is_uppercase() || !chars[i - 1].is_alphabetic())
+    }
+
+    fn pronounceable(&self, letters: &[char]) -> bool {
+        // Consonants only come together the way they do in the corpus, eg. no word starts with rr
+        if self.known_clusters
+            && !Self::consonant_clusters(letters)
+                .into_iter()
+                .all(|x| self.clusters.contains(&x))
+        {
+            return false;
+        }
+
+        let within = |run: usize, max: Option<usize>| max.is_none_or(|max| run <= max);
+
+        // Runs of consonants, vowels and the same letter, anything that isn't a letter breaks them up
+        let (mut consonants, mut vowels, mut repeats) = (0, 0, 0);
+        for (i, c) in letters.iter().enumerate() {
+            if !c.is_alphabetic() {
+                (consonants, vowels, repeats) = (0, 0, 0);
+                continue;
+            }
+            if Self::is_vowel(*c) {
+                (consonants, vowels) = (0, vowels + 1);
+            } else {
+                (consonants, vowels) = (consonants + 1, 0);
+            }
+            repeats = if i > 0 && letters[i - 1] == *c {
+                repeats + 1
+            } else {
+                1
+            };
+            if !within(consonants, self.max_consonants)
+                || !within(vowels, self.max_vowels)
+                || !within(repeats, self.max_repeats)
+            {
+                return false;
+            }
+        }
+
+        // Echoes are three or more letters said twice in a row, eg. majmaj
+        let echoes = (3..=letters.len() / 2).any(|size| {
+            (0..=letters.len() - size * 2)
+                .any(|i| letters[i..i + size] == letters[i + size..i + size * 2])
+        });
+        !(self.reject_echoes && echoes)
+    }
+
+    // The corpus without the names ascii_only leaves out. Never empty once parsed
+    pub fn usable_corpus(&self) -> impl Iterator<Item = &String> {
+        self.corpus
+            .iter()
+            .filter(|x| !self.ascii_only || x.is_ascii())
+    }
+
+    // For when the generator can't come up with anything usable
+    pub fn fallback_name(&self) -> String {
+        self.usable_corpus()
+            .next()
+            .cloned()
+            .unwrap_or_else(|| self.name.clone())
+    }
+
+    // The part of the corpus one variant trains on, so empires sharing a culture don't all sound the same
+    pub fn training_set(&self, rng: &mut impl Rng) -> Vec<String> {
+        let usable = self.usable_corpus().collect::<Vec<_>>();
+
+        let n = (usable.len() as i64 * self.subset).max(1) as usize;
+        let subset = usable
+            .choose_multiple(rng, n)
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+
+        if !self.skip_initial {
+            return subset;
+        }
+
+        let letter_to_skip = subset.choose(rng).and_then(|x| x.chars().next());
+
+        let with_skipped = subset
+            .iter()
+            .filter(|x| letter_to_skip.is_none_or(|letter| !x.starts_with(letter)))
+            .cloned()
+            .collect::<Vec<String>>();
+
+        if with_skipped.len() > 20 {
+            with_skipped
+        } else {
+            subset
+        }
+    }
+}
+
+impl NameCultures {
+    pub fn built_in() -> Self {
+        Self::parse(CULTURES_SOURCE).expect("built-in name cultures should parse")
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &NameCulture> {
+        self.cultures.iter()
+    }
+
+    pub fn for_use(&self, usage: CultureUse) -> impl Iterator<Item = &NameCulture> {
+        self.cultures.iter().filter(move |x| x.usage == usage)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, String> {
+        // Gather each key's value first, indented lines carry it on
+        let mut items: Vec<Item> = Vec::new();
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if line.starts_with([' ', '\t']) {
+                let Some(Item::Entry { value, .. }) = items.last_mut() else {
+                    return Err(format!(
+                        "line {}: indented line outside of a key",
+                        line_number
+                    ));
+                };
+                value.push(' ');
+                value.push_str(trimmed);
+            } else if let Some(header) = trimmed.strip_prefix('[') {
+                let Some(header) = header.strip_suffix(']') else {
+                    return Err(format!("line {}: unclosed section header", line_number));
+                };
+                items.push(Item::Section {
+                    line: line_number,
+                    header: header.trim().to_string(),
+                });
+            } else if let Some((key, value)) = trimmed.split_once('=') {
+                items.push(Item::Entry {
+                    line: line_number,
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                });
+            } else {
+                return Err(format!(
+                    "line {}: expected `key = value`, got `{}`",
+                    line_number, trimmed
+                ));
+            }
+        }
+
+        let mut cultures: Vec<NameCulture> = Vec::new();
+        let mut filters = NameFilters::default();
+        let mut in_filters = false;
+
+        for item in &items {
+            let (line, key, value) = match item {
+                Item::Section { line, header } => {
+                    in_filters = header == "filters";
+                    if !in_filters {
+                        let Some(name) = header.strip_prefix("culture ") else {
+                            return Err(format!("line {}: unknown section [{}]", line, header));
+                        };
+                        cultures.push(NameCulture::new(name.trim()));
+                    }
+                    continue;
+                }
+                Item::Entry { line, key, value } => (line, key, value),
+            };
+
+            let error = |message: &str| format!("line {}: {} `{}`", line, message, value);
+            let words = || value.split_whitespace().map(|x| x.to_string());
+            let words_lowercase = || words().map(|x| x.to_lowercase()).collect();
+            let yes_no = || match value.as_str() {
+                "yes" => Ok(true),
+                "no" => Ok(false),
+                _ => Err(error("expected yes or no, got")),
+            };
+            let number = || {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| error("expected a number, got"))
+            };
+
+            if in_filters {
+                match key.as_str() {
+                    "blocklist" => filters.blocklist = words_lowercase(),
+                    "reject-near-duplicates" => filters.reject_near_duplicates = yes_no()?,
+                    _ => return Err(format!("line {}: unknown filter `{}`", line, key)),
+                }
+                continue;
+            }
+
+            let Some(culture) = cultures.last_mut() else {
+                return Err(format!("line {}: `{}` outside of a section", line, key));
+            };
+            match key.as_str() {
+                "for" => {
+                    culture.usage = match value.as_str() {
+                        "empires" => CultureUse::Empires,
+                        "stars" => CultureUse::Stars,
+                        _ => return Err(error("expected empires or stars, got")),
+                    }
+                }
+                "order" => culture.order = number()?,
+                "prior" => {
+                    culture.prior = value
+                        .parse()
+                        .map_err(|_| error("expected a decimal number, got"))?
+                }
+                "min-length" => culture.min_length = number()?,
+                "max-length" => culture.max_length = number()?,
+                "ascii-only" => culture.ascii_only = yes_no()?,
+                "subset" => culture.subset = IPercent::new(number()?.min(1000) as i32),
+                "skip-initial" => culture.skip_initial = yes_no()?,
+                "variants" => culture.variants = number()?.max(1),
+                "reject" => culture.reject = words_lowercase(),
+                "max-copied" => culture.max_copied = Some(number()?),
+                "max-consonants" => culture.max_consonants = Some(number()?),
+                "max-vowels" => culture.max_vowels = Some(number()?),
+                "max-repeats" => culture.max_repeats = Some(number()?),
+                "reject-echoes" => culture.reject_echoes = yes_no()?,
+                "known-clusters" => culture.known_clusters = yes_no()?,
+                "corpus" => culture.corpus = words().collect(),
+                _ => return Err(format!("line {}: unknown key `{}`", line, key)),
+            }
+        }
+
+        if let Some(culture) = cultures.iter().find(|x| x.corpus.is_empty()) {
+            return Err(format!("culture {} has no corpus", culture.name));
+        }
+        if let Some(culture) = cultures.iter().find(|x| x.usable_corpus().next().is_none()) {
+            return Err(format!(
+                "culture {} has no ASCII names in its corpus",
+                culture.name
+            ));
+        }
+        for culture in cultures.iter_mut() {
+            culture.index_corpus();
+        }
+
+        Ok(Self { cultures, filters })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORPUS: &str = "corpus = Andromeda Barrion Al-Lat Stella";
+
+    fn culture(rules: &str) -> NameCulture {
+        let source = format!("[culture test]\n{}\n{}", rules, CORPUS);
+        NameCultures::parse(&source).unwrap().cultures.remove(0)
+    }
+
+    fn error_line(source: &str) -> String {
+        let error = NameCultures::parse(source).err().unwrap();
+        error.split(':').next().unwrap().to_string()
+    }
+
+    #[test]
+    fn parses_cultures_and_filters() {
+        let source = "\
+# Comment
+[filters]
+reject-near-duplicates = yes
+blocklist = Bad
+    WORSE
+
+[culture stellar]
+for = stars
+order = 2
+prior = 0.5
+subset = 1500
+variants = 0
+reject = Qu  xx
+max-copied = 4
+corpus = Alcor Mizar
+    # Comments can come between the lines of a value
+    Vega
+
+[culture imperial]
+ascii-only = yes
+corpus = Rome";
+        let cultures = NameCultures::parse(source).unwrap();
+        assert!(cultures.filters.reject_near_duplicates);
+        assert_eq!(cultures.filters.blocklist, ["bad", "worse"]);
+
+        let names = cultures.iter().map(|x| x.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["stellar", "imperial"]);
+        assert_eq!(cultures.for_use(CultureUse::Stars).count(), 1);
+
+        let stellar = cultures.for_use(CultureUse::Stars).next().unwrap();
+        assert_eq!(stellar.order, 2);
+        assert_eq!(stellar.prior, 0.5);
+        assert_eq!(stellar.subset, IPercent::FULL);
+        assert_eq!(stellar.variants, 1);
+        assert_eq!(stellar.reject, ["qu", "xx"]);
+        assert_eq!(stellar.max_copied, Some(4));
+        assert_eq!(stellar.corpus, ["Alcor", "Mizar", "Vega"]);
+        assert!(stellar.is_source("MIZAR"));
+
+        let imperial = cultures.for_use(CultureUse::Empires).next().unwrap();
+        assert!(imperial.ascii_only);
+        assert_eq!(imperial.order, 3);
+        assert_eq!(imperial.max_copied, None);
+    }
+
+    #[test]
+    fn reports_the_failing_line() {
+        assert_eq!(
+            error_line("[culture a]\ncorpus = A\n\nfor = planets"),
+            "line 4"
+        );
+        assert_eq!(
+            error_line("[culture a]\norder = three\ncorpus = A"),
+            "line 2"
+        );
+        assert_eq!(
+            error_line("[culture a]\nascii-only = maybe\ncorpus = A"),
+            "line 2"
+        );
+        assert_eq!(
+            error_line("[culture a]\ncolour = blue\ncorpus = A"),
+            "line 2"
+        );
+        assert_eq!(error_line("[culture a\ncorpus = A"), "line 1");
+        assert_eq!(error_line("[cultures]\ncorpus = A"), "line 1");
+        assert_eq!(error_line("  corpus = A"), "line 1");
+        assert_eq!(error_line("corpus = A"), "line 1");
+        assert_eq!(error_line("[filters]\nreject = a"), "line 2");
+        assert_eq!(error_line("[culture a]\ncorpus = A\nno value"), "line 3");
+        assert_eq!(
+            error_line("[culture a]\n[culture b]\ncorpus = B"),
+            "culture a has no corpus"
+        );
+        assert_eq!(
+            error_line("[culture a]\nascii-only = yes\ncorpus = Ærø Öland"),
+            "culture a has no ASCII names in its corpus"
+        );
+        let mixed = "[culture a]\nascii-only = yes\ncorpus = Ærø Lund";
+        let cultures = NameCultures::parse(mixed).unwrap();
+        assert_eq!(cultures.cultures[0].fallback_name(), "Lund");
+    }
+
+    #[test]
+    fn built_in_cultures_parse() {
+        let cultures = NameCultures::built_in();
+        assert!(cultures.for_use(CultureUse::Stars).count() > 0);
+        assert!(cultures.for_use(CultureUse::Empires).count() > 0);
+        assert!(!cultures.filters.blocklist.is_empty());
+    }
+
+    #[test]
+    fn checks_length_and_reject_rules() {
+        let culture = culture("min-length = 3\nmax-length = 6\nreject = ZZ");
+        assert_eq!(culture.check("Lamedo"), Ok(()));
+        assert_eq!(culture.check("Ba"), Err(Rejection::Length));
+        assert_eq!(culture.check("Lamedos"), Err(Rejection::Length));
+        assert_eq!(culture.check("Bazzo"), Err(Rejection::Rule));
+        assert_eq!(culture.check("BAZZO"), Err(Rejection::Rule));
+    }
+
+    #[test]
+    fn rejects_copied_names() {
+        let culture = culture("max-copied = 3");
+        assert_eq!(culture.check("Lamedo"), Ok(()));
+        assert_eq!(culture.check("Barromi"), Err(Rejection::Copied));
+        assert_eq!(culture.check("Istella"), Err(Rejection::Copied));
+        assert_eq!(culture.check("Andromeda"), Err(Rejection::Copied));
+    }
+
+    #[test]
+    fn rejects_unpronounceable_names() {
+        let runs = culture("max-consonants = 2\nmax-vowels = 2\nmax-repeats = 2");
+        assert_eq!(runs.check("Barrion"), Ok(()));
+        assert_eq!(runs.check("Andromeda"), Err(Rejection::Unpronounceable));
+        assert_eq!(runs.check("Baeia"), Err(Rejection::Unpronounceable));
+        assert_eq!(runs.check("Bae-ia"), Ok(()));
+
+        let repeats = culture("max-repeats = 1");
+        assert_eq!(repeats.check("Barion"), Ok(()));
+        assert_eq!(repeats.check("Barrion"), Err(Rejection::Unpronounceable));
+
+        let echoes = culture("reject-echoes = yes");
+        assert_eq!(echoes.check("Majmaj"), Err(Rejection::Unpronounceable));
+        assert_eq!(echoes.check("Sumajmajta"), Err(Rejection::Unpronounceable));
+        assert_eq!(echoes.check("Mama"), Ok(()));
+        assert_eq!(culture("").check("Majmaj"), Ok(()));
+
+        // Clusters from the corpus, b, l and st can start a word, ndr, m, d, rr, n, l, t and ll go anywhere else
+        let clusters = culture("known-clusters = yes");
+        assert_eq!(clusters.check("Lamedo"), Ok(()));
+        assert_eq!(clusters.check("Stendra"), Ok(()));
+        assert_eq!(clusters.check("Tamedo"), Err(Rejection::Unpronounceable));
+        assert_eq!(clusters.check("Bastella"), Err(Rejection::Unpronounceable));
+        assert_eq!(clusters.check("Al-Stella"), Ok(()));
+
+        assert_eq!(culture("").check("AlLat"), Err(Rejection::Unpronounceable));
+        assert_eq!(culture("").check("Al-Lat"), Ok(()));
+    }
+}
@@ -1,51 +1,56 @@
 use std::collections::{HashMap, HashSet};
 
-use rand::{prelude::*, rng};
+use rand::prelude::*;
 
 // Primary reference - https://www.roguebasin.com/index.php?title=Names_from_a_high_order_Markov_Process_and_a_simplified_Katz_back-off_scheme
 
+// How often each char of the support followed a context, in the support's order
+// The prior is added on top when sampling (this is the cover against sitations with no prior data)
+#[derive(PartialEq, Debug)]
 struct ObservedCount {
-    counts: HashMap<char, f32>,
-    total: f32,
+    counts: Vec<u32>,
+    total: u32,
 }
 
 impl ObservedCount {
-    fn new(support: &HashSet<char>, prior: f32) -> Self {
-        let mut counts = HashMap::<char, f32>::new();
-        for c in support {
-            // base count for all outputs is "prior" (this is the cover against sitations with no prior data)
-            counts.insert(*c, prior);
+    fn new(support_len: usize) -> Self {
+        Self {
+            counts: vec![0; support_len],
+            total: 0,
         }
-
-        let total = counts.len() as f32 * prior;
-
-        Self { counts, total }
     }
 
-    fn observe(&mut self, event: char) {
-        self.counts.insert(event, self.counts[&event] + 1.0);
-        self.total += 1.0;
+    fn observe(&mut self, index: usize) {
+        self.counts[index] += 1;
+        self.total += 1;
     }
 
-    fn sample(&self) -> char {
-        // sample a char from counts, weighted by the associated count/weight
-        let r = rng().random_range(0.0..self.total);
+    fn sample(&self, support: &[char], prior: f32, rng: &mut impl Rng) -> Option<char> {
+        // sample a char from counts, weighted by the associated count + prior
+        // Nothing to sample without counts or a prior, eg. a context read from a file with no chars after it
+        let total = self.total as f32 + prior * support.len() as f32;
+        if !total.is_finite() || total <= 0.0 {
+            return None;
+        }
+        let r = rng.random_range(0.0..total);
 
         let mut running_count = 0.0;
-        for (char, weight) in self.counts.iter() {
-            running_count += weight;
+        for (char, count) in support.iter().zip(self.counts.iter()) {
+            running_count += *count as f32 + prior;
             if running_count > r {
-                return *char;
+                return Some(*char);
             }
         }
-        '#' // error symbol
+        Some('#') // error symbol
     }
 }
 
+#[derive(PartialEq, Debug)]
 pub struct MarkovChainModel {
     counts: HashMap<String, ObservedCount>,
-    support: HashSet<char>,
+    support: Vec<char>, // sorted, so sampling only depends on the rng
     order: usize,
+    prior: f32,
 }
 
 impl MarkovChainModel {
@@ -55,11 +60,13 @@ impl MarkovChainModel {
     pub fn new(order: usize) -> Self {
         Self {
             counts: HashMap::new(),
-            support: HashSet::new(),
+            support: Vec::new(),
             order,
+            prior: 0.0,
         }
     }
-    fn observe(&mut self, word: &str, prior: f32) {
+
+    fn observe(&mut self, word: &str) {
         let chars: Vec<char> = word.chars().collect();
         let mut sequence = Vec::<char>::with_capacity(chars.len() + 1 + self.order);
         for _i in 0..self.order {
@@ -72,18 +79,15 @@ impl MarkovChainModel {
 
         for i in self.order..sequence.len() {
             let context = &sequence[(i - self.order)..i];
-            let event = sequence[i];
+            let event = self.support.binary_search(&sequence[i]).unwrap();
 
             for j in 0..context.len() {
                 let subcontext: String = context[j..context.len()].iter().collect();
-                if !self.counts.contains_key(&subcontext) {
-                    self.counts
-                        .insert(subcontext.clone(), ObservedCount::new(&self.support, prior));
-                }
-
-                let count = self.counts.get_mut(&subcontext).unwrap();
-
-                count.observe(event);
+                let support_len = self.support.len();
+                self.counts
+                    .entry(subcontext)
+                    .or_insert_with(|| ObservedCount::new(support_len))
+                    .observe(event);
             }
         }
     }
@@ -110,17 +114,19 @@ impl MarkovChainModel {
         context.iter().collect()
     }
 
-    fn sample(&self, seq: &[char]) -> Option<char> {
+    fn sample(&self, seq: &[char], rng: &mut impl Rng) -> Option<char> {
         let context = self.backoff(seq);
 
-        self.counts.get(&context).map(|x| x.sample())
+        self.counts
+            .get(&context)
+            .and_then(|x| x.sample(&self.support, self.prior, rng))
     }
 
-    pub fn generate(&self) -> String {
-        self.generate_iter(0)
+    pub fn generate(&self, rng: &mut impl Rng) -> String {
+        self.generate_iter(0, rng)
     }
 
-    fn generate_iter(&self, iter: i32) -> String {
+    fn generate_iter(&self, iter: i32, rng: &mut impl Rng) -> String {
         let mut seq: Vec<char> = Vec::new();
 
         if iter >= 1000 {
@@ -128,10 +134,10 @@ impl MarkovChainModel {
         }
 
         while seq.is_empty() || seq[seq.len() - 1] != Self::ENDCHAR {
-            if let Some(next_sample) = self.sample(&seq) {
+            if let Some(next_sample) = self.sample(&seq, rng) {
                 seq.push(next_sample);
             } else {
-                return self.generate_iter(iter + 1);
+                return self.generate_iter(iter + 1, rng);
             }
         }
 
@@ -141,15 +147,212 @@ impl MarkovChainModel {
     // default for prior 0.01?
 
     pub fn build(&mut self, inputs: &Vec<String>, prior: f32) {
+        let mut support = HashSet::new();
         for word in inputs {
             for ch in word.chars() {
-                self.support.insert(ch);
+                support.insert(ch);
             }
         }
-        self.support.insert(Self::ENDCHAR);
+        support.insert(Self::ENDCHAR);
+        self.support = support.into_iter().collect();
+        self.support.sort();
+        self.prior = prior;
 
         for word in inputs {
-            self.observe(word, prior);
+            self.observe(word);
+        }
+    }
+
+    // Compact binary form, see model_file.rs. Contexts go in order so the same model always writes the same bytes
+    // Only the counts that aren't zero are written
+    pub fn write(&self, out: &mut Vec<u8>) {
+        write_varint(out, self.order as u64);
+        out.extend_from_slice(&self.prior.to_le_bytes());
+        write_varint(out, self.support.len() as u64);
+        for c in &self.support {
+            write_varint(out, *c as u64);
+        }
+
+        let mut contexts = self.counts.iter().collect::<Vec<_>>();
+        contexts.sort_by(|a, b| a.0.cmp(b.0));
+        write_varint(out, contexts.len() as u64);
+        for (context, observed) in contexts {
+            write_varint(out, context.len() as u64);
+            out.extend_from_slice(context.as_bytes());
+            let observed_chars = observed.counts.iter().filter(|x| **x > 0).count();
+            write_varint(out, observed_chars as u64);
+            for (i, count) in observed.counts.iter().enumerate() {
+                if *count > 0 {
+                    write_varint(out, i as u64);
+                    write_varint(out, *count as u64);
+                }
+            }
+        }
+    }
+
+    pub fn read(bytes: &mut &[u8]) -> Result<Self, String> {
+        let order = read_varint(bytes)? as usize;
+        let prior = f32::from_le_bytes(take(bytes, 4)?.try_into().unwrap());
+        if !prior.is_finite() || prior < 0.0 {
+            return Err(format!("invalid prior {}", prior));
+        }
+        let support_len = read_varint(bytes)? as usize;
+        let support = (0..support_len)
+            .map(|_| {
+                let code = read_varint(bytes)?;
+                u32::try_from(code)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid char {}", code))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let num_contexts = read_varint(bytes)? as usize;
+        let mut counts = HashMap::with_capacity(num_contexts);
+        for _ in 0..num_contexts {
+            let context_len = read_varint(bytes)? as usize;
+            let context = std::str::from_utf8(take(bytes, context_len)?)
+                .map_err(|x| x.to_string())?
+                .to_string();
+            let mut observed = ObservedCount::new(support_len);
+            for _ in 0..read_varint(bytes)? {
+                let index = read_varint(bytes)? as usize;
+                let count = u32::try_from(read_varint(bytes)?).map_err(|x| x.to_string())?;
+                let Some(slot) = observed.counts.get_mut(index) else {
+                    return Err(format!("char {} out of the support", index));
+                };
+                *slot = count;
+                observed.total = observed
+                    .total
+                    .checked_add(count)
+                    .ok_or_else(|| format!("counts after {:?} overflow", context))?;
+            }
+            counts.insert(context, observed);
         }
+
+        Ok(Self {
+            counts,
+            support,
+            order,
+            prior,
+        })
+    }
+}
+
+// LEB128, 7 bits at a time with the top bit set while there's more to come
+pub fn write_varint(out: &mut Vec<u8>, mut x: u64) {
+    while x >= 0x80 {
+        out.push((x as u8) | 0x80);
+        x >>= 7;
+    }
+    out.push(x as u8);
+}
+
+pub fn read_varint(bytes: &mut &[u8]) -> Result<u64, String> {
+    let mut x = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = take(bytes, 1)?[0];
+        x |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(x);
+        }
+    }
+    Err("varint too long".to_string())
+}
+
+pub fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], String> {
+    if bytes.len() < n {
+        return Err("unexpected end of file".to_string());
+    }
+    let (taken, rest) = bytes.split_at(n);
+    *bytes = rest;
+    Ok(taken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trained() -> MarkovChainModel {
+        let names = ["Alcor", "Algol", "Mizar", "Vega", "Al-Lat", "Ålesund"];
+        let mut markov = MarkovChainModel::new(2);
+        markov.build(&names.map(|x| x.to_string()).to_vec(), 0.001);
+        markov
+    }
+
+    // Order 1, no prior, a support of just ENDCHAR, then the given contexts and their (char, count) pairs
+    fn model_bytes(contexts: &[(&str, &[(u64, u64)])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_varint(&mut out, 1);
+        out.extend_from_slice(&0.0f32.to_le_bytes());
+        write_varint(&mut out, 1);
+        write_varint(&mut out, MarkovChainModel::ENDCHAR as u64);
+        write_varint(&mut out, contexts.len() as u64);
+        for (context, counts) in contexts {
+            write_varint(&mut out, context.len() as u64);
+            out.extend_from_slice(context.as_bytes());
+            write_varint(&mut out, counts.len() as u64);
+            for (index, count) in *counts {
+                write_varint(&mut out, *index);
+                write_varint(&mut out, *count);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn reads_what_it_writes() {
+        let markov = trained();
+        let mut bytes = Vec::new();
+        markov.write(&mut bytes);
+
+        let mut rest = bytes.as_slice();
+        let read = MarkovChainModel::read(&mut rest).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(read, markov);
+
+        let mut again = Vec::new();
+        read.write(&mut again);
+        assert_eq!(again, bytes);
+
+        let mut a = StdRng::seed_from_u64(0);
+        let mut b = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            assert_eq!(read.generate(&mut a), markov.generate(&mut b));
+        }
+    }
+
+    #[test]
+    fn rejects_broken_files() {
+        let mut bytes = Vec::new();
+        trained().write(&mut bytes);
+        for end in 0..bytes.len() {
+            assert!(MarkovChainModel::read(&mut &bytes[..end]).is_err());
+        }
+
+        let overflowing = model_bytes(&[
+            ("(", &[(0, u32::MAX as u64)]),
+            (")", &[(0, 1), (0, u32::MAX as u64)]),
+        ]);
+        let error = MarkovChainModel::read(&mut overflowing.as_slice())
+            .err()
+            .unwrap();
+        assert_eq!(error, "counts after \")\" overflow");
+
+        let out_of_support = model_bytes(&[("(", &[(1, 1)])]);
+        assert!(MarkovChainModel::read(&mut out_of_support.as_slice()).is_err());
+
+        let mut negative_prior = model_bytes(&[]);
+        negative_prior[1..5].copy_from_slice(&(-1.0f32).to_le_bytes());
+        assert!(MarkovChainModel::read(&mut negative_prior.as_slice()).is_err());
+    }
+
+    // Contexts without counts can't come from training, but a file could have them
+    #[test]
+    fn gives_up_on_contexts_with_nothing_to_sample() {
+        let bytes = model_bytes(&[("", &[]), ("(", &[])]);
+        let markov = MarkovChainModel::read(&mut bytes.as_slice()).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(markov.generate(&mut rng), "FAILED_NAME_GENERATION");
     }
 }
@@ -1,8 +1,37 @@
+mod culture;
 mod markov;
+mod model_file;
+mod name_registry;
+mod name_stats;
 mod planet_name_generator;
 mod star_name_generator;
 
+use bevy::prelude::*;
 use markov::MarkovChainModel;
+use name_registry::NameRegistry;
+use name_stats::{NameStats, Rejection};
+use rand::prelude::*;
 
+pub use culture::{CultureUse, NameCulture, NameCultures, NameFilters};
+pub use model_file::{train_name_models, NameModels};
+pub use name_stats::report_name_stats;
 pub use planet_name_generator::{PlanetNameGenerator, UsedPlanetNames};
 pub use star_name_generator::StarNameGenerator;
+
+// All name generation draws from this, so a seed gives the same names every time
+//   --name-seed <number>
+#[derive(Resource)]
+pub struct NameRng(pub StdRng);
+
+impl NameRng {
+    pub fn with_args(args: &[String]) -> Self {
+        let seed = args
+            .windows(2)
+            .find(|x| x[0] == "--name-seed")
+            .and_then(|x| x[1].parse::<u64>().ok());
+        match seed {
+            Some(seed) => Self(StdRng::seed_from_u64(seed)),
+            None => Self(StdRng::from_os_rng()),
+        }
+    }
+}
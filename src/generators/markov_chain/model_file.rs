@@ -0,0 +1,235 @@
+use super::markov::{read_varint, take, write_varint};
+use super::{MarkovChainModel, NameCulture, NameCultures};
+use bevy::prelude::*;
+use rand::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// PRETRAINED NAME MODELS
+// Every culture's chains are trained ahead of time into assets/names/models/<culture>.markov
+//   cynewulf --train-names
+// and built into the binary like the cultures, so they're there on every platform before any asset could load.
+// A new culture needs its line in BUILT_IN_MODELS. Each file keeps a fingerprint of what it was trained from,
+// cultures without a file or that changed since get trained at startup instead (with a warning)
+//
+// Layout, numbers are LEB128 varints:
+//   "CYNM", format version, fingerprint (8 bytes little endian), number of variants, each variant's model
+
+const MAGIC: &[u8; 4] = b"CYNM";
+const VERSION: u64 = 1;
+const MODELS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/names/models");
+
+const BUILT_IN_MODELS: [(&str, &[u8]); 7] = [
+    (
+        "stellar",
+        include_bytes!("../../../assets/names/models/stellar.markov"),
+    ),
+    (
+        "exoplanet",
+        include_bytes!("../../../assets/names/models/exoplanet.markov"),
+    ),
+    (
+        "latin",
+        include_bytes!("../../../assets/names/models/latin.markov"),
+    ),
+    (
+        "norse",
+        include_bytes!("../../../assets/names/models/norse.markov"),
+    ),
+    (
+        "sumerian",
+        include_bytes!("../../../assets/names/models/sumerian.markov"),
+    ),
+    (
+        "polynesian",
+        include_bytes!("../../../assets/names/models/polynesian.markov"),
+    ),
+    (
+        "japanese",
+        include_bytes!("../../../assets/names/models/japanese.markov"),
+    ),
+];
+
+#[derive(Resource)]
+pub struct NameModels {
+    models: HashMap<String, Vec<Arc<MarkovChainModel>>>,
+}
+
+impl NameModels {
+    pub fn load(cultures: &NameCultures) -> Self {
+        let mut models = HashMap::new();
+        for culture in cultures.iter() {
+            let variants = match BUILT_IN_MODELS
+                .iter()
+                .find(|(name, _)| *name == culture.name)
+                .ok_or_else(|| "no model file built in".to_string())
+                .and_then(|(_, bytes)| decode(culture, bytes))
+            {
+                Ok(variants) => variants,
+                Err(error) => {
+                    warn!(
+                        "Name models for {} not loaded ({}), training them instead. Run with --train-names and rebuild to save them",
+                        culture.name, error
+                    );
+                    train(culture)
+                }
+            };
+            models.insert(
+                culture.name.clone(),
+                variants.into_iter().map(Arc::new).collect(),
+            );
+        }
+        Self { models }
+    }
+
+    pub fn variants(&self, culture: &NameCulture) -> &[Arc<MarkovChainModel>] {
+        &self.models[&culture.name]
+    }
+}
+
+// Written into the source tree, the game only sees them once rebuilt
+fn model_path(culture: &NameCulture) -> PathBuf {
+    PathBuf::from(MODELS_DIR).join(format!("{}.markov", culture.name))
+}
+
+// FNV-1a over everything that goes into training
+fn fingerprint(culture: &NameCulture) -> u64 {
+    let mut bytes = Vec::new();
+    write_varint(&mut bytes, VERSION);
+    write_varint(&mut bytes, culture.order as u64);
+    bytes.extend_from_slice(&culture.prior.to_le_bytes());
+    bytes.push(culture.ascii_only as u8);
+    write_varint(&mut bytes, culture.subset.value() as u64);
+    bytes.push(culture.skip_initial as u8);
+    write_varint(&mut bytes, culture.variants as u64);
+    for word in &culture.corpus {
+        bytes.extend_from_slice(word.as_bytes());
+        bytes.push(0);
+    }
+
+    bytes.iter().fold(0xcbf29ce484222325, |hash, x| {
+        (hash ^ *x as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// Each variant's subset of the corpus is seeded with its index, so training at startup gives the same models as --train-names
+pub fn train(culture: &NameCulture) -> Vec<MarkovChainModel> {
+    (0..culture.variants)
+        .map(|i| {
+            let names = culture.training_set(&mut StdRng::seed_from_u64(i as u64));
+            let mut markov = MarkovChainModel::new(culture.order);
+            markov.build(&names, culture.prior);
+            markov
+        })
+        .collect()
+}
+
+fn encode(culture: &NameCulture, variants: &[MarkovChainModel]) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    write_varint(&mut out, VERSION);
+    out.extend_from_slice(&fingerprint(culture).to_le_bytes());
+    write_varint(&mut out, variants.len() as u64);
+    for markov in variants {
+        markov.write(&mut out);
+    }
+    out
+}
+
+fn decode(culture: &NameCulture, mut bytes: &[u8]) -> Result<Vec<MarkovChainModel>, String> {
+    let bytes = &mut bytes;
+    if take(bytes, 4)? != MAGIC {
+        return Err("not a name model file".to_string());
+    }
+    if read_varint(bytes)? != VERSION {
+        return Err("written by another version".to_string());
+    }
+    if u64::from_le_bytes(take(bytes, 8)?.try_into().unwrap()) != fingerprint(culture) {
+        return Err("the culture changed since it was trained".to_string());
+    }
+    let num_variants = read_varint(bytes)?;
+    (0..num_variants)
+        .map(|_| MarkovChainModel::read(bytes))
+        .collect()
+}
+
+// --train-names, runs instead of the game
+pub fn train_name_models() {
+    let cultures = NameCultures::built_in();
+    for culture in cultures.iter() {
+        let path = model_path(culture);
+        let bytes = encode(culture, &train(culture));
+        let written = std::fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| std::fs::write(&path, &bytes));
+        match written {
+            Ok(()) => println!(
+                "{}: {} variants, {} bytes -> {}",
+                culture.name,
+                culture.variants,
+                bytes.len(),
+                path.display()
+            ),
+            Err(error) => eprintln!(
+                "{}: couldn't write {}: {}",
+                culture.name,
+                path.display(),
+                error
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn culture(corpus: &str) -> NameCulture {
+        let source = format!("[culture test]\norder = 2\nvariants = 3\nsubset = 800\nskip-initial = yes\ncorpus = {}", corpus);
+        NameCultures::parse(&source)
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn models_round_trip() {
+        let culture = culture("Alcor Algol Mizar Vega Deneb Altair Sirius Rigel Betelgeuse");
+        let variants = train(&culture);
+        let bytes = encode(&culture, &variants);
+        assert_eq!(decode(&culture, &bytes).unwrap(), variants);
+
+        // Training is seeded, so a file written earlier still matches
+        assert_eq!(train(&culture), variants);
+        assert_eq!(encode(&culture, &train(&culture)), bytes);
+    }
+
+    // Otherwise the game quietly trains them on every start
+    #[test]
+    fn built_in_models_match_the_cultures() {
+        let cultures = NameCultures::built_in();
+        for culture in cultures.iter() {
+            let bytes = BUILT_IN_MODELS
+                .iter()
+                .find(|(name, _)| *name == culture.name)
+                .unwrap_or_else(|| panic!("no built-in models for {}", culture.name))
+                .1;
+            assert!(
+                decode(culture, bytes).is_ok(),
+                "{} is out of date",
+                culture.name
+            );
+        }
+        assert_eq!(cultures.iter().count(), BUILT_IN_MODELS.len());
+    }
+
+    #[test]
+    fn rejects_files_for_other_cultures() {
+        let culture = culture("Alcor Algol Mizar Vega");
+        let bytes = encode(&culture, &train(&culture));
+        assert!(decode(&self::culture("Alcor Algol Mizar Deneb"), &bytes).is_err());
+        assert!(decode(&culture, &bytes[..bytes.len() - 1]).is_err());
+        assert!(decode(&culture, b"CYNX").is_err());
+    }
+}
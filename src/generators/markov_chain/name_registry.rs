@@ -0,0 +1,305 @@
+use super::{NameCulture, NameFilters, NameStats, Rejection};
+use std::collections::HashSet;
+
+// Every name handed out so far, to keep new ones from clashing with them
+// Near duplicates are one edit apart: a letter added, removed, changed, or two swapped
+// Each name is stored along with every way of deleting one of its letters, two names are near duplicates if they share one of those
+pub struct NameRegistry {
+    names: HashSet<String>,
+    deletions: HashSet<String>,
+    filters: NameFilters,
+}
+
+impl NameRegistry {
+    // Names under this many letters only clash exactly, there aren't enough short ones to go round otherwise
+    const NEAR_DUPLICATE_MIN_LENGTH: usize = 5;
+    const MAX_ATTEMPTS: usize = 1000;
+
+    pub fn new(filters: NameFilters) -> Self {
+        Self {
+            names: HashSet::new(),
+            deletions: HashSet::new(),
+            filters,
+        }
+    }
+
+    fn normalise(name: &str) -> Vec<char> {
+        name.chars()
+            .filter(|x| x.is_alphanumeric())
+            .flat_map(|x| x.to_lowercase())
+            .collect()
+    }
+
+    fn deletions_of(name: &[char]) -> impl Iterator<Item = String> + '_ {
+        (0..name.len()).map(move |i| {
+            name[..i]
+                .iter()
+                .chain(name[i + 1..].iter())
+                .collect::<String>()
+        })
+    }
+
+    pub fn insert(&mut self, name: &str) {
+        let normalised = Self::normalise(name);
+        if normalised.len() >= Self::NEAR_DUPLICATE_MIN_LENGTH {
+            self.deletions.extend(Self::deletions_of(&normalised));
+        }
+        self.names.insert(normalised.into_iter().collect());
+    }
+
+    pub fn is_taken(&self, name: &str) -> bool {
+        let normalised = Self::normalise(name);
+        let whole: String = normalised.iter().collect();
+        if self.names.contains(&whole) {
+            return true;
+        }
+        if !self.filters.reject_near_duplicates
+            || normalised.len() < Self::NEAR_DUPLICATE_MIN_LENGTH
+        {
+            return false;
+        }
+        // One letter added to a name in use, or the same letter of both changed or swapped
+        self.deletions.contains(&whole)
+            || Self::deletions_of(&normalised)
+                .any(|x| self.names.contains(&x) || self.deletions.contains(&x))
+    }
+
+    pub fn is_blocked(&self, name: &str) -> bool {
+        let lowercase = name.to_lowercase();
+        self.filters
+            .blocklist
+            .iter()
+            .any(|x| lowercase.contains(x.as_str()))
+    }
+
+    fn check(&self, culture: &NameCulture, name: &str) -> Result<(), Rejection> {
+        culture.check(name)?;
+        if self.is_blocked(name) {
+            return Err(Rejection::Blocked);
+        }
+        Ok(())
+    }
+
+    // Draws names until one passes the culture's rules, the blocklist and isn't taken, then takes it
+    // If the generator can't come up with one, the last usable draw gets numbered instead
+    pub fn claim(
+        &mut self,
+        culture: &NameCulture,
+        stats: &mut NameStats,
+        mut generate: impl FnMut() -> String,
+    ) -> String {
+        let mut fallback = None;
+        for _ in 0..Self::MAX_ATTEMPTS {
+            let name = generate();
+            stats.generated(!culture.is_source(&name));
+            let checked = self.check(culture, &name).and_then(|_| {
+                if self.is_taken(&name) {
+                    fallback = Some(name.clone());
+                    Err(Rejection::Taken)
+                } else {
+                    Ok(())
+                }
+            });
+            match checked {
+                Ok(()) => {
+                    self.insert(&name);
+                    stats.accepted(&name);
+                    return name;
+                }
+                Err(reason) => stats.rejected(reason),
+            }
+        }
+
+        let base = fallback.unwrap_or_else(|| culture.fallback_name());
+        let name = (2..)
+            .map(|i| format!("{} {}", base, i))
+            .find(|x| {
+                !self
+                    .names
+                    .contains(&Self::normalise(x).into_iter().collect::<String>())
+            })
+            .unwrap();
+        self.insert(&name);
+        stats.accepted(&name);
+        name
+    }
+
+    // A name that passes the rules without being taken, for things that can share names like ships
+    // Falls back on a name from the corpus if the generator can't come up with one
+    pub fn draw(
+        &self,
+        culture: &NameCulture,
+        stats: &mut NameStats,
+        mut generate: impl FnMut() -> String,
+    ) -> String {
+        let name = (0..Self::MAX_ATTEMPTS)
+            .map(|_| generate())
+            .find(|name| {
+                stats.generated(!culture.is_source(name));
+                match self.check(culture, name) {
+                    Ok(()) => true,
+                    Err(reason) => {
+                        stats.rejected(reason);
+                        false
+                    }
+                }
+            })
+            .unwrap_or_else(|| culture.fallback_name());
+        stats.accepted(&name);
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::NameCultures;
+    use super::*;
+    use crate::prelude::*;
+    use rand::prelude::*;
+
+    fn test_cultures(near_duplicates: bool) -> NameCultures {
+        let source = format!(
+            "[filters]\nreject-near-duplicates = {}\nblocklist = Bad\n[culture test]\nmin-length = 3\ncorpus = Andromeda",
+            if near_duplicates { "yes" } else { "no" }
+        );
+        NameCultures::parse(&source).unwrap()
+    }
+
+    fn registry_with(cultures: &NameCultures, names: &[&str]) -> NameRegistry {
+        let mut registry = NameRegistry::new(cultures.filters.clone());
+        for name in names {
+            registry.insert(name);
+        }
+        registry
+    }
+
+    // One letter added, removed or changed, or two next to each other swapped
+    fn one_edit_apart(a: &[char], b: &[char]) -> bool {
+        let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+        match long.len() - short.len() {
+            0 => {
+                let differ = (0..a.len()).filter(|i| a[*i] != b[*i]).collect::<Vec<_>>();
+                match differ[..] {
+                    [_] => true,
+                    [i, j] => j == i + 1 && a[i] == b[j] && a[j] == b[i],
+                    _ => false,
+                }
+            }
+            1 => (0..long.len()).any(|i| long[..i] == short[..i] && long[i + 1..] == short[i..]),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn rejects_near_duplicates() {
+        let cultures = test_cultures(true);
+        let registry = registry_with(&cultures, &["Andromeda", "Baal", "Al-Lat"]);
+        for name in [
+            "Andromeda",
+            "ANDROMEDA",
+            "Andro Meda",
+            "Andromedas",
+            "Andromda",
+            "Andrameda",
+            "Anrdomeda",
+            "Baal",
+            "Allat",
+            "Al-Lats",
+        ] {
+            assert!(registry.is_taken(name), "{}", name);
+        }
+        // Short names only clash exactly
+        for name in ["Andromedans", "Anrdomdea", "Bael", "Lat"] {
+            assert!(!registry.is_taken(name), "{}", name);
+        }
+
+        let cultures = test_cultures(false);
+        let registry = registry_with(&cultures, &["Andromeda"]);
+        assert!(registry.is_taken("Andro-meda"));
+        assert!(!registry.is_taken("Andromedas"));
+    }
+
+    #[test]
+    fn blocks_substrings() {
+        let cultures = test_cultures(true);
+        let registry = registry_with(&cultures, &[]);
+        assert!(registry.is_blocked("Bad"));
+        assert!(registry.is_blocked("Sinbadia"));
+        assert!(registry.is_blocked("SINBAD"));
+        assert!(!registry.is_blocked("Ba-d"));
+
+        let culture = cultures.iter().next().unwrap();
+        let mut stats = NameStats::default();
+        let mut names = ["Badlands", "Goodlands"].into_iter();
+        let name = registry.draw(culture, &mut stats, || names.next().unwrap().to_string());
+        assert_eq!(name, "Goodlands");
+        assert_eq!(stats.rejection_rate(), IPercent::new(500));
+
+        // Nothing usable at all
+        let name = registry.draw(culture, &mut stats, || "Bad".to_string());
+        assert_eq!(name, "Andromeda");
+    }
+
+    // Draws from a handful of letters clash all the time, the claimed names never do
+    #[test]
+    fn claim_never_returns_a_taken_name() {
+        let cultures = test_cultures(true);
+        let culture = cultures.iter().next().unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut registry = registry_with(&cultures, &["Andromeda"]);
+        let mut claimed: Vec<Vec<char>> = vec![NameRegistry::normalise("Andromeda")];
+        let mut stats = NameStats::default();
+
+        for _ in 0..2000 {
+            let name = registry.claim(culture, &mut stats, || {
+                let length = rng.random_range(1..=7);
+                let name = (0..length)
+                    .map(|_| *['a', 'b', 'd', 'e', 'n'].choose(&mut rng).unwrap())
+                    .collect::<String>();
+                name[..1].to_uppercase() + &name[1..]
+            });
+            let normalised = NameRegistry::normalise(&name);
+            assert!(!registry.is_blocked(&name), "{}", name);
+            for other in &claimed {
+                assert!(normalised != *other, "{} was already claimed", name);
+                // Numbered fallbacks only avoid exact clashes
+                if normalised.len() >= NameRegistry::NEAR_DUPLICATE_MIN_LENGTH
+                    && !name.contains(' ')
+                {
+                    assert!(
+                        !one_edit_apart(&normalised, other),
+                        "{} is too close to {}",
+                        name,
+                        other.iter().collect::<String>()
+                    );
+                }
+            }
+            claimed.push(normalised);
+        }
+        assert!(claimed
+            .iter()
+            .any(|x| x.len() >= NameRegistry::NEAR_DUPLICATE_MIN_LENGTH));
+    }
+
+    #[test]
+    fn numbers_names_it_cant_avoid() {
+        let cultures = test_cultures(true);
+        let culture = cultures.iter().next().unwrap();
+        let mut registry = registry_with(&cultures, &["Vega", "Vega 2"]);
+        let mut stats = NameStats::default();
+        assert_eq!(
+            registry.claim(culture, &mut stats, || "Vega".to_string()),
+            "Vega 3"
+        );
+        assert_eq!(
+            registry.claim(culture, &mut stats, || "Vega".to_string()),
+            "Vega 4"
+        );
+        // Nothing usable at all
+        assert_eq!(
+            registry.claim(culture, &mut stats, || "Bad".to_string()),
+            "Andromeda 2"
+        );
+        assert!(registry.is_taken("Vega 4"));
+    }
+}
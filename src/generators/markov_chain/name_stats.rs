@@ -0,0 +1,213 @@
+use super::{NameCultures, NameModels, NameRegistry};
+use crate::prelude::*;
+use rand::prelude::*;
+use std::fmt;
+
+// Why a generated name was thrown away
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rejection {
+    Length,
+    Copied,          // Too much of it comes straight from one source word
+    Unpronounceable, // Too many consonants or vowels in a row, repeated letters or echoes
+    Rule,            // One of the culture's own reject substrings
+    Blocked,
+    Taken,
+}
+
+impl Rejection {
+    const ALL: [Rejection; 6] = [
+        Self::Length,
+        Self::Copied,
+        Self::Unpronounceable,
+        Self::Rule,
+        Self::Blocked,
+        Self::Taken,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Length => "length",
+            Self::Copied => "copied",
+            Self::Unpronounceable => "unpronounceable",
+            Self::Rule => "rule",
+            Self::Blocked => "blocked",
+            Self::Taken => "taken",
+        }
+    }
+}
+
+// What a generator has been up to, to curate corpora and rules with
+#[derive(Clone, Debug, Default)]
+pub struct NameStats {
+    generated: u32,
+    novel: u32, // Raw outputs that aren't a source word
+    accepted: u32,
+    accepted_length: u32,
+    rejections: [u32; 6],
+}
+
+impl NameStats {
+    pub fn generated(&mut self, novel: bool) {
+        self.generated += 1;
+        self.novel += novel as u32;
+    }
+
+    pub fn accepted(&mut self, name: &str) {
+        self.accepted += 1;
+        self.accepted_length += name.chars().count() as u32;
+    }
+
+    pub fn rejected(&mut self, reason: Rejection) {
+        self.rejections[reason as usize] += 1;
+    }
+
+    pub fn add(&mut self, other: &NameStats) {
+        self.generated += other.generated;
+        self.novel += other.novel;
+        self.accepted += other.accepted;
+        self.accepted_length += other.accepted_length;
+        for (x, y) in self.rejections.iter_mut().zip(other.rejections) {
+            *x += y;
+        }
+    }
+
+    pub fn rejection_rate(&self) -> IPercent {
+        IPercent::ratio(
+            (self.generated - self.accepted) as i64,
+            self.generated as i64,
+        )
+    }
+
+    pub fn novelty(&self) -> IPercent {
+        IPercent::ratio(self.novel as i64, self.generated as i64)
+    }
+
+    pub fn average_length(&self) -> Fixed {
+        Fixed::ratio(self.accepted_length as i64, self.accepted as i64)
+    }
+}
+
+impl fmt::Display for NameStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} names from {} draws, {} rejected (",
+            self.accepted,
+            self.generated,
+            self.rejection_rate()
+        )?;
+        let reasons = Rejection::ALL
+            .iter()
+            .filter(|x| self.rejections[**x as usize] > 0)
+            .map(|x| {
+                let share =
+                    IPercent::ratio(self.rejections[*x as usize] as i64, self.generated as i64);
+                format!("{} {}", x.name(), share)
+            })
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{}), {} novel, {:.1} letters on average",
+            reasons.join(", "),
+            self.novelty(),
+            self.average_length()
+        )
+    }
+}
+
+// --name-stats [count], runs instead of the game
+// Draws names from every variant of every culture, the way galaxy generation would, and reports how it went
+pub fn report_name_stats(args: &[String]) {
+    let count = args
+        .windows(2)
+        .find(|x| x[0] == "--name-stats")
+        .and_then(|x| x[1].parse::<usize>().ok())
+        .unwrap_or(200);
+    let cultures = NameCultures::built_in();
+    let models = NameModels::load(&cultures);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for culture in cultures.iter() {
+        let mut total = NameStats::default();
+        for (i, markov) in models.variants(culture).iter().enumerate() {
+            let mut registry = NameRegistry::new(cultures.filters.clone());
+            for source_name in &culture.corpus {
+                registry.insert(source_name);
+            }
+            let mut stats = NameStats::default();
+            let names = (0..count)
+                .map(|_| registry.claim(culture, &mut stats, || markov.generate(&mut rng)))
+                .collect::<Vec<_>>();
+            println!("{} #{}: {}", culture.name, i, stats);
+            println!("    {}", names[..count.min(12)].join(" "));
+            total.add(&stats);
+        }
+        println!("{}: {}", culture.name, total);
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CULTURES: &str = "\
+[filters]
+reject-near-duplicates = yes
+blocklist = bad
+
+[culture test]
+min-length = 3
+max-length = 8
+reject = qu
+max-copied = 3
+max-consonants = 2
+corpus = Andromeda Vega";
+
+    // One draw for each reason to reject a name, then one that makes it
+    fn claim_one(stats: &mut NameStats) -> String {
+        let cultures = NameCultures::parse(CULTURES).unwrap();
+        let culture = cultures.iter().next().unwrap();
+        let mut registry = NameRegistry::new(cultures.filters.clone());
+        registry.insert("Lamedo");
+
+        let mut draws = ["Ab", "Quasi", "Vega", "Astra", "Badia", "Lamedo", "Solara"].into_iter();
+        registry.claim(culture, stats, || draws.next().unwrap().to_string())
+    }
+
+    #[test]
+    fn counts_every_rejection() {
+        let mut stats = NameStats::default();
+        assert_eq!(claim_one(&mut stats), "Solara");
+        assert_eq!(stats.rejections, [1; 6]);
+        assert_eq!(stats.rejection_rate(), IPercent::ratio(6, 7));
+        assert_eq!(stats.novelty(), IPercent::ratio(6, 7));
+        assert_eq!(stats.average_length(), Fixed::from_int(6));
+        assert_eq!(
+            stats.to_string(),
+            "1 names from 7 draws, 85.7% rejected (length 14.3%, copied 14.3%, unpronounceable 14.3%, rule 14.3%, blocked 14.3%, taken 14.3%), 85.7% novel, 6.0 letters on average"
+        );
+    }
+
+    #[test]
+    fn adds_up() {
+        let mut one = NameStats::default();
+        claim_one(&mut one);
+        let mut total = NameStats::default();
+        total.add(&one);
+        total.add(&one);
+        assert_eq!(total.generated, 14);
+        assert_eq!(total.accepted, 2);
+        assert_eq!(total.rejections, [2; 6]);
+        assert_eq!(total.rejection_rate(), one.rejection_rate());
+        assert_eq!(total.average_length(), one.average_length());
+    }
+
+    #[test]
+    fn prints_without_any_draws() {
+        assert_eq!(
+            NameStats::default().to_string(),
+            "0 names from 0 draws, 0.0% rejected (), 0.0% novel, 0.0 letters on average"
+        );
+    }
+}
@@ -10,14 +10,29 @@ pub struct GalaxyGenerationPlugin;
 
 impl Plugin for GalaxyGenerationPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(markov_chain::UsedPlanetNames::default())
-            .add_systems(
-                SimStart,
-                (
-                    galaxy_generation::setup_stars,
-                    empires_placement::place_star_empires.after(galaxy_generation::setup_stars),
-                ),
-            )
-            .add_systems(Update, empires_placement::finish_create_colony);
+        let name_cultures = markov_chain::NameCultures::built_in();
+        app.insert_resource(markov_chain::UsedPlanetNames::new(
+            name_cultures.filters.clone(),
+        ))
+        .insert_resource(markov_chain::NameModels::load(&name_cultures))
+        .insert_resource(markov_chain::NameRng::with_args(
+            &std::env::args().collect::<Vec<_>>(),
+        ))
+        .insert_resource(name_cultures)
+        .add_systems(
+            SimStart,
+            (
+                galaxy_generation::setup_stars,
+                empires_placement::place_star_empires.after(galaxy_generation::setup_stars),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                empires_placement::finish_create_colony,
+                empires_placement::name_new_fleets,
+                empires_placement::rename_conquered_colonies,
+            ),
+        );
     }
 }
@@ -13,7 +13,10 @@ impl Plugin for DrawGalaxyPlugin {
                 BuildGalaxyGraphics,
                 (finish_assemble_star_system, star_gfx, place_galaxy_volume),
             )
-            .add_systems(Update, update_planet_materials);
+            .add_systems(
+                Update,
+                (update_planet_materials, draw_wormholes, draw_bridges),
+            );
     }
 }
 
@@ -123,8 +126,40 @@ fn star_gfx(
     ));
 }
 
+// Wormholes cross the regular lane network, so they aren't part of the territory overlay's triangulation
+fn draw_wormholes(hypernet: Res<Hypernet>, mut gizmos: Gizmos) {
+    for lane in hypernet.graph.edge_indices() {
+        if hypernet.graph.edge_weight(lane).unwrap().lane_type != LaneType::Wormhole {
+            continue;
+        }
+        let (a, b) = hypernet.graph.edge_endpoints(lane).unwrap();
+        let a_pos = hypernet.graph.node_weight(a).unwrap().pos;
+        let b_pos = hypernet.graph.node_weight(b).unwrap().pos;
+        gizmos.line(a_pos, b_pos, Color::srgba(0.3, 0.8, 1.0, 0.5));
+    }
+}
+
+// Bridges are lanes the network splits without, worth knowing about when planning a war
+fn draw_bridges(
+    hypernet: Res<Hypernet>,
+    analysis: Option<Res<crate::galaxy::HypernetAnalysis>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(analysis) = analysis else {
+        return;
+    };
+    for lane in analysis.bridges() {
+        let Some((a, b)) = hypernet.graph.edge_endpoints((*lane).into()) else {
+            continue;
+        };
+        let a_pos = hypernet.graph.node_weight(a).unwrap().pos;
+        let b_pos = hypernet.graph.node_weight(b).unwrap().pos;
+        gizmos.line(a_pos, b_pos, Color::srgba(1.0, 0.6, 0.1, 0.5));
+    }
+}
+
 pub fn draw_system_overlays(stars: Query<&Star>, cam: Res<CameraSettings>, mut gizmos: Gizmos) {
-    /* 
+    /*
     if cam.camera_mode == CameraMode::Star {
         if let Some(star_id) = cam.star {
             if let Ok(star) = stars.get(star_id) {
@@ -12,6 +12,8 @@ pub use ui_materials::StarIconMaterial;
 
 mod territory_overlay;
 
+pub use territory_overlay::OverlayMode;
+
 use draw_galaxy::draw_system_overlays;
 
 impl Plugin for GraphicsPlugin {
@@ -1,5 +1,6 @@
 use delaunator::{triangulate, Point};
 
+use crate::galaxy::Hyperlane;
 use crate::galaxy::{OverlaysTriangulationVertex, Star};
 use crate::prelude::*;
 use bevy::prelude::*;
@@ -21,6 +22,29 @@ use bevy::{
 struct LaneFormat {
     enabled: u32,
     color: Vec3,
+    lane_type: u32,
+}
+
+impl LaneFormat {
+    fn new(lane: &Hyperlane, empire_query: &Query<&Empire>) -> Self {
+        let (lane_type, color) = match lane.lane_type {
+            LaneType::Standard => (0, Vec3::new(1.0, 0.75, 0.0)),
+            LaneType::Wormhole => (1, Vec3::new(0.3, 0.8, 1.0)),
+            LaneType::Gateway(owner) => (
+                2,
+                empire_query
+                    .get(owner)
+                    .map(|x| x.color.to_srgba().to_vec3())
+                    .unwrap_or(Vec3::ONE),
+            ),
+            LaneType::Hazardous => (3, Vec3::new(0.6, 0.25, 0.1)),
+        };
+        Self {
+            enabled: 1,
+            color,
+            lane_type,
+        }
+    }
 }
 
 #[derive(ShaderType, Default, Debug, Clone)]
@@ -97,6 +121,25 @@ struct OverlaysData {
     material_handle: Handle<TerritoryOverlaysMaterial>,
 }
 
+// What the star halos show. Cycled with T
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayMode {
+    #[default]
+    Territory,
+    Threat, // The player's threat map
+    Supply, // How deep inside the player's supply network each system is
+}
+
+impl OverlayMode {
+    pub fn toggle(&mut self) {
+        *self = match self {
+            Self::Territory => Self::Threat,
+            Self::Threat => Self::Supply,
+            Self::Supply => Self::Territory,
+        };
+    }
+}
+
 pub struct OverlaysPlugin;
 
 impl Plugin for OverlaysPlugin {
@@ -104,17 +147,21 @@ impl Plugin for OverlaysPlugin {
         app.add_plugins(MaterialPlugin::<TerritoryOverlaysMaterial>::default())
             .add_systems(BuildGalaxyGraphics, generate_overlays_mesh)
             .add_systems(BuildGalaxyGraphics, update_selection_radius)
+            .init_resource::<OverlayMode>()
             .add_systems(Update, update_overlays);
     }
 }
 
 use crate::galaxy::indexes::SystemIndex;
+use crate::simulation::supply::SupplyNetwork;
+use crate::simulation::threat::ThreatMap;
 
+#[allow(clippy::too_many_arguments)]
 fn update_overlays(
-    star_update_query: Query<(Entity, &StarGfxTag, &StarClaim, &SystemIndex), With<Star>>,
+    star_update_query: Query<(Entity, &StarGfxTag, &StarClaim, &SystemIndex, &Star)>,
     star_changed_update_query: Query<
-        (Entity, &StarGfxTag, &StarClaim, &SystemIndex),
-        (With<Star>, Or<(Changed<StarClaim>, Changed<SystemIndex>)>),
+        (Entity, &StarGfxTag, &StarClaim, &SystemIndex, &Star),
+        Or<(Changed<StarClaim>, Changed<SystemIndex>)>,
     >,
     stars_query: Query<&Star>,
     empire_query: Query<&Empire>,
@@ -124,13 +171,41 @@ fn update_overlays(
     selection: Res<crate::galaxy::Selection>,
     hypernet: Res<Hypernet>,
     cam_query: Query<&crate::camera::CameraMain>,
+    mode: Res<OverlayMode>,
+    player_empire: Res<crate::galaxy::empire::PlayerEmpire>,
+    threat_query: Query<Ref<ThreatMap>>,
+    supply_query: Query<Ref<SupplyNetwork>>,
 ) {
     let cam = cam_query.single().expect("couldn't find camera!");
+    let threat_map = player_empire
+        .empire
+        .and_then(|x| threat_query.get(x).ok())
+        .filter(|_| *mode == OverlayMode::Threat);
+    let supply_network = player_empire
+        .empire
+        .and_then(|x| supply_query.get(x).ok())
+        .filter(|_| *mode == OverlayMode::Supply);
+    let star_colour = |claim: &StarClaim, star: &Star| {
+        if let Some(map) = &threat_map {
+            threat_colour(map.threat(star.node_id))
+        } else if let Some(network) = &supply_network {
+            supply_colour(network.coverage(star.node_id))
+        } else {
+            claim_colour(claim, &empire_query)
+        }
+    };
+
     let mut any_change = false;
-    if selection.is_changed() {
+    if selection.is_changed()
+        || hypernet.is_changed()
+        || mode.is_changed()
+        || threat_map.as_ref().is_some_and(|x| x.is_changed())
+        || supply_network.as_ref().is_some_and(|x| x.is_changed())
+    {
         any_change = true;
-        for edge in &mut overlays_data.edge_data {
-            edge.color = Vec3::new(1.0, 0.75, 0.0);
+        for edge in hypernet.graph.edge_indices() {
+            overlays_data.edge_data[edge.index()] =
+                LaneFormat::new(hypernet.graph.edge_weight(edge).unwrap(), &empire_query);
         }
         if let Some(star_b) = selection.hovered.and_then(|b| stars_query.get(b).ok()) {
             if let Some(star_a) = selection
@@ -149,12 +224,8 @@ fn update_overlays(
         // otherwise only for stars whose starclaim has changed
         // Could be a little more elegant!
 
-        for (entity, tag, claim, system_index) in &star_update_query {
-            let col: Srgba = if let Some(owner) = claim.owner {
-                empire_query.get(owner).unwrap().color.to_srgba()
-            } else {
-                Srgba::new(0.0, 0.0, 0.0, 0.0)
-            };
+        for (entity, tag, claim, system_index, star) in &star_update_query {
+            let col = star_colour(claim, star);
 
             let empire_halo_col = claim
                 .owner
@@ -170,12 +241,8 @@ fn update_overlays(
                 f64::log10(1.0 + system_index.population as f64) as f32;
         }
     } else {
-        for (entity, tag, claim, system_index) in &star_changed_update_query {
-            let col: Srgba = if let Some(owner) = claim.owner {
-                empire_query.get(owner).unwrap().color.to_srgba()
-            } else {
-                Srgba::new(0.0, 0.0, 0.0, 0.0)
-            };
+        for (entity, tag, claim, system_index, star) in &star_changed_update_query {
+            let col = star_colour(claim, star);
 
             let empire_halo_col = claim
                 .owner
@@ -209,6 +276,36 @@ fn update_overlays(
     }
 }
 
+// Contested systems are shown halfway between the owner's and the challenger's colour
+fn claim_colour(claim: &StarClaim, empire_query: &Query<&Empire>) -> Srgba {
+    let Some(owner) = claim.owner else {
+        return Srgba::new(0.0, 0.0, 0.0, 0.0);
+    };
+    let col = empire_query.get(owner).unwrap().color.to_srgba();
+    match claim.contested_by.and_then(|x| empire_query.get(x).ok()) {
+        Some(challenger) => col.mix(&challenger.color.to_srgba(), 0.5),
+        None => col,
+    }
+}
+
+// Unthreatened systems are left clear, the rest fade in from orange to red
+fn threat_colour(threat: IPercent) -> Srgba {
+    let t = threat.value() as f32 / 1000.0;
+    if t <= 0.0 {
+        return Srgba::new(0.0, 0.0, 0.0, 0.0);
+    }
+    Srgba::new(1.0, 0.6 * (1.0 - t), 0.0, 0.3 + 0.7 * t)
+}
+
+// Systems outside the network are left clear, the rest get greener the deeper inside it they are
+fn supply_colour(coverage: IPercent) -> Srgba {
+    let t = coverage.value() as f32 / 1000.0;
+    if t <= 0.0 {
+        return Srgba::new(0.0, 0.0, 0.0, 0.0);
+    }
+    Srgba::new(0.2, 0.5 + 0.5 * t, 0.3, 0.3 + 0.7 * t)
+}
+
 use crate::galaxy::selection::GalaxySelectable;
 fn update_selection_radius(mut query: Query<(&StarGfxTag, &mut GalaxySelectable)>) {
     for (tag, mut selectable) in query.iter_mut() {
@@ -227,6 +324,7 @@ fn generate_overlays_mesh(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<TerritoryOverlaysMaterial>>,
     mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    empire_query: Query<&Empire>,
     hypernet: Res<Hypernet>,
 ) {
     let mut points = Vec::<Point>::new();
@@ -337,12 +435,14 @@ fn generate_overlays_mesh(
     let mut edge_data = vec![
         LaneFormat {
             enabled: 0,
-            color: Vec3::new(1.0, 0.75, 0.0)
+            color: Vec3::new(1.0, 0.75, 0.0),
+            lane_type: 0,
         };
         hypernet.graph.capacity().1
     ];
     for edge in hypernet.graph.edge_indices() {
-        edge_data[edge.index()].enabled = 1;
+        edge_data[edge.index()] =
+            LaneFormat::new(hypernet.graph.edge_weight(edge).unwrap(), &empire_query);
     }
 
     let mesh = meshes.add(
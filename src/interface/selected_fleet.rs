@@ -189,6 +189,7 @@ fn update_widget_system(
                     Action::Jumping => "Jumping",
                     Action::BeingDestroyed => "BeingDestroyed",
                     Action::Colonise(_) => "Colonising",
+                    Action::Invade(_) => "Invading",
                     Action::Idle => "Idle",
                     Action::Move(_) => "Moving",
                 };
@@ -197,10 +198,11 @@ fn update_widget_system(
                     None => "None",
                     Some(plan) => match plan {
                         Plan::Colonise(_) => "Colonise",
+                        Plan::Invade(_) => "Invade",
                         Plan::Jump(_) => "Jump",
                         Plan::ReachHomeEmpire => "ReachHomeEmpire",
                         Plan::ReachPoint(_) => "ReachPoint",
-                        Plan::ReachSystem(_) => "ReachSystem",
+                        Plan::ReachSystem(..) => "ReachSystem",
                     }
                 };
                 
@@ -0,0 +1,101 @@
+use crate::localisation::Localisation;
+use crate::prelude::*;
+use crate::simulation::{DateFormat, SimTime};
+use bevy::prelude::*;
+
+// How the interface writes numbers and dates. Widgets format through this rather than on their own,
+// so switching styles changes every one of them. The locale follows the language unless it's given
+//   --number-style abbreviated|si|words|scientific|exact
+//   --locale en|de|fr
+//   --date-format dmy|mdy|iso|long
+// N, L and C cycle through number styles, languages and date formats
+#[derive(Resource, Default)]
+pub struct Formatting {
+    pub numbers: NumberFormat,
+    pub dates: DateFormat,
+}
+
+impl Formatting {
+    pub fn with_args(mut self, args: &[String]) -> Self {
+        for pair in args.windows(2) {
+            let (arg, value) = (pair[0].as_str(), pair[1].as_str());
+            match arg {
+                "--number-style" => match NumberStyle::parse(value) {
+                    Some(x) => self.numbers.style = x,
+                    None => warn!("--number-style: unknown style {}", value),
+                },
+                "--locale" => match Locale::parse(value) {
+                    Some(x) => self.numbers.locale = x,
+                    None => warn!("--locale: unknown locale {}", value),
+                },
+                "--date-format" => match DateFormat::parse(value) {
+                    Some(x) => self.dates = x,
+                    None => warn!("--date-format: unknown format {}", value),
+                },
+                _ => {}
+            }
+        }
+        self
+    }
+
+    pub fn number(&self, x: i64) -> String {
+        x.format_with(&self.numbers)
+    }
+
+    pub fn date(&self, time: &SimTime) -> String {
+        time.format(self.dates, &self.numbers.locale)
+    }
+}
+
+fn locale_from_language_system(
+    mut formatting: ResMut<Formatting>,
+    localisation: Res<Localisation>,
+) {
+    let args = std::env::args().collect::<Vec<_>>();
+    if !args.iter().any(|x| x == "--locale") {
+        if let Some(locale) = Locale::parse(localisation.language()) {
+            formatting.numbers.locale = locale;
+        }
+    }
+}
+
+fn formatting_input_system(
+    mut formatting: ResMut<Formatting>,
+    mut localisation: ResMut<Localisation>,
+    asset_server: Res<AssetServer>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::KeyN) {
+        formatting.numbers.style = formatting.numbers.style.next();
+    }
+    if keys.just_pressed(KeyCode::KeyL) {
+        let language = localisation.next_language();
+        localisation.set_language(language, &asset_server);
+        if let Some(locale) = Locale::parse(language) {
+            formatting.numbers.locale = locale;
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyC) {
+        formatting.dates = formatting.dates.next();
+    }
+}
+
+// Numbers in messages are formatted the same way as everywhere else
+fn sync_localisation_system(formatting: Res<Formatting>, mut localisation: ResMut<Localisation>) {
+    if formatting.is_changed() {
+        localisation.numbers = formatting.numbers;
+    }
+}
+
+pub struct FormattingPlugin;
+
+impl Plugin for FormattingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Formatting::default().with_args(&std::env::args().collect::<Vec<_>>()))
+            .add_systems(Startup, locale_from_language_system)
+            .add_systems(
+                Update,
+                (formatting_input_system, sync_localisation_system).chain(),
+            );
+    }
+}
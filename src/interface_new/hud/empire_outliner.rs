@@ -1,6 +1,8 @@
 use crate::galaxy::selection::{InterfaceIdentifier, SelectionProxy};
 use crate::galaxy::Description;
 use crate::galaxy::Selection;
+use crate::interface_new::format::Formatting;
+use crate::localisation::{Localisation, LocalisedText};
 use crate::prelude::*;
 use bevy::prelude::*;
 
@@ -34,7 +36,11 @@ impl Plugin for EmpireOutlinerPlugin {
 
 use crate::graphics::StarIconMaterial;
 
-fn setup_widget(mut commands: Commands, mut materials: ResMut<Assets<StarIconMaterial>>) {
+fn setup_widget(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StarIconMaterial>>,
+    loc: Res<Localisation>,
+) {
     commands
         .spawn((
             SelectionPanel,
@@ -81,7 +87,8 @@ fn setup_widget(mut commands: Commands, mut materials: ResMut<Assets<StarIconMat
                 ))
                 .with_children(|parent| {
                     parent.spawn((
-                        Text("IMPERIAL SYSTEMS".to_string()),
+                        LocalisedText("empire-systems"),
+                        Text::default(),
                         BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.5)),
                     ));
                 });
@@ -127,7 +134,7 @@ fn setup_widget(mut commands: Commands, mut materials: ResMut<Assets<StarIconMat
                                 slot: i,
                                 material: mat,
                             },
-                            Text("N/A".to_string()),
+                            Text(loc.get("not-available")),
                             TextFont {
                                 font_size: super::UiConsts::STANDARD_UI_FONT_SIZE,
                                 ..default()
@@ -140,7 +147,7 @@ fn setup_widget(mut commands: Commands, mut materials: ResMut<Assets<StarIconMat
                         ));
                         parent.spawn((
                             SelectionPanelTabDetails { slot: i },
-                            Text("N/A".to_string()),
+                            Text(loc.get("not-available")),
                             TextFont {
                                 font_size: super::UiConsts::STANDARD_UI_FONT_SIZE,
                                 ..default()
@@ -171,8 +178,9 @@ fn update_widget_system(
     player_empire: Res<PlayerEmpire>,
     empires_index: Query<&EmpireIndex>,
     mut star_icon_materials: ResMut<Assets<StarIconMaterial>>,
+    formatting: Res<Formatting>,
 ) {
-    if selection.is_changed() {
+    if selection.is_changed() || formatting.is_changed() {
         let Some(empire) = player_empire.empire else {
             for (mut style, _, _) in root_query.iter_mut() {
                 style.display = Display::None;
@@ -198,7 +206,7 @@ fn update_widget_system(
                 text.0 = format!(
                     "{} ({})",
                     desc[panel.slot as usize].0.name,
-                    desc[panel.slot as usize].1.population.format_big_number()
+                    formatting.number(desc[panel.slot as usize].1.population)
                 );
                 //text.sections[0].value = format!("{} ", desc[panel.slot as usize].0.name);
                 //text.sections[0].style.color = Color::WHITE;
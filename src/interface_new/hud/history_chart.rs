@@ -0,0 +1,198 @@
+use crate::localisation::{Arg, Localisation};
+use crate::prelude::*;
+use crate::simulation::history::{History, HistorySample};
+use bevy::prelude::*;
+
+// Bar chart of the player's empire over the last CHART_MONTHS months. Clicking the title cycles through the metrics
+const CHART_MONTHS: usize = 60;
+const CHART_HEIGHT: f32 = 120.;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ChartMetric {
+    Population,
+    Output,
+    Infrastructure,
+    Colonies,
+    Systems,
+}
+
+impl ChartMetric {
+    fn next(self) -> Self {
+        match self {
+            Self::Population => Self::Output,
+            Self::Output => Self::Infrastructure,
+            Self::Infrastructure => Self::Colonies,
+            Self::Colonies => Self::Systems,
+            Self::Systems => Self::Population,
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            Self::Population => "chart-population",
+            Self::Output => "chart-output",
+            Self::Infrastructure => "chart-infrastructure",
+            Self::Colonies => "chart-colonies",
+            Self::Systems => "chart-systems",
+        }
+    }
+
+    fn value(self, sample: &HistorySample) -> i64 {
+        match self {
+            Self::Population => sample.population,
+            Self::Output => sample.output.light + sample.output.heavy + sample.output.advanced,
+            Self::Infrastructure => {
+                sample.infrastructure.light
+                    + sample.infrastructure.heavy
+                    + sample.infrastructure.advanced
+            }
+            Self::Colonies => sample.colonies,
+            Self::Systems => sample.systems,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ChartTitle {
+    metric: ChartMetric,
+}
+
+#[derive(Component)]
+struct ChartButton;
+
+#[derive(Component)]
+struct ChartBar {
+    slot: usize,
+}
+
+pub struct HistoryChartPlugin;
+
+impl Plugin for HistoryChartPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_widget)
+            .add_systems(Update, update_widget_system);
+    }
+}
+
+fn setup_widget(mut commands: Commands, loc: Res<Localisation>) {
+    commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                position_type: PositionType::Absolute,
+                width: Val::Px(320.),
+                left: Val::Percent(1.),
+                bottom: Val::Percent(1.),
+                top: Val::Auto,
+                right: Val::Auto,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::linear_rgba(0.0, 0.03, 0.08, 0.5)),
+            GlobalZIndex(i32::MAX - 1),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Button,
+                    ChartButton,
+                    Node {
+                        width: Val::Percent(100.),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        ChartTitle {
+                            metric: ChartMetric::Population,
+                        },
+                        Text(loc.get("not-available")),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        Pickable {
+                            should_block_lower: false,
+                            is_hoverable: false,
+                        },
+                    ));
+                });
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::FlexEnd,
+                    width: Val::Percent(100.),
+                    height: Val::Px(CHART_HEIGHT),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for slot in 0..CHART_MONTHS {
+                        parent.spawn((
+                            ChartBar { slot },
+                            Node {
+                                width: Val::Percent(100. / CHART_MONTHS as f32),
+                                height: Val::Px(0.),
+                                margin: UiRect::horizontal(Val::Px(0.5)),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(0.3, 0.6, 1.0)),
+                        ));
+                    }
+                });
+        });
+}
+
+fn update_widget_system(
+    button_query: Query<&Interaction, (Changed<Interaction>, With<ChartButton>)>,
+    mut title_query: Query<(&mut Text, &mut ChartTitle)>,
+    mut bar_query: Query<(&mut Node, &ChartBar)>,
+    history_query: Query<Ref<History>>,
+    player_empire: Res<PlayerEmpire>,
+    loc: Res<Localisation>,
+) {
+    let Ok((mut text, mut title)) = title_query.single_mut() else {
+        return;
+    };
+    let metric_changed = button_query.iter().any(|x| *x == Interaction::Pressed);
+    if metric_changed {
+        title.metric = title.metric.next();
+    }
+
+    let Some(history) = player_empire.empire.and_then(|x| history_query.get(x).ok()) else {
+        return;
+    };
+    if !history.is_changed() && !metric_changed && !loc.is_changed() {
+        return;
+    }
+    let metric = title.metric;
+
+    let samples = history.samples();
+    let recent = samples
+        .iter()
+        .skip(samples.len().saturating_sub(CHART_MONTHS))
+        .map(|x| metric.value(x))
+        .collect::<Vec<_>>();
+    let max = recent.iter().copied().max().unwrap_or(0).max(1);
+
+    let latest = history.latest().map(|x| metric.value(x)).unwrap_or(0);
+    text.0 = loc.format(
+        "chart-title",
+        &[
+            ("metric", Arg::Text(loc.get(metric.message()))),
+            ("latest", Arg::Number(latest)),
+            ("max", Arg::Number(max)),
+        ],
+    );
+
+    // The newest sample goes in the last slot
+    let offset = CHART_MONTHS - recent.len();
+    for (mut node, bar) in bar_query.iter_mut() {
+        let value = bar
+            .slot
+            .checked_sub(offset)
+            .and_then(|x| recent.get(x))
+            .copied()
+            .unwrap_or(0);
+        node.height = Val::Px(CHART_HEIGHT * value as f32 / max as f32);
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 mod empire_outliner;
+mod history_chart;
+mod policy_panel;
 mod system_outliner;
 mod time_widget;
 pub struct HudPlugin;
@@ -39,6 +41,8 @@ impl Plugin for HudPlugin {
         app.add_plugins((
             time_widget::TimeWidgetPlugin,
             empire_outliner::EmpireOutlinerPlugin,
+            history_chart::HistoryChartPlugin,
+            policy_panel::PolicyPanelPlugin,
             system_outliner::SystemOutlinerPlugin,
         ));
     }
@@ -0,0 +1,141 @@
+use crate::localisation::{Arg, Localisation, LocalisedText};
+use crate::prelude::*;
+use crate::simulation::policy::{EconomicPolicies, PolicyKind};
+use bevy::prelude::*;
+
+// The player's economic policies. Clicking a policy starts a reform to its next option
+#[derive(Component)]
+struct PolicyButton {
+    kind: PolicyKind,
+}
+
+pub struct PolicyPanelPlugin;
+
+impl Plugin for PolicyPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_widget)
+            .add_systems(Update, (policy_button_system, update_widget_system).chain());
+    }
+}
+
+fn setup_widget(mut commands: Commands, loc: Res<Localisation>) {
+    commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::FlexStart,
+                width: Val::Px(256.),
+                right: Val::Percent(1.),
+                bottom: Val::Percent(1.),
+                top: Val::Auto,
+                left: Val::Auto,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::linear_rgba(0.0, 0.03, 0.08, 0.5)),
+            GlobalZIndex(i32::MAX - 1),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                LocalisedText("policy-title"),
+                Text::default(),
+                TextFont {
+                    font_size: super::UiConsts::STANDARD_UI_FONT_SIZE,
+                    ..default()
+                },
+            ));
+            for kind in [
+                PolicyKind::Investment,
+                PolicyKind::Education,
+                PolicyKind::Taxation,
+                PolicyKind::Conscription,
+            ] {
+                parent
+                    .spawn((
+                        Button,
+                        PolicyButton { kind },
+                        Node {
+                            width: Val::Percent(100.),
+                            padding: UiRect::all(Val::Px(2.0)),
+                            margin: UiRect::all(Val::Px(1.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.5)),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text(loc.get("not-available")),
+                            TextFont {
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            Pickable {
+                                should_block_lower: false,
+                                is_hoverable: false,
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+fn policy_button_system(
+    button_query: Query<(&Interaction, &PolicyButton), Changed<Interaction>>,
+    mut empire_query: Query<&mut EconomicPolicies>,
+    player_empire: Res<PlayerEmpire>,
+) {
+    let Some(mut policies) = player_empire
+        .empire
+        .and_then(|x| empire_query.get_mut(x).ok())
+    else {
+        return;
+    };
+    for (interaction, button) in &button_query {
+        if *interaction == Interaction::Pressed {
+            let change = policies.current(button.kind).next();
+            if !policies.request(change) {
+                info!("Can't start a reform while another one is under way");
+            }
+        }
+    }
+}
+
+fn update_widget_system(
+    button_query: Query<(&PolicyButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+    empire_query: Query<Ref<EconomicPolicies>>,
+    player_empire: Res<PlayerEmpire>,
+    loc: Res<Localisation>,
+) {
+    let Some(policies) = player_empire.empire.and_then(|x| empire_query.get(x).ok()) else {
+        return;
+    };
+    if !policies.is_changed() && !loc.is_changed() {
+        return;
+    }
+
+    for (button, children) in &button_query {
+        let current = policies.current(button.kind);
+        let policy = Arg::Text(loc.get(button.kind.message()));
+        let option = Arg::Text(loc.get(current.option_message()));
+        let label = match policies.reform() {
+            Some((change, days)) if change.kind() == button.kind => loc.format(
+                "policy-reform",
+                &[
+                    ("policy", policy),
+                    ("option", option),
+                    ("next", Arg::Text(loc.get(change.option_message()))),
+                    ("days", Arg::Number(days)),
+                ],
+            ),
+            _ => loc.format("policy-current", &[("policy", policy), ("option", option)]),
+        };
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.0 = label.clone();
+            }
+        }
+    }
+}
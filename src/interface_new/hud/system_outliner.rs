@@ -1,6 +1,8 @@
+use crate::galaxy::hypernet_analysis::SystemRole;
 use crate::galaxy::selection::{InterfaceIdentifier, SelectionProxy};
 use crate::galaxy::Description;
 use crate::galaxy::{GalaxyConfig, Selection};
+use crate::localisation::{Arg, Localisation};
 use crate::prelude::*;
 use bevy::prelude::*;
 
@@ -31,7 +33,7 @@ impl Plugin for SystemOutlinerPlugin {
     }
 }
 
-fn setup_widget(mut commands: Commands) {
+fn setup_widget(mut commands: Commands, loc: Res<Localisation>) {
     commands
         .spawn((
             SelectionPanel,
@@ -81,7 +83,7 @@ fn setup_widget(mut commands: Commands) {
                         parent.spawn((
                             SelectionPanelTabHeader { slot: i as i32 },
                             BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.2)),
-                            Text("Header text".to_string()),
+                            Text(loc.get("tab-header")),
                             Pickable {
                                 should_block_lower: false,
                                 is_hoverable: false,
@@ -90,7 +92,7 @@ fn setup_widget(mut commands: Commands) {
                         parent.spawn((
                             SelectionPanelTabDetails { slot: i as i32 },
                             BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.75)),
-                            Text("Tab details text".to_string()),
+                            Text(loc.get("tab-details")),
                             Pickable {
                                 should_block_lower: false,
                                 is_hoverable: false,
@@ -101,6 +103,7 @@ fn setup_widget(mut commands: Commands) {
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_widget_system(
     mut root_query: Query<(&mut Node, &mut BackgroundColor, &SelectionPanelTabRoot)>,
     mut header_query: Query<(&mut Text, &SelectionPanelTabHeader), Without<SelectionPanelTabRoot>>,
@@ -115,8 +118,11 @@ fn update_widget_system(
     description_query: Query<&Description, Without<SelectionPanelTabHeader>>,
     star_query: Query<&Star, Without<SelectionPanelTabHeader>>,
     planet_colony_query: Query<(&Planet, Option<&Colony>), Without<SelectionPanelTabHeader>>,
+    claim_query: Query<&StarClaim>,
+    analysis: Option<Res<crate::galaxy::HypernetAnalysis>>,
+    loc: Res<Localisation>,
 ) {
-    if selection.is_changed() {
+    if selection.is_changed() || loc.is_changed() {
         let Some(star_entity) = selection.selected_system else {
             for (mut style, _, _) in root_query.iter_mut() {
                 style.display = Display::None;
@@ -137,14 +143,16 @@ fn update_widget_system(
 
         for (mut text, panel) in header_query.iter_mut() {
             if panel.slot < len {
-                let mut t_name = desc[panel.slot as usize].type_name().to_string();
+                let mut t_name = loc.get(desc[panel.slot as usize].type_name());
                 if let Ok((_planet, Some(colony))) =
                     planet_colony_query.get(star_and_orbiters[panel.slot as usize])
                 {
-                    t_name = format!(
-                        "({}, {})",
-                        colony.population.to_string(),
-                        desc[panel.slot as usize].type_name()
+                    t_name = loc.format(
+                        "body-colony",
+                        &[
+                            ("population", Arg::Number(colony.population.val())),
+                            ("type", Arg::Text(t_name)),
+                        ],
                     );
                 }
                 *text = Text(format!("{} ({})", desc[panel.slot as usize].name, t_name));
@@ -159,19 +167,57 @@ fn update_widget_system(
                         planet_colony_query.get(star_and_orbiters[panel.slot as usize])
                     {
                         let colony_details = if let Some(colony) = colony {
-                            format!("\n\n{}\n\n{}", colony.population.details(), colony.economy)
+                            format!(
+                                "\n\n{}\n\n{}",
+                                colony.population.details(&loc),
+                                colony.economy.details(&loc)
+                            )
                         } else {
                             "".to_string()
                         };
 
-                        *text = Text(format!(
-                            "Size: {} | Insolation: {}{}",
-                            planet.radius, planet.insolation, colony_details
-                        ));
+                        let size = loc.format(
+                            "body-size",
+                            &[
+                                ("size", Arg::Text(planet.radius.to_string())),
+                                ("insolation", Arg::Text(planet.insolation.to_string())),
+                            ],
+                        );
+                        *text = Text(format!("{}{}", size, colony_details));
+                    } else if let Some(analysis) = &analysis {
+                        // the star itself, show what the system is worth strategically
+                        let mut details = loc.format(
+                            "system-centrality",
+                            &[(
+                                "centrality",
+                                Arg::Percent(analysis.betweenness(star.node_id)),
+                            )],
+                        );
+                        if analysis.is_chokepoint(star.node_id) {
+                            details.push_str(&format!(" | {}", loc.get("system-chokepoint")));
+                        }
+                        let owner = claim_query.get(star_entity).ok().and_then(|x| x.owner);
+                        if let Some(owner) = owner {
+                            if let Some(role) = analysis.role(owner, star.node_id) {
+                                let role = match role {
+                                    SystemRole::Frontier => "system-role-frontier",
+                                    SystemRole::Core => "system-role-core",
+                                };
+                                details.push_str(&format!(" | {}", loc.get(role)));
+                            }
+                            if let Some(d) = analysis.border_distance(owner, star.node_id) {
+                                let distance = loc.format(
+                                    "system-border-distance",
+                                    &[("jumps", Arg::Number(d as i64))],
+                                );
+                                details.push_str(&format!(" {}", distance));
+                            }
+                        }
+                        *text = Text(details);
                     } else {
-                        *text = Text(format!(
-                            "Panel Details for body {}",
-                            desc[panel.slot as usize].name
+                        *text = Text(loc.format(
+                            "body-placeholder",
+                            &[("name", Arg::Text(desc[panel.slot as usize].name.clone()))],
                         ));
                     }
 
@@ -65,12 +65,16 @@ fn setup_widget(mut commands: Commands) {
         commands.entity(root).add_child(text_fps);
     }
 }
+use crate::interface_new::format::Formatting;
+use crate::localisation::Localisation;
 use crate::simulation::SimTime;
 use crate::simulation::{SimulationMode, SimulationSettings};
 
 fn update_widget_system(
     sim_time: Res<SimTime>,
     sim_settings: Res<SimulationSettings>,
+    formatting: Res<Formatting>,
+    loc: Res<Localisation>,
     diagnostics: Res<DiagnosticsStore>,
     mut query: Query<(&mut Text, &SimulationWidget)>,
 ) {
@@ -84,29 +88,28 @@ fn update_widget_system(
         .unwrap_or(0.0);
     let fps_str = format!("{fps:.1} ({frame_time:.2} ms)");
 
-    let (day, month, year) = sim_time.to_daymonthyear();
-    //let dmy = format!("{}/{}/{}", day, month, year);
+    let date = formatting.date(&sim_time);
     let speed = if sim_settings.paused {
-        "Paused"
+        "speed-paused"
     } else {
         match sim_settings.mode {
-            SimulationMode::Slow => "Slow",
-            SimulationMode::Normal => "Normal",
-            SimulationMode::Fast => "Fast",
-            SimulationMode::Fastest => "Fastest",
+            SimulationMode::Slow => "speed-slow",
+            SimulationMode::Normal => "speed-normal",
+            SimulationMode::Fast => "speed-fast",
+            SimulationMode::Fastest => "speed-fastest",
         }
     };
 
     for (mut text, widget) in &mut query {
         let label = match widget.ui_slot {
-            0 => "Date:",
-            1 => "Speed:",
-            2 => "FPS:",
-            _ => "Error:",
+            0 => loc.get("time-date"),
+            1 => loc.get("time-speed"),
+            2 => loc.get("time-fps"),
+            _ => "".into(),
         };
         let text_val = match widget.ui_slot {
-            0 => format!("{:2}/{:2}/{:2}", day, month, year),
-            1 => speed.into(),
+            0 => date.clone(),
+            1 => loc.get(speed),
             2 => fps_str.clone(),
             _ => "".into(),
         };
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+pub mod format;
 mod hud;
 mod interface_state;
 mod user_input;
@@ -9,6 +10,7 @@ pub struct InterfacePlugin;
 impl Plugin for InterfacePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
+            format::FormattingPlugin,
             hud::HudPlugin,
             user_input::InputPlugin,
             interface_state::UiStatePlugin,
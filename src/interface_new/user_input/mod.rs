@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 pub struct InputPlugin;
 
+use crate::graphics::OverlayMode;
+use crate::simulation::history::ExportHistoryEvent;
+use crate::simulation::stats_export::ExportStatsEvent;
 use crate::simulation::SimulationSettings;
 
 pub fn time_control_system(
@@ -18,8 +21,40 @@ pub fn time_control_system(
     }
 }
 
+pub fn export_history_input_system(
+    mut ev_export: EventWriter<ExportHistoryEvent>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::F9) {
+        ev_export.write(ExportHistoryEvent);
+    }
+}
+
+pub fn export_stats_input_system(
+    mut ev_export: EventWriter<ExportStatsEvent>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::F10) {
+        ev_export.write(ExportStatsEvent);
+    }
+}
+
+pub fn overlay_mode_system(mut mode: ResMut<OverlayMode>, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::KeyT) {
+        mode.toggle();
+    }
+}
+
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, time_control_system);
+        app.add_systems(
+            Update,
+            (
+                time_control_system,
+                overlay_mode_system,
+                export_history_input_system,
+                export_stats_input_system,
+            ),
+        );
     }
 }
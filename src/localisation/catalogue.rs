@@ -0,0 +1,501 @@
+use crate::prelude::*;
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+use bevy::prelude::*;
+
+use std::collections::HashMap;
+use std::fmt;
+
+// MESSAGE CATALOGUES
+// A small subset of Fluent (https://projectfluent.org): one message per id, with variables and select expressions
+//
+//   # Comment
+//   empire-systems = IMPERIAL SYSTEMS
+//   colony-count = { $count ->
+//       [one] { $count } colony
+//      *[other] { $count } colonies
+//   }
+//
+// Continuation lines are indented, and joined with newlines once their indentation is stripped
+// Text can't start with spaces, use a string literal for those: {"   "}
+
+#[derive(Clone, Debug)]
+enum Element {
+    Text(String),
+    Variable(String),
+    Select {
+        variable: String,
+        variants: Vec<(String, Pattern)>,
+        default: usize,
+    },
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Pattern {
+    elements: Vec<Element>,
+}
+
+// What gets interpolated into a message
+pub enum Arg {
+    Number(i64),
+    Percent(IPercent),
+    Text(String),
+}
+
+// The parts of the language a message gets formatted with
+pub trait Formatter {
+    fn number(&self, x: i64) -> String;
+    fn percent(&self, x: IPercent) -> String;
+    fn plural_category(&self, x: i64) -> &'static str;
+}
+
+impl Pattern {
+    pub fn format(&self, args: &[(&str, Arg)], formatter: &impl Formatter, out: &mut String) {
+        for element in &self.elements {
+            match element {
+                Element::Text(x) => out.push_str(x),
+                Element::Variable(name) => match args.iter().find(|(x, _)| x == name) {
+                    Some((_, Arg::Number(x))) => out.push_str(&formatter.number(*x)),
+                    Some((_, Arg::Percent(x))) => out.push_str(&formatter.percent(*x)),
+                    Some((_, Arg::Text(x))) => out.push_str(x),
+                    None => {
+                        out.push_str("{$");
+                        out.push_str(name);
+                        out.push('}');
+                    }
+                },
+                Element::Select {
+                    variable,
+                    variants,
+                    default,
+                } => {
+                    // Exact matches come first, eg. [0], then plural categories for numbers
+                    let key = args.iter().find(|(x, _)| x == variable).map(|(_, x)| x);
+                    let matches = |wanted: &str| variants.iter().position(|(x, _)| x == wanted);
+                    let chosen = match key {
+                        Some(Arg::Number(x)) => matches(&x.to_string())
+                            .or_else(|| matches(formatter.plural_category(*x))),
+                        Some(Arg::Text(x)) => matches(x),
+                        Some(Arg::Percent(_)) | None => None,
+                    };
+                    variants[chosen.unwrap_or(*default)]
+                        .1
+                        .format(args, formatter, out);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Asset, TypePath, Clone, Default)]
+pub struct Catalogue {
+    messages: HashMap<String, Pattern>,
+}
+
+impl Catalogue {
+    pub fn get(&self, id: &str) -> Option<&Pattern> {
+        self.messages.get(id)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let mut messages = HashMap::new();
+
+        // Gather each message's lines first
+        let mut current: Option<(usize, String, String)> = None;
+        let mut finish = |current: Option<(usize, String, String)>| -> Result<(), ParseError> {
+            if let Some((line, id, value)) = current {
+                let pattern = PatternParser::new(&value)
+                    .pattern(false)
+                    .map_err(|message| ParseError { line, message })?;
+                messages.insert(id, pattern);
+            }
+            Ok(())
+        };
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let is_continuation = line.starts_with([' ', '\t', '}']);
+            if is_continuation && !line.trim().is_empty() {
+                let Some((_, _, value)) = current.as_mut() else {
+                    return Err(ParseError {
+                        line: line_number,
+                        message: "indented line outside of a message".to_string(),
+                    });
+                };
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(line.trim());
+                continue;
+            }
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((id, value)) = line.split_once('=') else {
+                return Err(ParseError {
+                    line: line_number,
+                    message: format!("expected `id = value`, got `{}`", line),
+                });
+            };
+            finish(current.take())?;
+            current = Some((line_number, id.trim().to_string(), value.trim().to_string()));
+        }
+        finish(current)?;
+
+        Ok(Self { messages })
+    }
+}
+
+struct PatternParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl PatternParser {
+    fn new(source: &str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|x| x.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(x) if x == c => {
+                self.pos += 1;
+                Ok(())
+            }
+            x => Err(format!("expected `{}`, found {:?}", c, x)),
+        }
+    }
+
+    // Variants end at the next line that starts another variant, or closes the select
+    fn at_variant_end(&self) -> bool {
+        match self.peek() {
+            Some('}') => true,
+            Some('\n') => self.chars[self.pos..]
+                .iter()
+                .find(|x| !x.is_whitespace())
+                .is_none_or(|x| matches!(x, '[' | '*' | '}')),
+            _ => false,
+        }
+    }
+
+    fn pattern(&mut self, in_variant: bool) -> Result<Pattern, String> {
+        let mut elements = Vec::new();
+        let mut text = String::new();
+        loop {
+            if in_variant && self.at_variant_end() {
+                break;
+            }
+            match self.peek() {
+                None => break,
+                Some('{') => {
+                    self.pos += 1;
+                    if !text.is_empty() {
+                        elements.push(Element::Text(std::mem::take(&mut text)));
+                    }
+                    elements.push(self.placeable()?);
+                }
+                Some('}') => return Err("unexpected `}`".to_string()),
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            elements.push(Element::Text(text));
+        }
+        Ok(Pattern { elements })
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|x| x.is_alphanumeric() || x == '_' || x == '-')
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn placeable(&mut self) -> Result<Element, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') => {
+                self.pos += 1;
+                let start = self.pos;
+                while self.peek().is_some_and(|x| x != '"') {
+                    self.pos += 1;
+                }
+                let literal = self.chars[start..self.pos].iter().collect();
+                self.expect('"')?;
+                self.expect('}')?;
+                Ok(Element::Text(literal))
+            }
+            Some('$') => {
+                self.pos += 1;
+                let variable = self.identifier();
+                self.skip_whitespace();
+                if self.chars[self.pos..].starts_with(&['-', '>']) {
+                    self.pos += 2;
+                    self.select(variable)
+                } else {
+                    self.expect('}')?;
+                    Ok(Element::Variable(variable))
+                }
+            }
+            x => Err(format!("expected a variable or a string, found {:?}", x)),
+        }
+    }
+
+    fn select(&mut self, variable: String) -> Result<Element, String> {
+        let mut variants = Vec::new();
+        let mut default = None;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some('*') => {
+                    self.pos += 1;
+                    default = Some(variants.len());
+                }
+                _ => {}
+            }
+            self.expect('[')?;
+            self.skip_whitespace();
+            let key = self.identifier();
+            self.expect(']')?;
+            while self.peek() == Some(' ') {
+                self.pos += 1;
+            }
+            let pattern = self.pattern(true)?;
+            variants.push((key, pattern));
+        }
+
+        let Some(default) = default else {
+            return Err(format!("select on ${} has no default variant", variable));
+        };
+        Ok(Element::Select {
+            variable,
+            variants,
+            default,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct CatalogueLoader;
+
+impl AssetLoader for CatalogueLoader {
+    type Asset = Catalogue;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Catalogue, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(Catalogue::parse(std::str::from_utf8(&bytes)?)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ftl"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // English plurals, and numbers without any grouping
+    struct Plain;
+
+    impl Formatter for Plain {
+        fn number(&self, x: i64) -> String {
+            x.to_string()
+        }
+
+        fn percent(&self, x: IPercent) -> String {
+            x.to_string()
+        }
+
+        fn plural_category(&self, x: i64) -> &'static str {
+            if x.abs() == 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+    }
+
+    fn format(source: &str, id: &str, args: &[(&str, Arg)]) -> String {
+        let catalogue = Catalogue::parse(source).unwrap();
+        let mut out = String::new();
+        catalogue.get(id).unwrap().format(args, &Plain, &mut out);
+        out
+    }
+
+    fn parse_error(source: &str) -> usize {
+        Catalogue::parse(source).err().unwrap().line
+    }
+
+    #[test]
+    fn formats_placeables() {
+        let source = "colony = ({ $population }, {$type}) at {$growth}";
+        let args = [
+            ("population", Arg::Number(1200)),
+            ("type", Arg::Text("Ocean".to_string())),
+            ("growth", Arg::Percent(IPercent::new(25))),
+        ];
+        assert_eq!(format(source, "colony", &args), "(1200, Ocean) at 2.5%");
+        assert_eq!(
+            format(source, "colony", &[]),
+            "({$population}, {$type}) at {$growth}"
+        );
+        assert_eq!(format(r#"padded = {"   "}x{ "" }"#, "padded", &[]), "   x");
+    }
+
+    #[test]
+    fn selects_variants() {
+        let source = "\
+jumps = { $jumps ->
+    [0] at the border
+    [one] { $jumps } jump away
+   *[other] { $jumps } jumps away
+}
+stance = Stance: { $stance ->
+   *[peace] Peaceful
+    [war] At war
+}.";
+        let jumps = |x| format(source, "jumps", &[("jumps", Arg::Number(x))]);
+        assert_eq!(jumps(0), "at the border");
+        assert_eq!(jumps(1), "1 jump away");
+        assert_eq!(jumps(-1), "-1 jump away");
+        assert_eq!(jumps(7), "7 jumps away");
+        assert_eq!(format(source, "jumps", &[]), "{$jumps} jumps away");
+
+        let stance = |x: &str| format(source, "stance", &[("stance", Arg::Text(x.to_string()))]);
+        assert_eq!(stance("war"), "Stance: At war.");
+        assert_eq!(stance("peace"), "Stance: Peaceful.");
+        assert_eq!(stance("truce"), "Stance: Peaceful.");
+    }
+
+    #[test]
+    fn joins_multiline_values() {
+        let source = "\
+details = Capacity: { $capacity }
+    Births: { $births }
+
+      Deaths: { $deaths }
+next = Next";
+        let args = [
+            ("capacity", Arg::Number(10)),
+            ("births", Arg::Number(2)),
+            ("deaths", Arg::Number(1)),
+        ];
+        assert_eq!(
+            format(source, "details", &args),
+            "Capacity: 10\nBirths: 2\nDeaths: 1"
+        );
+        assert_eq!(format(source, "next", &[]), "Next");
+
+        // A value may start on the next line
+        assert_eq!(
+            format("title =\n    Imperial\n    Systems", "title", &[]),
+            "Imperial\nSystems"
+        );
+    }
+
+    #[test]
+    fn skips_comments() {
+        let source = "\
+# English
+## Outliners
+empire-systems = IMPERIAL SYSTEMS
+  # Not a comment once it's indented
+# Between messages
+body-star = Star # Not a comment either";
+        let catalogue = Catalogue::parse(source).unwrap();
+        assert_eq!(catalogue.messages.len(), 2);
+        assert_eq!(
+            format(source, "empire-systems", &[]),
+            "IMPERIAL SYSTEMS\n# Not a comment once it's indented"
+        );
+        assert_eq!(
+            format(source, "body-star", &[]),
+            "Star # Not a comment either"
+        );
+    }
+
+    #[test]
+    fn misses_unknown_keys() {
+        let catalogue = Catalogue::parse("body-star = Star").unwrap();
+        assert!(catalogue.get("body-planet").is_none());
+        assert!(catalogue.get("body").is_none());
+        assert!(Catalogue::default().get("body-star").is_none());
+    }
+
+    #[test]
+    fn reports_the_failing_line() {
+        assert_eq!(parse_error("a = A\n\nno value here"), 3);
+        assert_eq!(parse_error("  indented = A"), 1);
+        assert_eq!(
+            parse_error("a = A\nb = { $x ->\n    [one] One\n    [other] Other\n}"),
+            2
+        );
+        assert_eq!(parse_error("a = A\nb = { x }"), 2);
+        assert_eq!(parse_error("a = A }"), 1);
+        assert_eq!(parse_error(r#"a = {"unterminated }"#), 1);
+    }
+
+    // Every language parses, and only has messages English has, otherwise a misspelt id would never show
+    #[test]
+    fn shipped_catalogues_parse() {
+        let english = Catalogue::parse(include_str!("../../assets/locales/en.ftl")).unwrap();
+        for source in [
+            include_str!("../../assets/locales/de.ftl"),
+            include_str!("../../assets/locales/fr.ftl"),
+        ] {
+            let catalogue = Catalogue::parse(source).unwrap();
+            for id in catalogue.messages.keys() {
+                assert!(english.get(id).is_some(), "{} isn't in English", id);
+            }
+        }
+    }
+}
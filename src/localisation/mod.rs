@@ -0,0 +1,197 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+mod catalogue;
+
+pub use catalogue::Arg;
+use catalogue::{Catalogue, CatalogueLoader, Formatter};
+
+// LOCALISATION
+// Every string the player sees comes from a message catalogue, assets/locales/<language>.ftl
+// English is also built in, so there's always something to show while catalogues load, or if one misses a message
+//   --language en|de|fr
+
+pub const LANGUAGES: [&str; 3] = ["en", "de", "fr"];
+const FALLBACK_SOURCE: &str = include_str!("../../assets/locales/en.ftl");
+
+#[derive(Resource)]
+pub struct Localisation {
+    language: &'static str,
+    handle: Handle<Catalogue>,
+    catalogue: Catalogue,
+    fallback: Catalogue,
+    pub numbers: NumberFormat, // For numbers interpolated into messages, kept in sync with the interface's formatting
+}
+
+// Sets the text of its entity to a message, whenever the language changes
+#[derive(Component)]
+pub struct LocalisedText(pub &'static str);
+
+struct LanguageRules<'a> {
+    language: &'a str,
+    numbers: &'a NumberFormat,
+}
+
+impl Formatter for LanguageRules<'_> {
+    fn number(&self, x: i64) -> String {
+        x.format_with(self.numbers)
+    }
+
+    fn percent(&self, x: IPercent) -> String {
+        x.to_string()
+            .replace('.', &self.numbers.locale.decimal.to_string())
+    }
+
+    fn plural_category(&self, x: i64) -> &'static str {
+        match self.language {
+            "fr" if x == 0 || x.abs() == 1 => "one",
+            _ if x.abs() == 1 => "one",
+            _ => "other",
+        }
+    }
+}
+
+impl Localisation {
+    pub fn language(&self) -> &'static str {
+        self.language
+    }
+
+    pub fn get(&self, id: &str) -> String {
+        self.format(id, &[])
+    }
+
+    // Messages missing from the catalogue come from the built-in English one, or show their id if that's missing too
+    pub fn format(&self, id: &str, args: &[(&str, Arg)]) -> String {
+        let Some(pattern) = self.catalogue.get(id).or_else(|| self.fallback.get(id)) else {
+            return id.to_string();
+        };
+        let rules = LanguageRules {
+            language: self.language,
+            numbers: &self.numbers,
+        };
+        let mut out = String::new();
+        pattern.format(args, &rules, &mut out);
+        out
+    }
+
+    pub fn set_language(&mut self, language: &'static str, asset_server: &AssetServer) {
+        self.language = language;
+        self.handle = asset_server.load(format!("locales/{}.ftl", language));
+        // Until it's loaded
+        self.catalogue = Catalogue::default();
+    }
+
+    pub fn next_language(&self) -> &'static str {
+        let i = LANGUAGES
+            .iter()
+            .position(|x| *x == self.language)
+            .unwrap_or(0);
+        LANGUAGES[(i + 1) % LANGUAGES.len()]
+    }
+}
+
+fn setup_localisation(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let fallback = Catalogue::parse(FALLBACK_SOURCE).expect("built-in catalogue should parse");
+    let args = std::env::args().collect::<Vec<_>>();
+    let language = args
+        .windows(2)
+        .find(|x| x[0] == "--language")
+        .and_then(|x| LANGUAGES.iter().find(|language| **language == x[1]))
+        .copied()
+        .unwrap_or(LANGUAGES[0]);
+
+    let mut localisation = Localisation {
+        language,
+        handle: Handle::default(),
+        catalogue: Catalogue::default(),
+        fallback,
+        numbers: NumberFormat::default(),
+    };
+    localisation.set_language(language, &asset_server);
+    commands.insert_resource(localisation);
+}
+
+// Picks up the current language's catalogue once it's loaded, and again whenever the file changes
+fn catalogue_loaded_system(
+    mut events: EventReader<AssetEvent<Catalogue>>,
+    catalogues: Res<Assets<Catalogue>>,
+    mut localisation: ResMut<Localisation>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            if *id == localisation.handle.id() {
+                if let Some(catalogue) = catalogues.get(*id) {
+                    localisation.catalogue = catalogue.clone();
+                }
+            }
+        }
+    }
+}
+
+fn update_localised_text_system(
+    mut query: Query<(&mut Text, Ref<LocalisedText>)>,
+    localisation: Res<Localisation>,
+) {
+    for (mut text, message) in query.iter_mut() {
+        if localisation.is_changed() || message.is_added() {
+            text.0 = localisation.get(message.0);
+        }
+    }
+}
+
+pub struct LocalisationPlugin;
+
+impl Plugin for LocalisationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Catalogue>()
+            .init_asset_loader::<CatalogueLoader>()
+            .add_systems(PreStartup, setup_localisation)
+            .add_systems(
+                Update,
+                (catalogue_loaded_system, update_localised_text_system).chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn localisation(language: &'static str, source: &str) -> Localisation {
+        Localisation {
+            language,
+            handle: Handle::default(),
+            catalogue: Catalogue::parse(source).unwrap(),
+            fallback: Catalogue::parse(FALLBACK_SOURCE).unwrap(),
+            numbers: NumberFormat::default(),
+        }
+    }
+
+    #[test]
+    fn falls_back_to_english() {
+        let german = localisation("de", "body-star = Stern");
+        assert_eq!(german.get("body-star"), "Stern");
+        assert_eq!(german.get("body-planet"), "Planet");
+        assert_eq!(german.get("no-such-message"), "no-such-message");
+
+        // While a catalogue loads
+        let loading = Localisation {
+            catalogue: Catalogue::default(),
+            ..localisation("fr", "")
+        };
+        assert_eq!(loading.get("body-star"), "Star");
+    }
+
+    // French counts 0 as singular, even in messages that came from the English catalogue
+    #[test]
+    fn plurals_follow_the_language() {
+        let jumps = |language, x| {
+            localisation(language, "")
+                .format("system-border-distance", &[("jumps", Arg::Number(x))])
+        };
+        assert_eq!(jumps("en", 0), "(0 jumps from the border)");
+        assert_eq!(jumps("fr", 0), "(0 jump from the border)");
+        assert_eq!(jumps("de", 1), "(1 jump from the border)");
+        assert_eq!(jumps("de", 2), "(2 jumps from the border)");
+    }
+}
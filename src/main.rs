@@ -7,6 +7,7 @@ mod simulation;
 //mod interface;
 mod generators;
 mod interface_new;
+mod localisation;
 mod prelude;
 mod util;
 
@@ -15,6 +16,17 @@ use std::env;
 fn main() {
     env::set_var("RUST_BACKTRACE", "1");
 
+    // Pretrains the name generators into assets/names/models, instead of running the game
+    if env::args().any(|x| x == "--train-names") {
+        generators::markov_chain::train_name_models();
+        return;
+    }
+    // Reports how well each name culture's rules go with its models, to curate them
+    if env::args().any(|x| x == "--name-stats") {
+        generators::markov_chain::report_name_stats(&env::args().collect::<Vec<_>>());
+        return;
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins.set(WindowPlugin {
@@ -34,6 +46,7 @@ fn main() {
             simulation::SimulationPlugin,
             galaxy::GalaxySetupPlugin,
             graphics::GraphicsPlugin,
+            localisation::LocalisationPlugin,
             interface_new::InterfacePlugin,
             //interface::InterfacePlugin,
             camera::CameraPlugin,
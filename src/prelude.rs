@@ -6,9 +6,10 @@ pub use crate::galaxy::{
         galaxy_index::{GalaxyIndex, PlanetHandle, StarHandle},
         EmpireIndex, SystemIndex,
     },
-    navigation_filter::{NavigationFilter, NavigationMask},
-    Colony, Economy, Empire, Fleet, GalaxyConfig, Hypernet, Pathfinding, Planet, Selection, Star,
-    StarClaim,
+    navigation_cost::CostProfile,
+    navigation_filter::{NavigationFilter, NavigationMask, SystemAccess},
+    Colony, Diplomacy, Economy, Empire, Fleet, GalaxyConfig, Hypernet, LaneType, PathCache,
+    Pathfinding, Planet, Selection, Star, StarClaim,
 };
 
 pub use crate::simulation::{
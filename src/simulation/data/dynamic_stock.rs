@@ -1,39 +1,37 @@
 use crate::prelude::*;
+use crate::util::number::quantity::{Day, Decade, Period};
 
 #[derive(Default)]
 pub struct DynamicStock {
-    pub stock: i64,
-    stock_frac: i64, // 3650ths of a stock. This could use IFraction but there isn't really any need to.
-    change_per_decade: i64,
+    pub stock: People,
+    stock_frac: Fixed, // Fraction of a unit carried over between days, so slow changes still add up
+    change_per_day: Fixed,
 }
 
 impl std::string::ToString for DynamicStock {
     fn to_string(&self) -> String {
-        self.stock.format_big_number()
+        self.stock.value().format_big_number()
     }
 }
 
 impl DynamicStock {
     pub fn new(val: i64) -> Self {
         DynamicStock {
-            stock: val,
-            stock_frac: 0,
-            change_per_decade: 0,
+            stock: People::new(val),
+            stock_frac: Fixed::ZERO,
+            change_per_day: Fixed::ZERO,
         }
     }
     pub fn set(&mut self, val: i64) {
-        self.stock = val;
-        self.stock_frac = 0;
+        self.stock = People::new(val);
+        self.stock_frac = Fixed::ZERO;
     }
 
     pub fn increment_daily(&mut self) {
-        let fr = self.stock_frac + self.change_per_decade;
-
-        self.stock_frac = fr % 3650;
-        self.stock += fr / 3650;
+        self.stock_frac = self.stock.add_fixed(self.stock_frac + self.change_per_day);
     }
 
-    pub fn set_change_per_decade(&mut self, rate: i64) {
-        self.change_per_decade = rate;
+    pub fn set_change_per_decade(&mut self, change: Fixed) {
+        self.change_per_day = change.mul_div(Day::DAYS, Decade::DAYS);
     }
 }
@@ -1,15 +1,19 @@
 use super::dynamic_stock::DynamicStock;
+use crate::localisation::{Arg, Localisation};
 use crate::prelude::*;
+use crate::util::number::quantity::Year;
 
 pub struct Population {
     pop: DynamicStock,
     planet_capacity: i64,
-    birth_rate: IFraction,
-    death_rate: IFraction,
-    births: i64,
-    deaths: i64,
+    birth_rate: DecadeRate,
+    death_rate: DecadeRate,
+    births: Fixed, // Per decade
+    deaths: Fixed,
 }
 
+const BIRTH_RATE: DecadeRate = DecadeRate::per_mille(200);
+
 impl std::string::ToString for Population {
     fn to_string(&self) -> String {
         self.pop.to_string()
@@ -21,49 +25,57 @@ impl Population {
         Self {
             pop: DynamicStock::new(raw),
             planet_capacity: 0,
-            birth_rate: IFraction::new(0, 1),
-            death_rate: IFraction::new(0, 1),
-            births: 0,
-            deaths: 0,
+            birth_rate: DecadeRate::ZERO,
+            death_rate: DecadeRate::ZERO,
+            births: Fixed::ZERO,
+            deaths: Fixed::ZERO,
         }
     }
     pub fn add(&mut self, val: i64) {
-        self.pop.stock += val;
+        self.pop.stock += People::new(val);
     }
     pub fn set(&mut self, val: i64) {
-        self.pop.stock = val;
+        self.pop.stock = People::new(val);
     }
     pub fn val(&self) -> i64 {
-        self.pop.stock
+        self.pop.stock.value()
+    }
+    // Births and deaths per decade, at the latest rates
+    pub fn births(&self) -> i64 {
+        self.births.round()
     }
-    pub fn decade_birth_rate(&self) -> i64 {
-        (self.pop.stock * 10) / 50
+    pub fn deaths(&self) -> i64 {
+        self.deaths.round()
     }
 
-    pub fn details(&self) -> String {
-        format!("Capacity: {}\nBirth Rate: {:<7} | Death Rate: {:<7}\nBirths: {:<7} | Deaths: {:<7} | Net: {:<7}",
-            self.planet_capacity.format_big_number(),
-            self.birth_rate.display_as_percent(),
-            self.death_rate.display_as_percent(),
-            self.births.format_big_number(),
-            self.deaths.format_big_number(),
-            (self.births-self.deaths).format_big_number())
+    pub fn details(&self, loc: &Localisation) -> String {
+        let yearly =
+            |rate: DecadeRate| Arg::Percent(IPercent::from_fixed(rate.to::<Year>().share()));
+        loc.format(
+            "population-details",
+            &[
+                ("capacity", Arg::Number(self.planet_capacity)),
+                ("birth_rate", yearly(self.birth_rate)),
+                ("death_rate", yearly(self.death_rate)),
+                ("births", Arg::Number(self.births())),
+                ("deaths", Arg::Number(self.deaths())),
+                ("net", Arg::Number((self.births - self.deaths).round())),
+            ],
+        )
     }
 
     pub fn increment_daily(&mut self, planet: &Planet) {
         self.planet_capacity = planet.get_population_support() as i64;
-        self.birth_rate = IFraction::new(10, 50);
-
-        let birth = self.pop.stock * IFraction::new(10, 50); //;self.decade_birth_rate();
-        let mut death_rate = IFraction::new(self.pop.stock * 10, self.planet_capacity * 50); //IFraction = IFraction::new(self.pop.stock * 10, self.planet_capacity * 50);
-        death_rate.brute_simplify();
-        self.death_rate = death_rate;
-        let death = self.pop.stock * death_rate;
+        self.birth_rate = BIRTH_RATE;
+        // Deaths catch up with births as the population reaches the planet's capacity
+        self.death_rate = DecadeRate::new(
+            BIRTH_RATE.share() * Fixed::ratio(self.pop.stock.value(), self.planet_capacity),
+        );
 
-        self.births = birth;
-        self.deaths = death;
+        self.births = self.birth_rate.flow(self.pop.stock);
+        self.deaths = self.death_rate.flow(self.pop.stock);
 
-        self.pop.set_change_per_decade(birth - death);
+        self.pop.set_change_per_decade(self.births - self.deaths);
         self.pop.increment_daily();
     }
 }
@@ -0,0 +1,42 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use super::SimTime;
+
+// Once a year, empires contesting each other's systems go to war
+// A war ends once neither side contests anything the other owns
+pub fn update_wars_system(
+    mut empire_query: Query<(Entity, &mut Diplomacy), With<Empire>>,
+    star_query: Query<&StarClaim>,
+    sim_time: Res<SimTime>,
+) {
+    if !sim_time.is_new_year() {
+        return;
+    }
+
+    let mut disputes: HashSet<(Entity, Entity)> = HashSet::new();
+    for claim in &star_query {
+        if let (Some(owner), Some(challenger)) = (claim.owner, claim.contested_by) {
+            disputes.insert((owner, challenger));
+            disputes.insert((challenger, owner));
+        }
+    }
+
+    for (empire, mut diplomacy) in empire_query.iter_mut() {
+        let mut at_war = disputes
+            .iter()
+            .filter(|(a, _)| *a == empire)
+            .map(|(_, b)| *b)
+            .collect::<Vec<_>>();
+        at_war.sort();
+
+        // Only touch the component when something changed, it triggers a full navigation mask rebuild
+        if diplomacy.at_war != at_war {
+            for enemy in at_war.iter().filter(|x| !diplomacy.at_war.contains(x)) {
+                info!("{:?} declares war on {:?}", empire, enemy);
+            }
+            diplomacy.at_war = at_war;
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::galaxy::{Goods, Prices};
+use crate::prelude::*;
+use bevy::prelude::*;
+
+use std::collections::HashMap;
+
+use crate::simulation::SimTime;
+
+// Prices stay within 10% and 1000% of the base price
+const MIN_PRICE: IPercent = IPercent::new(100);
+const MAX_PRICE: IPercent = IPercent::new(10000);
+// How far prices move towards balancing supply and demand each month
+// At 25%, a market with twice the demand it has supply gets ~8% pricier per month
+const PRICE_ADJUSTMENT: IPercent = IPercent::new(250);
+
+// Per empire, all its colonies trade on one market
+#[derive(Component, Default)]
+pub struct Market {
+    pub prices: Prices,
+    pub supply: Goods,
+    pub demand: Goods,
+}
+
+// Moves the price by the relative imbalance between demand and supply
+fn adjust_price(price: IPercent, supply: i64, demand: i64) -> IPercent {
+    let total = (supply + demand).max(1);
+    let change = price * IPercent::ratio(demand - supply, total) * PRICE_ADJUSTMENT;
+    (price + change).clamp(MIN_PRICE, MAX_PRICE)
+}
+
+// Monthly. Adds up what each empire's colonies produce and want, sets new prices, then hands them back to the colonies
+pub fn update_markets_system(
+    mut market_query: Query<(Entity, &mut Market)>,
+    mut colony_query: Query<&mut Colony>,
+    sim_time: Res<SimTime>,
+) {
+    if !sim_time.is_new_month() {
+        return;
+    }
+
+    let mut totals: HashMap<Entity, (Goods, Goods)> = HashMap::new();
+    for colony in &colony_query {
+        let (supply, demand) = totals.entry(colony.owner).or_default();
+        *supply += colony.economy.supply();
+        *demand += colony.economy.demand();
+    }
+
+    for (empire, mut market) in market_query.iter_mut() {
+        let (supply, demand) = totals.get(&empire).copied().unwrap_or_default();
+        market.prices = Prices {
+            light: adjust_price(market.prices.light, supply.light, demand.light),
+            heavy: adjust_price(market.prices.heavy, supply.heavy, demand.heavy),
+            advanced: adjust_price(market.prices.advanced, supply.advanced, demand.advanced),
+        };
+        market.supply = supply;
+        market.demand = demand;
+    }
+
+    for mut colony in colony_query.iter_mut() {
+        if let Ok((_, market)) = market_query.get(colony.owner) {
+            colony.economy.set_prices(market.prices);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_follow_the_imbalance() {
+        let base = IPercent::FULL;
+        assert_eq!(adjust_price(base, 100, 100), base);
+        assert_eq!(adjust_price(base, 0, 0), base);
+        // A third of the trade is unmet demand, a quarter of which moves the price
+        assert_eq!(adjust_price(base, 100, 200).value(), 1083);
+        assert_eq!(adjust_price(base, 200, 100).value(), 917);
+
+        let (mut scarce, mut glut) = (base, base);
+        for _ in 0..1000 {
+            scarce = adjust_price(scarce, 0, 100);
+            glut = adjust_price(glut, 100, 0);
+        }
+        assert_eq!(scarce, MAX_PRICE);
+        assert_eq!(glut, MIN_PRICE);
+    }
+
+    fn colony(world: &mut World, owner: Entity, population: i64) {
+        let mut colony = Colony {
+            owner,
+            claimed_tick: 0,
+            population: Population::new(population),
+            economy: Economy::new(),
+        };
+        // Once to find workers, then once more for them to produce anything
+        colony.economy.update_dynamic_params(population);
+        colony.economy.update_dynamic_params(population);
+        world.spawn(colony);
+    }
+
+    #[test]
+    fn markets_trade_their_empires_goods() {
+        let mut world = World::new();
+        world.insert_resource(SimTime::new());
+        let empires = [
+            world.spawn(Market::default()).id(),
+            world.spawn(Market::default()).id(),
+        ];
+        colony(&mut world, empires[0], 1_000_000);
+        colony(&mut world, empires[0], 4_000_000);
+        colony(&mut world, empires[1], 9_000_000);
+        // Belongs to an empire without a market
+        let stateless = world.spawn_empty().id();
+        colony(&mut world, stateless, 1_000_000);
+
+        let mut totals = HashMap::<Entity, (Goods, Goods)>::new();
+        let mut query = world.query::<&Colony>();
+        for colony in query.iter(&world) {
+            let (supply, demand) = totals.entry(colony.owner).or_default();
+            *supply += colony.economy.supply();
+            *demand += colony.economy.demand();
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(update_markets_system);
+        schedule.run(&mut world);
+
+        for empire in empires {
+            let (supply, demand) = totals[&empire];
+            let market = world.get::<Market>(empire).unwrap();
+            assert_eq!(market.supply.light, supply.light);
+            assert_eq!(market.supply.heavy, supply.heavy);
+            assert_eq!(market.demand.advanced, demand.advanced);
+            let base = IPercent::FULL;
+            assert_eq!(
+                market.prices.light,
+                adjust_price(base, supply.light, demand.light)
+            );
+            assert_eq!(
+                market.prices.heavy,
+                adjust_price(base, supply.heavy, demand.heavy)
+            );
+            assert_eq!(
+                market.prices.advanced,
+                adjust_price(base, supply.advanced, demand.advanced)
+            );
+        }
+        // Wages don't stretch past light goods yet, and there are no engineers to make advanced ones
+        let market = world.get::<Market>(empires[0]).unwrap();
+        assert_eq!(market.supply.advanced, 0);
+        assert_eq!(market.demand.advanced, 0);
+        assert_eq!(market.prices.advanced, IPercent::FULL);
+    }
+}
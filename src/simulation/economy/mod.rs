@@ -1 +1,2 @@
 pub mod demography_system;
+pub mod market;
@@ -1,7 +1,12 @@
 use crate::prelude::*;
+use crate::simulation::mission::OnMission;
+use crate::simulation::threat::ThreatMap;
 use bevy::ecs::batching::BatchingStrategy;
 use bevy::prelude::*;
-use rand::prelude::*;
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 
 use crate::galaxy::fleet::FleetColonyCrew;
 
@@ -22,96 +27,221 @@ pub fn nav_update_task_system(
         if let Some(planet_id) = crew.destination {
             let planet = planet_query.get(planet_id).unwrap();
             nav.plan_queue.push(Plan::Colonise(planet_id));
-            nav.plan_queue.push(Plan::ReachSystem(planet.star_id));
+            // colony ships are civilian, so they keep away from hostile fleets
+            nav.plan_queue
+                .push(Plan::ReachSystem(planet.star_id, CostProfile::Safe));
         }
     }
 }
 
+// Planets a colony ship is already heading for, per empire, so no two ships of an empire race for the same one
+// Rebuilt every tick from the ships' destinations. Only uncolonised planets are reserved, colonies can take any number of settlers
+#[derive(Resource, Default)]
+pub struct ColonyTargetReservations {
+    reserved: HashMap<(Entity, Entity), Entity>, // (empire, planet) -> colony ship
+}
+
+impl ColonyTargetReservations {
+    // Whether another ship of the empire has the planet
+    pub fn is_reserved(&self, empire: Entity, planet: Entity, fleet: Entity) -> bool {
+        self.reserved
+            .get(&(empire, planet))
+            .is_some_and(|x| *x != fleet)
+    }
+}
+
+// Extra distance added to every target, so capacity still matters for planets next door
+const TARGET_DISTANCE_BIAS: i32 = 20 * GalaxyConfig::GALACTIC_INTEGER_SCALE; // 20 parsecs
+
+// How much a colony ship wants to go to a planet. Higher is better, 0 if there's no room
+// Free room (people), scaled by the system's security, over distance
+fn target_score(room: i64, distance: i32, security: IPercent) -> i64 {
+    (room.max(0) * security) / (distance + TARGET_DISTANCE_BIAS) as i64
+}
+
+// Drops the ship's target and whatever it was doing to get there. It picks the next best target on its next search
+pub fn drop_target(crew: &mut FleetColonyCrew, nav: &mut Navigator) {
+    crew.destination = None;
+    nav.plan_queue.clear();
+    if let Action::Move(_) = nav.action {
+        nav.action = Action::Idle;
+    }
+}
+
+// Same, but the ship won't consider that target again while "claim" (its system's) stays as it is
+pub fn abandon_target(crew: &mut FleetColonyCrew, nav: &mut Navigator, claim: &StarClaim) {
+    if let Some(target) = crew.destination {
+        crew.rejected
+            .push((target, claim.owner, claim.claimed_tick));
+    }
+    drop_target(crew, nav);
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn nav_find_colony_target_system(
-    mut nav_query: Query<(&NavPosition, &Fleet, &mut FleetColonyCrew)>,
+    mut nav_query: Query<(
+        Entity,
+        &NavPosition,
+        &Fleet,
+        &mut FleetColonyCrew,
+        &mut Navigator,
+    )>,
     system_query: Query<(&Star, &StarClaim)>,
     planet_query: Query<(&Planet, Entity, Option<&Colony>)>,
-    nav_masks: Query<&NavigationMask>,
+    empire_query: Query<(&NavigationMask, &ThreatMap)>,
+    reservations: Res<ColonyTargetReservations>,
     hypernet: Res<Hypernet>,
+    path_cache: Res<PathCache>,
 ) {
     nav_query
         .par_iter_mut()
         .batching_strategy(BatchingStrategy::fixed(32))
-        .for_each(|(nav_pos, fleet, mut colony_fleet)| {
-            if let Some(dest) = colony_fleet.destination {
-                // don't do validation calculations needlessly often
-                if fleet.time_since_last_jump % 40 == 0 {
-                    let empire = fleet.owner;
-                    let nav_mask = nav_masks
-                        .get(empire)
-                        .expect("Nav find colony target: Can't find empire nav mask");
-                    let nav_filter = nav_mask.to_filter(&hypernet);
-
-                    let (dest_planet, _entity, _colony) = planet_query.get(dest).unwrap();
-
-                    let path = nav_filter.find_path(nav_pos.root_system, dest_planet.star_id);
-
-                    if path.is_none() {
-                        colony_fleet.destination = None;
+        .for_each(
+            |(fleet_entity, nav_pos, fleet, mut colony_fleet, mut nav)| {
+                let empire = fleet.owner;
+                let (nav_mask, threat) = empire_query
+                    .get(empire)
+                    .expect("Nav find colony target: Can't find empire nav mask");
+                let nav_filter = nav_mask.to_filter(&hypernet);
+
+                if let Some(dest) = colony_fleet.destination {
+                    // don't do validation calculations needlessly often
+                    if fleet.time_since_last_jump % 40 != 0 {
+                        return;
+                    }
+                    let (dest_planet, _entity, colony) = planet_query.get(dest).unwrap();
+                    let (_, starclaim) = system_query
+                        .get(hypernet.star(dest_planet.star_id).entity)
+                        .unwrap();
+                    let taken = starclaim.owner.is_some_and(|x| x != empire)
+                        || colony.is_some_and(|x| x.owner != empire);
+                    let reachable = path_cache
+                        .find_path(&nav_filter, None, nav_pos.root_system, dest_planet.star_id)
+                        .is_some();
+                    if taken {
+                        abandon_target(&mut colony_fleet, &mut nav, starclaim);
+                    } else if !reachable {
+                        // the distance field keeps it out of the search until it's reachable again
+                        drop_target(&mut colony_fleet, &mut nav);
                     } else {
                         return;
                     }
-                } else {
-                    return;
-                }
-            };
-
-            let mut rng = rand::rng();
+                };
 
-            let mut best_option: Option<Entity> = None;
-            let mut best_dist = i32::MAX;
+                colony_fleet
+                    .rejected
+                    .retain(|(planet, owner, claimed_tick)| {
+                        planet_query
+                            .get(*planet)
+                            .ok()
+                            .and_then(|(planet, ..)| {
+                                system_query.get(hypernet.star(planet.star_id).entity).ok()
+                            })
+                            .is_some_and(|(_, claim)| {
+                                claim.owner == *owner && claim.claimed_tick == *claimed_tick
+                            })
+                    });
 
-            let empire = fleet.owner;
+                let mut best_option: Option<Entity> = None;
+                let mut best_score = 0;
 
-            let nav_mask = nav_masks
-                .get(empire)
-                .expect("Nav find colony target: Can't find empire nav mask");
+                // Idle colony fleets mostly start from the same few colonies, so the distance fields are shared through the cache
+                let dijkstra = path_cache.distances(&nav_filter, nav_pos.root_system);
 
-            let nav_filter = nav_mask.to_filter(&hypernet);
+                for (star_id, d_o) in dijkstra.iter().enumerate() {
+                    let Some(d) = d_o else {
+                        continue;
+                    };
 
-            let dijkstra = nav_filter.dijkstra(&vec![nav_pos.root_system]);
+                    let Ok((star, starclaim)) =
+                        system_query.get(hypernet.star(star_id as u32).entity)
+                    else {
+                        continue;
+                    };
 
-            for (star_id, d_o) in dijkstra.iter().enumerate() {
-                let Some(d) = d_o else {
-                    continue;
-                };
+                    if starclaim.owner.is_some() && starclaim.owner != Some(empire) {
+                        continue;
+                    }
+                    let security = threat.security(star_id as u32);
 
-                let Ok((star, starclaim)) = system_query.get(hypernet.star(star_id as u32).entity)
-                else {
-                    continue;
-                };
+                    for (planet, planet_entity, colony) in star
+                        .orbiters
+                        .iter()
+                        .filter_map(|planet_entity| planet_query.get(*planet_entity).ok())
+                    {
+                        if colony_fleet
+                            .rejected
+                            .iter()
+                            .any(|(x, ..)| *x == planet_entity)
+                        {
+                            continue;
+                        }
+                        let support = planet.get_population_support() as i64;
+                        let room = match colony {
+                            Some(colony) => support - colony.population.val(),
+                            None if reservations.is_reserved(
+                                empire,
+                                planet_entity,
+                                fleet_entity,
+                            ) =>
+                            {
+                                continue
+                            }
+                            None => support,
+                        };
 
-                if starclaim.owner.is_some() && starclaim.owner != Some(empire) {
-                    continue;
+                        let score = target_score(room, *d, security);
+                        if score > best_score {
+                            best_score = score;
+                            best_option = Some(planet_entity);
+                        }
+                    }
                 }
 
-                for (_planet, planet_entity, colony) in star
-                    .orbiters
-                    .iter()
-                    .filter_map(|planet_entity| planet_query.get(*planet_entity).ok())
-                {
-                    let weight = if let Some(_colony) = colony {
-                        10000000 + rng.random_range(0..1000000)
-                    } else {
-                        d + rng.random_range(0..d + 1)
-                    };
+                colony_fleet.destination = best_option;
+            },
+        );
+}
 
-                    if weight < best_dist {
-                        best_dist = weight;
-                        best_option = Some(planet_entity);
-                    }
-                }
-            }
+// Ships that picked the same planet on the same tick, or were sent to one another ship already had, are settled here
+// Ships on a mission keep their target first, then ones already colonising, then the oldest
+pub fn reserve_colony_targets_system(
+    mut fleet_query: Query<(
+        Entity,
+        &Fleet,
+        &mut FleetColonyCrew,
+        &mut Navigator,
+        Has<OnMission>,
+    )>,
+    planet_query: Query<(), (With<Planet>, Without<Colony>)>,
+    mut reservations: ResMut<ColonyTargetReservations>,
+) {
+    reservations.reserved.clear();
+
+    let mut claims = fleet_query
+        .iter()
+        .filter_map(|(entity, fleet, crew, nav, on_mission)| {
+            let planet = crew.destination.filter(|x| planet_query.contains(*x))?;
+            let colonising = matches!(nav.action, Action::Colonise(_));
+            Some(((fleet.owner, planet), entity, on_mission, colonising))
+        })
+        .collect::<Vec<_>>();
+    claims.sort_by_key(|(key, entity, on_mission, colonising)| {
+        (*key, Reverse(*on_mission), Reverse(*colonising), *entity)
+    });
 
-            if let Some(planet_entity) = best_option {
-                colony_fleet.destination = Some(planet_entity);
-            };
-        });
+    for (key, entity, ..) in claims {
+        match reservations.reserved.entry(key) {
+            Entry::Occupied(_) => {
+                let (_, _, mut crew, mut nav, _) = fleet_query.get_mut(entity).unwrap();
+                // not rejected for good, the planet may come free again
+                drop_target(&mut crew, &mut nav);
+            }
+            Entry::Vacant(x) => {
+                x.insert(entity);
+            }
+        }
+    }
 }
 
 #[derive(Event)]
@@ -142,10 +272,9 @@ pub fn process_colonise_events(
         if let Some(owner) = star_claim.owner {
             if owner != fleet.owner {
                 info!("colonisation failed: System is already claimed by someone else");
-                // reset the fleet
+                // look for somewhere else to go
                 nav.action = Action::Idle;
-                nav.plan_queue.clear();
-                colony_crew.destination = None;
+                abandon_target(&mut colony_crew, &mut nav, &star_claim);
                 continue;
             }
         } else {
@@ -173,3 +302,164 @@ pub fn process_colonise_events(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::galaxy::fleet::FleetBundle;
+    use rand::prelude::*;
+
+    fn spawn_colony_ship(world: &mut World, owner: Entity, destination: Option<Entity>) -> Entity {
+        world
+            .spawn((
+                FleetBundle::new(owner, Vec3::ZERO, 0),
+                FleetColonyCrew {
+                    colonists: 1000,
+                    destination,
+                    rejected: Vec::new(),
+                },
+            ))
+            .id()
+    }
+
+    fn planet(system: u32) -> Planet {
+        Planet::new(Vec3::ZERO, system, 1.0, 360, 0, 0.5, 1.0)
+    }
+
+    // Random ships of two empires heading for a handful of planets, some of them already colonised
+    #[test]
+    fn no_two_ships_of_an_empire_share_a_target() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..50 {
+            let mut world = World::new();
+            world.init_resource::<ColonyTargetReservations>();
+            let empires = [world.spawn_empty().id(), world.spawn_empty().id()];
+            let planets = (0..5)
+                .map(|_| world.spawn(planet(0)).id())
+                .collect::<Vec<_>>();
+            for planet in &planets[..2] {
+                world.entity_mut(*planet).insert(Colony {
+                    owner: empires[0],
+                    claimed_tick: 0,
+                    population: Population::new(1000),
+                    economy: Economy::new(),
+                });
+            }
+            for _ in 0..rng.random_range(1..20) {
+                let owner = *empires.choose(&mut rng).unwrap();
+                let destination = planets.choose(&mut rng).copied();
+                let ship = spawn_colony_ship(&mut world, owner, destination);
+                if rng.random_bool(0.2) {
+                    world.entity_mut(ship).insert(OnMission);
+                }
+                if rng.random_bool(0.2) {
+                    world.get_mut::<Navigator>(ship).unwrap().action =
+                        Action::Colonise((destination.unwrap_or(ship), 10));
+                }
+            }
+
+            let mut before =
+                world.query::<(Entity, &Fleet, &FleetColonyCrew, &Navigator, Has<OnMission>)>();
+            let before = before
+                .iter(&world)
+                .map(|(entity, fleet, crew, nav, on_mission)| {
+                    let colonising = matches!(nav.action, Action::Colonise(_));
+                    (
+                        entity,
+                        fleet.owner,
+                        crew.destination,
+                        on_mission,
+                        colonising,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let mut schedule = Schedule::default();
+            schedule.add_systems(reserve_colony_targets_system);
+            schedule.run(&mut world);
+
+            let reservations = world.resource::<ColonyTargetReservations>();
+            for (entity, owner, destination, on_mission, colonising) in &before {
+                let kept = world.get::<FleetColonyCrew>(*entity).unwrap().destination;
+                let Some(destination) = destination else {
+                    continue;
+                };
+                // Colonies take any number of settlers
+                if planets[..2].contains(destination) {
+                    assert_eq!(kept, Some(*destination));
+                    continue;
+                }
+                let rivals = before
+                    .iter()
+                    .filter(|x| x.0 != *entity && x.1 == *owner && x.2 == Some(*destination))
+                    .collect::<Vec<_>>();
+                match kept {
+                    Some(_) => {
+                        assert!(!reservations.is_reserved(*owner, *destination, *entity));
+                        // Ships on a mission come first, then ones already colonising
+                        assert!(rivals
+                            .iter()
+                            .all(|x| (x.3, x.4) <= (*on_mission, *colonising)));
+                    }
+                    None => assert!(reservations.is_reserved(*owner, *destination, *entity)),
+                }
+            }
+
+            let mut after = world.query::<(&Fleet, &FleetColonyCrew)>();
+            let mut targets = after
+                .iter(&world)
+                .filter_map(|(fleet, crew)| Some((fleet.owner, crew.destination?)))
+                .filter(|(_, planet)| !planets[..2].contains(planet))
+                .collect::<Vec<_>>();
+            let n = targets.len();
+            targets.sort();
+            targets.dedup();
+            assert_eq!(targets.len(), n);
+        }
+    }
+
+    // A target given up on because someone else claimed its system is picked again once they let it go
+    #[test]
+    fn forgets_rejections_once_the_claim_changes() {
+        let mut hypernet = Hypernet::from_lanes(&[(0.0, 0.0), (10.0, 0.0)], &[(0, 1)]);
+        let mut world = World::new();
+        let stars = hypernet.spawn_stars(&mut world);
+        let target = world.spawn(planet(1)).id();
+        world
+            .get_mut::<Star>(stars[1])
+            .unwrap()
+            .orbiters
+            .push(target);
+        let empire = world
+            .spawn((NavigationMask::new(&hypernet, true), ThreatMap::default()))
+            .id();
+        let rival = world.spawn_empty().id();
+        let mut claim = world.get_mut::<StarClaim>(stars[1]).unwrap();
+        claim.owner = Some(rival);
+        claim.claimed_tick = 5;
+        world.insert_resource(hypernet);
+        world.init_resource::<PathCache>();
+        world.init_resource::<ColonyTargetReservations>();
+        let ship = spawn_colony_ship(&mut world, empire, Some(target));
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(nav_find_colony_target_system);
+        schedule.run(&mut world);
+        let crew = world.get::<FleetColonyCrew>(ship).unwrap();
+        assert_eq!(crew.destination, None);
+        assert_eq!(crew.rejected, [(target, Some(rival), 5)]);
+
+        // Still theirs
+        schedule.run(&mut world);
+        assert_eq!(
+            world.get::<FleetColonyCrew>(ship).unwrap().rejected.len(),
+            1
+        );
+
+        world.get_mut::<StarClaim>(stars[1]).unwrap().owner = None;
+        schedule.run(&mut world);
+        let crew = world.get::<FleetColonyCrew>(ship).unwrap();
+        assert_eq!(crew.destination, Some(target));
+        assert!(crew.rejected.is_empty());
+    }
+}
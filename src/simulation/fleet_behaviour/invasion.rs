@@ -0,0 +1,382 @@
+use crate::galaxy::fleet::{FleetSupply, FleetTroops};
+use crate::prelude::*;
+use crate::simulation::mission::invade::InvadeMission;
+use crate::simulation::mission::{Mission, MissionState, OnMission};
+use crate::simulation::policy::EconomicPolicies;
+use crate::simulation::SimTime;
+use bevy::prelude::*;
+
+use super::navigation::{Action, Navigator, Plan};
+
+// Colonies need this many people before they will raise troops
+const INVASION_MIN_POPULATION: i64 = 1000000000; // 1e9
+
+// Number of people per defending soldier
+const GARRISON_RATIO: i64 = 200;
+// What the fighting costs the planet, win or lose
+const INVASION_POPULATION_LOSS: IPercent = IPercent::new(150);
+const INVASION_INFRASTRUCTURE_LOSS: IPercent = IPercent::new(250);
+
+#[derive(Event)]
+pub struct InvadePlanetEvent {
+    pub planet_entity: Entity,
+    pub troop_fleet: Entity,
+}
+
+// Sent when a colony changes hands
+#[derive(Event)]
+pub struct ColonyConqueredEvent {
+    pub planet_entity: Entity,
+    pub previous_owner: Entity,
+    pub new_owner: Entity,
+}
+
+// Once a month, each empire at war raises troops on its most populous colony, as many as its conscription policy allows
+// They're sent on the closest pending invasion mission, or failing that at the closest enemy colony on a mission of their own
+#[allow(clippy::too_many_arguments)]
+pub fn launch_invasions_system(
+    empire_query: Query<(
+        Entity,
+        &Diplomacy,
+        &NavigationMask,
+        &EmpireIndex,
+        &EconomicPolicies,
+    )>,
+    mut colony_query: Query<(&Planet, &mut Colony)>,
+    mut mission_query: Query<(Entity, &mut Mission), With<InvadeMission>>,
+    star_query: Query<&Star>,
+    sim_time: Res<SimTime>,
+    sim_settings: Res<SimulationSettings>,
+    hypernet: Res<Hypernet>,
+    path_cache: Res<PathCache>,
+    mut commands: Commands,
+) {
+    if !sim_time.is_new_month() {
+        return;
+    }
+
+    for (empire, diplomacy, mask, index, policies) in &empire_query {
+        if diplomacy.at_war.is_empty() {
+            continue;
+        }
+
+        let Some(origin) = index
+            .colonies
+            .iter()
+            .filter(|x| {
+                colony_query
+                    .get(**x)
+                    .is_ok_and(|(_, colony)| colony.population.val() >= INVASION_MIN_POPULATION)
+            })
+            .max_by_key(|x| colony_query.get(**x).unwrap().1.population.val())
+            .copied()
+        else {
+            continue;
+        };
+        let origin_system = colony_query.get(origin).unwrap().0.star_id;
+
+        let filter = mask
+            .to_filter(&hypernet)
+            .with_profile(CostProfile::Offensive);
+        let distances = path_cache.distances(&filter, origin_system);
+
+        let mut target = None;
+        let mut best_dist = i32::MAX;
+        for star in &star_query {
+            let Some(d) = distances[star.node_id as usize] else {
+                continue;
+            };
+            if d >= best_dist {
+                continue;
+            }
+            for planet_entity in &star.orbiters {
+                if let Ok((_, colony)) = colony_query.get(*planet_entity) {
+                    if diplomacy.at_war.contains(&colony.owner) {
+                        target = Some(*planet_entity);
+                        best_dist = d;
+                    }
+                }
+            }
+        }
+
+        let pending = mission_query
+            .iter()
+            .filter(|(_, mission)| {
+                mission.owner == empire && mission.state == MissionState::Pending
+            })
+            .filter_map(|(entity, mission)| {
+                let (planet, colony) = colony_query.get(mission.target).ok()?;
+                let d = distances[planet.star_id as usize]?;
+                diplomacy
+                    .at_war
+                    .contains(&colony.owner)
+                    .then_some((entity, mission.target, d))
+            })
+            .min_by_key(|(entity, _, d)| (*d, *entity));
+
+        let (mission_entity, target) = match pending {
+            Some((mission_entity, target, _)) => (Some(mission_entity), target),
+            None => {
+                let Some(target) = target else {
+                    continue;
+                };
+                (None, target)
+            }
+        };
+
+        let (planet, mut colony) = colony_query.get_mut(origin).unwrap();
+        let troops = colony.population.val() * policies.effects().troops;
+        colony.population.add(-troops);
+
+        let fleet = commands
+            .spawn((
+                crate::galaxy::fleet::FleetBundle::new(
+                    empire,
+                    planet.system_local_pos(),
+                    planet.star_id,
+                ),
+                FleetTroops {
+                    troops,
+                    destination: Some(target),
+                },
+                OnMission,
+            ))
+            .id();
+
+        if let Some(mission_entity) = mission_entity {
+            mission_query
+                .get_mut(mission_entity)
+                .unwrap()
+                .1
+                .assign(fleet);
+        } else {
+            let mut mission = Mission::new(empire, target, sim_settings.current_tick);
+            mission.assign(fleet);
+            commands.spawn((mission, InvadeMission));
+        }
+    }
+}
+
+pub fn nav_update_invasion_task_system(
+    mut nav_query: Query<(&mut Navigator, &FleetTroops)>,
+    planet_query: Query<&Planet>,
+) {
+    for (mut nav, troops) in nav_query.iter_mut() {
+        let Action::Idle = nav.action else {
+            continue;
+        };
+        if !nav.plan_queue.is_empty() {
+            continue;
+        }
+
+        if let Some(planet_id) = troops.destination {
+            let Ok(planet) = planet_query.get(planet_id) else {
+                continue;
+            };
+            nav.plan_queue.push(Plan::Invade(planet_id));
+            nav.plan_queue
+                .push(Plan::ReachSystem(planet.star_id, CostProfile::Offensive));
+        }
+    }
+}
+
+// Ground combat. Troops (weakened by poor supply) against a garrison raised from the population
+// A successful invasion hands the colony over, along with the system if the defender has no other colony there
+pub fn process_invasion_events(
+    mut planet_query: Query<(&ChildOf, &mut Colony)>,
+    fleet_query: Query<(&Fleet, &FleetTroops, &FleetSupply)>,
+    mut star_query: Query<(&Star, &mut StarClaim)>,
+    mut ev_invade: EventReader<InvadePlanetEvent>,
+    mut ev_conquered: EventWriter<ColonyConqueredEvent>,
+    sim_settings: Res<SimulationSettings>,
+    mut commands: Commands,
+) {
+    for ev in ev_invade.read() {
+        let Ok((fleet, troops, supply)) = fleet_query.get(ev.troop_fleet) else {
+            continue;
+        };
+        commands.entity(ev.troop_fleet).despawn();
+
+        let Ok((parent, mut colony)) = planet_query.get_mut(ev.planet_entity) else {
+            continue;
+        };
+        if colony.owner == fleet.owner {
+            continue;
+        }
+
+        let attack = troops.troops * supply.combat_effectiveness();
+        let garrison = colony.population.val() / GARRISON_RATIO;
+
+        let losses = colony.population.val() * INVASION_POPULATION_LOSS;
+        colony.population.add(-losses);
+        colony.economy.damage(INVASION_INFRASTRUCTURE_LOSS);
+
+        if attack <= garrison {
+            info!(
+                "Invasion by the {} repelled ({} against a garrison of {})",
+                fleet.name, attack, garrison
+            );
+            continue;
+        }
+
+        let previous_owner = colony.owner;
+        colony.owner = fleet.owner;
+        colony.claimed_tick = sim_settings.current_tick;
+        // the surviving troops settle
+        colony.population.add(attack - garrison);
+
+        ev_conquered.write(ColonyConqueredEvent {
+            planet_entity: ev.planet_entity,
+            previous_owner,
+            new_owner: fleet.owner,
+        });
+
+        let Ok((star, mut star_claim)) = star_query.get_mut(parent.parent()) else {
+            continue;
+        };
+        let defender_remains = star
+            .orbiters
+            .iter()
+            .filter(|x| **x != ev.planet_entity)
+            .filter_map(|x| planet_query.get(*x).ok())
+            .any(|(_, x)| x.owner == previous_owner);
+        if !defender_remains {
+            star_claim.owner = Some(fleet.owner);
+            star_claim.contested_by = None;
+            star_claim.claimed_tick = sim_settings.current_tick;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Battlefield {
+        world: World,
+        schedule: Schedule,
+        planets: [Entity; 2],
+        star: Entity,
+        defender: Entity,
+        attacker: Entity,
+    }
+
+    // A system with two colonies of the same empire
+    fn battlefield() -> Battlefield {
+        let mut hypernet = Hypernet::from_lanes(&[(0.0, 0.0)], &[]);
+        let mut world = World::new();
+        let star = hypernet.spawn_stars(&mut world)[0];
+        let (defender, attacker) = (world.spawn_empty().id(), world.spawn_empty().id());
+        world.get_mut::<StarClaim>(star).unwrap().owner = Some(defender);
+        let planets = [(); 2].map(|_| {
+            world
+                .spawn((
+                    ChildOf(star),
+                    Colony {
+                        owner: defender,
+                        claimed_tick: 0,
+                        population: Population::new(1_000_000_000),
+                        economy: Economy::new(),
+                    },
+                ))
+                .id()
+        });
+        world
+            .get_mut::<Star>(star)
+            .unwrap()
+            .orbiters
+            .extend(planets);
+        world.insert_resource(hypernet);
+        world.insert_resource(SimulationSettings::at_tick(3));
+        world.init_resource::<Events<InvadePlanetEvent>>();
+        world.init_resource::<Events<ColonyConqueredEvent>>();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(process_invasion_events);
+        Battlefield {
+            world,
+            schedule,
+            planets,
+            star,
+            defender,
+            attacker,
+        }
+    }
+
+    impl Battlefield {
+        fn invade(&mut self, planet: usize, troops: i64, readiness: i32) {
+            let fleet = self
+                .world
+                .spawn((
+                    Fleet {
+                        owner: self.attacker,
+                        name: String::new(),
+                        time_since_last_jump: 0,
+                    },
+                    FleetTroops {
+                        troops,
+                        destination: Some(self.planets[planet]),
+                    },
+                    FleetSupply { readiness },
+                ))
+                .id();
+            self.world.send_event(InvadePlanetEvent {
+                planet_entity: self.planets[planet],
+                troop_fleet: fleet,
+            });
+            self.schedule.run(&mut self.world);
+            assert!(self.world.get_entity(fleet).is_err());
+        }
+
+        fn colony(&self, planet: usize) -> &Colony {
+            self.world.get::<Colony>(self.planets[planet]).unwrap()
+        }
+
+        fn system_owner(&self) -> Option<Entity> {
+            self.world.get::<StarClaim>(self.star).unwrap().owner
+        }
+    }
+
+    #[test]
+    fn troops_need_to_outnumber_the_garrison() {
+        let mut battle = battlefield();
+        // A garrison of 5M, and the fighting costs 15% of the population either way
+        battle.invade(0, 4_000_000, FleetSupply::MAX_READINESS);
+        assert_eq!(battle.colony(0).owner, battle.defender);
+        assert_eq!(battle.colony(0).population.val(), 850_000_000);
+
+        // Poorly supplied troops only fight at a quarter of their strength
+        battle.invade(0, 10_000_000, 0);
+        assert_eq!(battle.colony(0).owner, battle.defender);
+
+        battle.invade(0, 10_000_000, FleetSupply::MAX_READINESS);
+        let colony = battle.colony(0);
+        assert_eq!(colony.owner, battle.attacker);
+        assert_eq!(colony.claimed_tick, 3);
+        // The survivors settle
+        assert_eq!(
+            colony.population.val(),
+            614_125_000 + 10_000_000 - 722_500_000 / 200
+        );
+        assert_eq!(
+            battle
+                .world
+                .resource::<Events<ColonyConqueredEvent>>()
+                .len(),
+            1
+        );
+    }
+
+    // The system only changes hands with the defender's last colony there
+    #[test]
+    fn takes_the_system_with_the_last_colony() {
+        let mut battle = battlefield();
+        battle.invade(0, 10_000_000, FleetSupply::MAX_READINESS);
+        assert_eq!(battle.colony(0).owner, battle.attacker);
+        assert_eq!(battle.system_owner(), Some(battle.defender));
+
+        battle.invade(1, 10_000_000, FleetSupply::MAX_READINESS);
+        assert_eq!(battle.colony(1).owner, battle.attacker);
+        assert_eq!(battle.system_owner(), Some(battle.attacker));
+    }
+}
@@ -1,2 +1,4 @@
 pub mod colonisation;
+pub mod invasion;
 pub mod navigation;
+pub mod station;
@@ -1,8 +1,12 @@
+use crate::galaxy::hypernet_hierarchy::HypernetClusters;
 use crate::galaxy::Hypernet;
+use crate::galaxy::Path;
 use crate::prelude::*;
 use bevy::prelude::*;
 
 use super::colonisation::ColonisePlanetEvent;
+use super::invasion::InvadePlanetEvent;
+use crate::galaxy::fleet::{FleetColonyCrew, FleetSupply};
 
 #[derive(Component)]
 pub struct NavPosition {
@@ -16,6 +20,7 @@ pub struct HyperlaneLocalPos {
     star_b: u32,
     progress: i32,
     distance: i32,
+    lane: u32, // edge id in the hypernet
 }
 
 #[derive(Clone)]
@@ -38,6 +43,7 @@ impl NavPosition {
                 star_b,
                 progress,
                 distance,
+                ..
             }) => {
                 let star_b_pos = hypernet.graph.node_weight(star_b.into()).unwrap().pos;
                 let dir = (star_b_pos.as_dvec3() - star_pos.as_dvec3()).normalize();
@@ -68,6 +74,7 @@ impl NavPosition {
                 star_b,
                 progress,
                 distance,
+                ..
             }) => {
                 let star_b_pos = hypernet.graph.node_weight(star_b.into()).unwrap().pos;
                 let dir = (star_b_pos.as_dvec3() - star_pos.as_dvec3()).normalize();
@@ -87,6 +94,7 @@ impl NavPosition {
 pub struct Navigator {
     pub action: Action,
     pub plan_queue: Vec<Plan>,
+    pub route: Option<Route>, // Path being followed by the current ReachSystem plan
     pub stranded_go_home: bool,
     pub speed: f32,
     pub hyperspeed: i32,
@@ -96,17 +104,64 @@ pub enum Action {
     Move(Vec3),
     Jumping,
     Colonise((Entity, i32)), // Duration
+    Invade((Entity, i32)),   // Duration
     BeingDestroyed,
     Idle,
 }
 
+// A solved path, stored so that a ReachSystem plan isn't solved again at every hop
+pub struct Route {
+    destination: u32,
+    profile: CostProfile,
+    mask_version: u32,
+    systems: Vec<u32>, // Remaining systems, in reverse order so the next hop is at the back
+}
+
+impl Route {
+    fn new(path: &Path, profile: CostProfile, mask_version: u32) -> Self {
+        Self {
+            destination: *path.nodes.last().unwrap(),
+            profile,
+            mask_version,
+            systems: path.nodes.iter().skip(1).rev().copied().collect(),
+        }
+    }
+
+    // The next system to jump to from "current", if this route still leads to "destination"
+    // Routes solved against an older navigation mask may cross systems that are now closed, so they're discarded
+    fn next_hop(
+        &mut self,
+        current: u32,
+        destination: u32,
+        profile: CostProfile,
+        mask_version: u32,
+        hypernet: &Hypernet,
+    ) -> Option<u32> {
+        if self.destination != destination
+            || self.profile != profile
+            || self.mask_version != mask_version
+        {
+            return None;
+        }
+        if self.systems.last() == Some(&current) {
+            self.systems.pop();
+        }
+        let next = *self.systems.last()?;
+        hypernet
+            .graph
+            .find_edge(current.into(), next.into())
+            .map(|_| next)
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum Plan {
-    ReachSystem(u32),
+    ReachSystem(u32, CostProfile),
     ReachHomeEmpire,
     ReachPoint(Vec3),
     Jump(u32),
     Colonise(Entity), // Planet
+    Invade(Entity),   // Planet
 }
 
 // Finds the entry/exit point in the system of "star" for the hyperlane connecting to "other"
@@ -118,13 +173,23 @@ fn hyperlane_transit_point(star: &Star, other: Vec3) -> Vec3 {
     (dir * star.system_radius_actual() as f64).as_vec3()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn navigation_update_nav_system(
-    mut nav_query: Query<(&mut NavPosition, &mut Navigator, &mut Fleet, Entity)>,
+    mut nav_query: Query<(
+        &mut NavPosition,
+        &mut Navigator,
+        &mut Fleet,
+        &FleetSupply,
+        Entity,
+    )>,
     system_query: Query<(&Star, &StarClaim)>,
     planet_query: Query<&Planet>,
     empire_query: Query<&NavigationMask, With<Empire>>,
     hypernet: Res<Hypernet>,
+    path_cache: Res<PathCache>,
+    clusters: Option<Res<HypernetClusters>>,
     mut ev_colonise: EventWriter<ColonisePlanetEvent>,
+    mut ev_invade: EventWriter<InvadePlanetEvent>,
 ) {
     // STEP 1 - RESOLVE MOVEMENT
     //    If we are in a hyperlane, update travel progress
@@ -143,12 +208,11 @@ pub fn navigation_update_nav_system(
     let blank_mask = NavigationMask::new(&hypernet, true);
     let blank_filter = blank_mask.to_filter(&hypernet);
 
-    for (mut nav_pos, mut nav, mut fleet, fleet_entity) in nav_query.iter_mut() {
+    for (mut nav_pos, mut nav, mut fleet, supply, fleet_entity) in nav_query.iter_mut() {
         fleet.time_since_last_jump += 1;
         let Ok(mask) = empire_query.get(fleet.owner) else {
             continue;
         };
-        let nav_filter = mask.to_filter(&hypernet);
 
         // 1. If we are in a hyperlane, update travel progress
         // -- If we finished travelling, set location to the new system and mark that we're idle
@@ -157,6 +221,7 @@ pub fn navigation_update_nav_system(
             star_b,
             progress,
             distance,
+            lane,
         }) = nav_pos.offset
         {
             let star_a_node = hypernet.star(nav_pos.root_system);
@@ -164,7 +229,14 @@ pub fn navigation_update_nav_system(
             let star_b_node = hypernet.star(star_b);
             let (star_b_ref, _) = system_query.get(star_b_node.entity).unwrap();
 
-            let progress = progress + nav.hyperspeed;
+            // Lane type modifies travel speed (eg. slowed by hazards, sped up by wormholes or the empire's own gateways)
+            let lane_speed = hypernet
+                .graph
+                .edge_weight(lane.into())
+                .map(|x| x.speed_percent(Some(fleet.owner)))
+                .unwrap_or(100);
+            let progress =
+                progress + nav.hyperspeed * lane_speed / 100 * supply.speed_percent() / 100;
 
             *nav_pos = if progress >= distance {
                 // Finished Jumping
@@ -182,6 +254,7 @@ pub fn navigation_update_nav_system(
                         star_b,
                         progress,
                         distance,
+                        lane,
                     }),
                 }
             };
@@ -190,24 +263,22 @@ pub fn navigation_update_nav_system(
         // 1.5 CHECK STRANDED
 
         if let NavOffset::Star(_) = nav_pos.offset {
-            let root_star_node = hypernet.star(nav_pos.root_system);
-            let (_, star_claim) = system_query.get(root_star_node.entity).unwrap();
-
-            if let Some(star_owner) = star_claim.owner {
-                if star_owner != fleet.owner {
-                    if !nav.stranded_go_home {
-                        nav.stranded_go_home = true;
-                        nav.action = Action::Idle;
-                        nav.plan_queue.clear();
-                        nav.plan_queue.push(Plan::ReachHomeEmpire);
-                    }
-                } else {
+            match mask.access(nav_pos.root_system) {
+                SystemAccess::Owned => {
                     if nav.stranded_go_home {
                         nav.action = Action::Idle;
                         nav.plan_queue.clear();
                     }
                     nav.stranded_go_home = false;
                 }
+                // Hostile territory isn't a reason to turn back, that's where troop ships are headed
+                SystemAccess::ClosedBorders if !nav.stranded_go_home => {
+                    nav.stranded_go_home = true;
+                    nav.action = Action::Idle;
+                    nav.plan_queue.clear();
+                    nav.plan_queue.push(Plan::ReachHomeEmpire);
+                }
+                _ => {}
             }
         }
 
@@ -231,7 +302,7 @@ pub fn navigation_update_nav_system(
                         let dest = planet.system_local_pos();
                         let dir = (dest - *offset).normalize_or_zero();
                         let dist = dest.distance(*offset);
-                        let speed = nav.speed.min(dist);
+                        let speed = (nav.speed * supply.speed_percent() as f32 / 100.0).min(dist);
                         *offset += dir * speed;
 
                         // I Guess the action could fail here if the ship can't keep up with the planet?
@@ -258,11 +329,41 @@ pub fn navigation_update_nav_system(
                     Action::Idle
                 }
             }
+            Action::Invade((planet_entity, duration)) => {
+                if let NavOffset::Star(offset) = &mut nav_pos.offset {
+                    if let Ok(planet) = planet_query.get(planet_entity) {
+                        // hold position over the target planet while the troops land
+                        let dest = planet.system_local_pos();
+                        let dir = (dest - *offset).normalize_or_zero();
+                        let dist = dest.distance(*offset);
+                        let speed = (nav.speed * supply.speed_percent() as f32 / 100.0).min(dist);
+                        *offset += dir * speed;
+
+                        let mut dur = duration;
+                        if dist <= speed {
+                            dur = duration - 1;
+                        }
+                        if dur <= 0 {
+                            ev_invade.write(InvadePlanetEvent {
+                                planet_entity,
+                                troop_fleet: fleet_entity,
+                            });
+                            Action::BeingDestroyed
+                        } else {
+                            Action::Invade((planet_entity, dur))
+                        }
+                    } else {
+                        Action::Idle
+                    }
+                } else {
+                    Action::Idle
+                }
+            }
             Action::Move(dest) => {
                 if let NavOffset::Star(offset) = &mut nav_pos.offset {
                     let dir = (dest - *offset).normalize_or_zero();
                     let dist = dest.distance(*offset);
-                    let speed = nav.speed.min(dist);
+                    let speed = (nav.speed * supply.speed_percent() as f32 / 100.0).min(dist);
                     *offset += dir * speed;
 
                     if dist <= speed {
@@ -299,9 +400,10 @@ pub fn navigation_update_nav_system(
                 let modestring = match top {
                     Plan::Jump(_) => "Jump",
                     Plan::Colonise(_) => "Colonise",
+                    Plan::Invade(_) => "Invade",
                     Plan::ReachHomeEmpire => "ReachHomEmpire",
                     Plan::ReachPoint(_) => "ReachPoint",
-                    Plan::ReachSystem(_) => "ReachSystem",
+                    Plan::ReachSystem(..) => "ReachSystem",
                 };
                 info!("trapped in loop, iter {}, mode: {}", iter, modestring);
             }
@@ -332,24 +434,18 @@ pub fn navigation_update_nav_system(
                             nav.plan_queue.pop();
                             nav.action = Action::Jumping;
 
-                            let edge = hypernet
+                            let lane = hypernet
                                 .graph
-                                .edge_weight(
-                                    hypernet
-                                        .graph
-                                        .find_edge(
-                                            nav_pos.root_system.into(),
-                                            dest_system_id.into(),
-                                        )
-                                        .unwrap(),
-                                )
+                                .find_edge(nav_pos.root_system.into(), dest_system_id.into())
                                 .unwrap();
+                            let edge = hypernet.graph.edge_weight(lane).unwrap();
                             *nav_pos = NavPosition {
                                 root_system: nav_pos.root_system,
                                 offset: NavOffset::Hyperlane(HyperlaneLocalPos {
                                     star_b: dest_system_id,
                                     progress: 0,
                                     distance: edge.length,
+                                    lane: lane.index() as u32,
                                 }),
                             }
                         } else {
@@ -368,19 +464,24 @@ pub fn navigation_update_nav_system(
                     nav.plan_queue.pop();
                     nav.action = Action::Colonise((planet_entity, 60));
                 }
+                Plan::Invade(planet_entity) => {
+                    nav.plan_queue.pop();
+                    nav.action = Action::Invade((planet_entity, 30));
+                }
                 Plan::ReachPoint(dest_point) => {
                     nav.plan_queue.pop();
                     nav.action = Action::Move(dest_point);
                 }
                 Plan::ReachHomeEmpire => {
                     info!("Stranded, trying to get home!");
-                    assert!(
-                        !mask.owned_systems.is_empty(),
-                        "fleet's owner owned systems mask cannot be empty"
-                    );
-                    if let Some(mut path) = blank_filter
-                        .find_path_multi_source(&mask.owned_systems, nav_pos.root_system)
-                    {
+                    // The owner may have lost all of its territory, or be cut off from it
+                    let path_home = if mask.owned_systems.is_empty() {
+                        None
+                    } else {
+                        blank_filter
+                            .find_path_multi_source(&mask.owned_systems, nav_pos.root_system)
+                    };
+                    if let Some(mut path) = path_home {
                         path.reverse();
 
                         if path.nodes.len() > 1 {
@@ -408,23 +509,54 @@ pub fn navigation_update_nav_system(
                             nav.plan_queue.pop();
                         }
                     } else {
-                        // THIS is an error state...
-                        panic!("ReachHomeEmpire ... can't find path home. (THIS SHOULD NOT BE POSSIBLE...)");
+                        // Nowhere to go, so hold position. stranded_go_home stays set, so this isn't retried every tick
+                        info!("Stranded with no way home, holding position");
+                        nav.plan_queue.clear();
                     }
                 }
-                Plan::ReachSystem(dest_system_id) => {
+                Plan::ReachSystem(dest_system_id, profile) => {
+                    let root_system = nav_pos.root_system;
+                    let next_hop = nav.route.as_mut().and_then(|route| {
+                        route.next_hop(
+                            root_system,
+                            dest_system_id,
+                            profile,
+                            mask.version(),
+                            &hypernet,
+                        )
+                    });
+
                     if dest_system_id == nav_pos.root_system {
                         // We're already there, so we can consider this plan finished...
                         nav.plan_queue.pop();
-                    } else if let Some(path) =
-                        nav_filter.find_path(nav_pos.root_system, dest_system_id)
-                    {
+                        nav.route = None;
+                    } else if let Some(next_system) = next_hop {
+                        let next_system_node = hypernet.star(next_system);
+                        let root_system_node = hypernet.star(nav_pos.root_system);
+                        let (root_system_star, _) =
+                            system_query.get(root_system_node.entity).unwrap();
+                        let (next_system_star, _) =
+                            system_query.get(next_system_node.entity).unwrap();
+
+                        nav.plan_queue.push(Plan::Jump(next_system));
+                        nav.plan_queue
+                            .push(Plan::ReachPoint(hyperlane_transit_point(
+                                root_system_star,
+                                next_system_star.pos,
+                            )));
+                    } else if let Some(path) = path_cache.find_path(
+                        &mask.to_filter(&hypernet).with_profile(profile),
+                        clusters.as_deref(),
+                        nav_pos.root_system,
+                        dest_system_id,
+                    ) {
                         assert!(
                             nav_pos.root_system == path.nodes[0],
                             "path[0] doesn't match path origin!"
                         );
 
                         if path.nodes.len() > 1 {
+                            nav.route = Some(Route::new(&path, profile, mask.version()));
                             let next_system = path.nodes[1];
                             let next_system_node = hypernet.star(next_system);
                             let root_system_node = hypernet.star(nav_pos.root_system);
@@ -451,9 +583,89 @@ pub fn navigation_update_nav_system(
                         // .. or not. Kinda debatable situation
                         info!("Navigation: ReachSystem plan couldn't find a path. Dropping navigation queue.");
                         nav.plan_queue.clear();
+                        nav.route = None;
                     }
                 }
             }
         }
     }
 }
+
+// Fleets crossing hazardous lanes lose part of their crew, once per crossing
+// Runs right after navigation, so it catches each fleet on the tick it enters the lane, the only one its progress is still 0
+pub fn hazardous_lane_attrition_system(
+    mut query: Query<(&NavPosition, &mut FleetColonyCrew)>,
+    hypernet: Res<Hypernet>,
+) {
+    for (nav_pos, mut crew) in query.iter_mut() {
+        let NavOffset::Hyperlane(HyperlaneLocalPos {
+            lane, progress: 0, ..
+        }) = nav_pos.offset
+        else {
+            continue;
+        };
+        let Some(edge) = hypernet.graph.edge_weight(lane.into()) else {
+            continue;
+        };
+        let losses = crew.colonists * edge.attrition_rate();
+        crew.colonists -= losses;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    // A colony ship crossing a long hazardous lane, moved along it the way navigation_update_nav_system moves it
+    #[test]
+    fn hazardous_lanes_take_their_toll_once_per_crossing() {
+        let mut hypernet = Hypernet::from_lanes(&[(0.0, 0.0), (300.0, 0.0)], &[(0, 1)]);
+        hypernet.set_lane_type(0, LaneType::Hazardous);
+        let lane = hypernet.graph.edge_weight(0.into()).unwrap().clone();
+
+        let mut world = World::new();
+        world.insert_resource(hypernet);
+        let fleet = world
+            .spawn((
+                NavPosition {
+                    root_system: 0,
+                    offset: NavOffset::Hyperlane(HyperlaneLocalPos {
+                        star_b: 1,
+                        progress: 0,
+                        distance: lane.length,
+                        lane: 0,
+                    }),
+                },
+                FleetColonyCrew {
+                    colonists: 10000,
+                    destination: None,
+                    rejected: Vec::new(),
+                },
+            ))
+            .id();
+
+        // Default hyperspeed, through a hazardous lane at half readiness
+        let step = 10000 * lane.speed_percent(None) / 100 * 75 / 100;
+        let mut ticks = 0;
+        loop {
+            world
+                .run_system_once(hazardous_lane_attrition_system)
+                .unwrap();
+            let mut nav_pos = world.get_mut::<NavPosition>(fleet).unwrap();
+            let NavOffset::Hyperlane(position) = &mut nav_pos.offset else {
+                unreachable!();
+            };
+            position.progress += step;
+            ticks += 1;
+            if position.progress >= position.distance {
+                break;
+            }
+        }
+
+        let colonists = world.get::<FleetColonyCrew>(fleet).unwrap().colonists;
+        assert!(ticks > 100);
+        assert!(colonists >= 9800, "{} colonists left", colonists);
+        assert!(colonists < 10000);
+    }
+}
@@ -0,0 +1,27 @@
+use crate::galaxy::fleet::{FleetStation, StationDuty};
+use crate::prelude::*;
+use bevy::prelude::*;
+
+use super::navigation::{Action, NavPosition, Navigator, Plan};
+
+// Stationed fleets head for their system whenever they're idle anywhere else
+// Scouts keep to safe lanes, patrols take the shortest way to the system they guard
+pub fn nav_update_station_task_system(
+    mut nav_query: Query<(&mut Navigator, &NavPosition, &FleetStation)>,
+) {
+    for (mut nav, nav_pos, station) in nav_query.iter_mut() {
+        let Action::Idle = nav.action else {
+            continue;
+        };
+        if !nav.plan_queue.is_empty() || nav_pos.root_system == station.system {
+            continue;
+        }
+
+        let profile = match station.duty {
+            StationDuty::Survey => CostProfile::Safe,
+            StationDuty::Patrol => CostProfile::Shortest,
+        };
+        nav.plan_queue
+            .push(Plan::ReachSystem(station.system, profile));
+    }
+}
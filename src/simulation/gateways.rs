@@ -0,0 +1,44 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+use super::SimTime;
+
+// Systems need to be this developed before an empire will link them with a gateway
+const GATEWAY_MIN_POPULATION: i64 = 1000000000; // 1e9
+
+// Once a year, each empire upgrades one lane between two of its developed systems into a gateway
+pub fn construct_gateways_system(
+    empire_query: Query<(Entity, &NavigationMask), With<Empire>>,
+    system_query: Query<&SystemIndex>,
+    sim_time: Res<SimTime>,
+    mut hypernet: ResMut<Hypernet>,
+) {
+    if !sim_time.is_new_year() {
+        return;
+    }
+
+    for (empire, mask) in &empire_query {
+        let developed = mask
+            .owned_systems
+            .iter()
+            .filter(|x| {
+                system_query
+                    .get(hypernet.star(**x).entity)
+                    .is_ok_and(|index| index.population >= GATEWAY_MIN_POPULATION)
+            })
+            .copied()
+            .collect::<Vec<_>>();
+
+        let candidate = hypernet.graph.edge_indices().find(|lane| {
+            let (a, b) = hypernet.graph.edge_endpoints(*lane).unwrap();
+            hypernet.graph.edge_weight(*lane).unwrap().lane_type == LaneType::Standard
+                && developed.contains(&(a.index() as u32))
+                && developed.contains(&(b.index() as u32))
+        });
+
+        if let Some(lane) = candidate {
+            let (a, b) = hypernet.graph.edge_endpoints(lane).unwrap();
+            hypernet.build_gateway(a.index() as u32, b.index() as u32, empire);
+        }
+    }
+}
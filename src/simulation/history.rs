@@ -0,0 +1,307 @@
+use crate::galaxy::Goods;
+use crate::prelude::*;
+use bevy::prelude::*;
+
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::ops::AddAssign;
+
+use super::stats_export::StatsExportConfig;
+use super::SimTime;
+
+// HISTORY
+// Once a month, every colony, empire and the galaxy as a whole record a sample of their economy and demography
+// Empires and the galaxy are the sums of their colonies. Buffers are bounded, the oldest samples get dropped
+// Only empires and the galaxy keep the long series, colonies just the last few years
+
+const HISTORY_LENGTH: usize = 1200; // 100 years of months
+const COLONY_HISTORY_LENGTH: usize = 60; // As much as the chart shows
+const EXPORT_DIR: &str = "history";
+
+#[derive(Clone, Copy, Default)]
+pub struct HistorySample {
+    pub month: u32, // Months since the start
+    pub population: i64,
+    pub births: i64, // Per decade, at the month's rates
+    pub deaths: i64,
+    pub output: Goods,
+    pub infrastructure: Goods,
+    pub colonies: i64,
+    pub systems: i64,
+}
+
+impl AddAssign for HistorySample {
+    fn add_assign(&mut self, rhs: Self) {
+        self.population += rhs.population;
+        self.births += rhs.births;
+        self.deaths += rhs.deaths;
+        self.output += rhs.output;
+        self.infrastructure += rhs.infrastructure;
+        self.colonies += rhs.colonies;
+        self.systems += rhs.systems;
+    }
+}
+
+// On colonies (added on their first sample) and empires
+#[derive(Component)]
+pub struct History {
+    samples: VecDeque<HistorySample>,
+    length: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(HISTORY_LENGTH)
+    }
+}
+
+impl History {
+    pub fn new(length: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(length),
+            length,
+        }
+    }
+
+    pub fn push(&mut self, sample: HistorySample) {
+        if self.samples.len() >= self.length {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    // Oldest first
+    pub fn samples(&self) -> &VecDeque<HistorySample> {
+        &self.samples
+    }
+
+    pub fn latest(&self) -> Option<&HistorySample> {
+        self.samples.back()
+    }
+
+    pub fn write_csv(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "month,population,births,deaths,light_output,heavy_output,advanced_output,light_infra,heavy_infra,advanced_infra,colonies,systems"
+        )?;
+        for x in &self.samples {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{}",
+                x.month,
+                x.population,
+                x.births,
+                x.deaths,
+                x.output.light,
+                x.output.heavy,
+                x.output.advanced,
+                x.infrastructure.light,
+                x.infrastructure.heavy,
+                x.infrastructure.advanced,
+                x.colonies,
+                x.systems
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct GalaxyHistory(pub History);
+
+// Writes the galaxy's and every empire's history to EXPORT_DIR, one CSV file each, named after the run
+#[derive(Event)]
+pub struct ExportHistoryEvent;
+
+pub fn record_history_system(
+    mut colony_query: Query<(Entity, &Colony, Option<&mut History>), Without<Empire>>,
+    mut empire_query: Query<(&EmpireIndex, &mut History), With<Empire>>,
+    mut galaxy_history: ResMut<GalaxyHistory>,
+    sim_time: Res<SimTime>,
+    mut commands: Commands,
+) {
+    if !sim_time.is_new_month() {
+        return;
+    }
+    let (_, month, year) = sim_time.to_daymonthyear();
+    let month = year * 12 + month - 1;
+
+    let mut colony_samples = HashMap::new();
+    for (entity, colony, history) in colony_query.iter_mut() {
+        let sample = HistorySample {
+            month,
+            population: colony.population.val(),
+            births: colony.population.births(),
+            deaths: colony.population.deaths(),
+            output: colony.economy.supply(),
+            infrastructure: colony.economy.infrastructure(),
+            colonies: 1,
+            systems: 0,
+        };
+        match history {
+            Some(mut history) => history.push(sample),
+            None => {
+                let mut history = History::new(COLONY_HISTORY_LENGTH);
+                history.push(sample);
+                commands.entity(entity).insert(history);
+            }
+        }
+        colony_samples.insert(entity, sample);
+    }
+
+    let mut galaxy_sample = HistorySample { month, ..default() };
+    for (index, mut history) in empire_query.iter_mut() {
+        let mut sample = HistorySample {
+            month,
+            systems: index.systems.len() as i64,
+            ..default()
+        };
+        for x in index.colonies.iter().filter_map(|x| colony_samples.get(x)) {
+            sample += *x;
+        }
+        history.push(sample);
+        galaxy_sample += sample;
+    }
+    galaxy_history.0.push(galaxy_sample);
+}
+
+pub fn export_history_system(
+    mut ev_export: EventReader<ExportHistoryEvent>,
+    empire_query: Query<(&Empire, &History)>,
+    galaxy_history: Res<GalaxyHistory>,
+    config: Res<StatsExportConfig>,
+) {
+    if ev_export.read().count() == 0 {
+        return;
+    }
+
+    let export = || -> std::io::Result<()> {
+        std::fs::create_dir_all(EXPORT_DIR)?;
+        let run = &config.run;
+        let mut file = std::fs::File::create(format!("{}/{}-galaxy.csv", EXPORT_DIR, run))?;
+        galaxy_history.0.write_csv(&mut file)?;
+        for (empire, history) in &empire_query {
+            let name = empire
+                .name
+                .chars()
+                .filter(|x| x.is_alphanumeric())
+                .collect::<String>();
+            let mut file = std::fs::File::create(format!("{}/{}-{}.csv", EXPORT_DIR, run, name))?;
+            history.write_csv(&mut file)?;
+        }
+        Ok(())
+    };
+    match export() {
+        Ok(()) => info!("History exported to {}/{}-*", EXPORT_DIR, config.run),
+        Err(e) => warn!("History export failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::time::tick_date_system;
+
+    fn sample(month: u32) -> HistorySample {
+        HistorySample { month, ..default() }
+    }
+
+    #[test]
+    fn drops_the_oldest_samples() {
+        let mut history = History::new(3);
+        assert!(history.latest().is_none());
+        for month in 0..5 {
+            history.push(sample(month));
+        }
+        let months = history
+            .samples()
+            .iter()
+            .map(|x| x.month)
+            .collect::<Vec<_>>();
+        assert_eq!(months, [2, 3, 4]);
+        assert_eq!(history.latest().unwrap().month, 4);
+    }
+
+    #[test]
+    fn writes_one_csv_row_per_sample() {
+        let mut history = History::default();
+        history.push(HistorySample {
+            population: 1000,
+            colonies: 2,
+            ..sample(7)
+        });
+        let mut csv = Vec::new();
+        history.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("month,population,"));
+        assert_eq!(lines[1], "7,1000,0,0,0,0,0,0,0,0,2,0");
+    }
+
+    fn colony(world: &mut World, owner: Entity, population: i64) -> Entity {
+        world
+            .spawn(Colony {
+                owner,
+                claimed_tick: 0,
+                population: Population::new(population),
+                economy: Economy::new(),
+            })
+            .id()
+    }
+
+    // Colonies only keep the last few years, empires and the galaxy add them up over the whole series
+    #[test]
+    fn records_colonies_empires_and_the_galaxy() {
+        let mut world = World::new();
+        world.insert_resource(SimTime::new());
+        world.init_resource::<GalaxyHistory>();
+        let empires = [world.spawn_empty().id(), world.spawn_empty().id()];
+        let colonies = [
+            colony(&mut world, empires[0], 1000),
+            colony(&mut world, empires[0], 2000),
+            colony(&mut world, empires[1], 4000),
+        ];
+        let system = world.spawn_empty().id();
+        world.entity_mut(empires[0]).insert((
+            Empire::named("Zero"),
+            History::default(),
+            EmpireIndex {
+                colonies: colonies[..2].to_vec(),
+                systems: vec![system],
+                population: 0,
+            },
+        ));
+        world.entity_mut(empires[1]).insert((
+            Empire::named("One"),
+            History::default(),
+            EmpireIndex {
+                colonies: colonies[2..].to_vec(),
+                ..default()
+            },
+        ));
+
+        let months = COLONY_HISTORY_LENGTH as u32 + 10;
+        let mut schedule = Schedule::default();
+        schedule.add_systems((record_history_system, tick_date_system).chain());
+        for _ in 0..months * 30 {
+            schedule.run(&mut world);
+        }
+
+        let history = world.get::<History>(colonies[0]).unwrap();
+        assert_eq!(history.samples().len(), COLONY_HISTORY_LENGTH);
+        assert_eq!(history.latest().unwrap().month, months - 1);
+
+        let history = world.get::<History>(empires[0]).unwrap();
+        assert_eq!(history.samples().len(), months as usize);
+        let latest = history.latest().unwrap();
+        assert_eq!(latest.population, 3000);
+        assert_eq!((latest.colonies, latest.systems), (2, 1));
+        assert_eq!(history.samples()[0].colonies, 2);
+
+        let history = &world.resource::<GalaxyHistory>().0;
+        assert_eq!(history.samples().len(), months as usize);
+        assert_eq!(history.latest().unwrap().population, 7000);
+        assert_eq!(history.latest().unwrap().colonies, 3);
+    }
+}
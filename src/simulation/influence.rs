@@ -0,0 +1,246 @@
+use crate::prelude::*;
+use crate::simulation::supply::SupplyDepot;
+use bevy::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+use super::SimTime;
+
+// Influence a station projects into its own system
+const STATION_INFLUENCE: i32 = 300;
+// Influence halves with every jump, and stops after this many
+const INFLUENCE_JUMPS: u32 = 3;
+// Minimum influence to start claiming an unclaimed system
+const CLAIM_THRESHOLD: i32 = 100;
+// A challenger needs this much more influence than the owner (in percent) to contest a system
+const CONTEST_MARGIN_PERCENT: i32 = 150;
+// Claim progress gained per month by the leading empire. A claim completes at 1000
+const CLAIM_PROGRESS_PER_MONTH: i32 = 250;
+
+// Per star. Influence of every empire projecting into the system, strongest first
+#[derive(Component, Default)]
+pub struct SystemInfluence {
+    pub strengths: Vec<(Entity, i32)>,
+    claimant: Option<Entity>,
+    progress: i32,
+}
+
+impl SystemInfluence {
+    pub fn strength(&self, empire: Entity) -> i32 {
+        self.strengths
+            .iter()
+            .find(|(e, _)| *e == empire)
+            .map(|(_, s)| *s)
+            .unwrap_or(0)
+    }
+
+    // Moves the claim towards "claimant", returning true once it completes
+    fn advance_claim(&mut self, claimant: Entity) -> bool {
+        if self.claimant != Some(claimant) {
+            self.claimant = Some(claimant);
+            self.progress = 0;
+        }
+        self.progress += CLAIM_PROGRESS_PER_MONTH;
+        if self.progress >= 1000 {
+            self.reset_claim();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn reset_claim(&mut self) {
+        self.claimant = None;
+        self.progress = 0;
+    }
+}
+
+// Colonies (scaled by population) and stations project influence through nearby lanes
+// Once a month, systems follow the balance of influence:
+//  - Unclaimed systems are claimed by an empire that dominates them for long enough
+//  - Claimed systems without any of the owner's colonies can be taken over the same way, or released once the owner's influence is gone
+//  - Colonised systems can only be contested, it takes an invasion to actually take them
+pub fn update_influence_system(
+    mut star_query: Query<(&Star, &mut StarClaim, &mut SystemInfluence)>,
+    colony_query: Query<&Colony>,
+    depot_query: Query<(&Star, &SupplyDepot)>,
+    hypernet: Res<Hypernet>,
+    sim_time: Res<SimTime>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    if !sim_time.is_new_month() {
+        return;
+    }
+
+    let (n, _) = hypernet.graph.capacity();
+    let mut influence: HashMap<Entity, Vec<i32>> = HashMap::new();
+    let mut project = |empire: Entity, system: u32, strength: i32| {
+        let field = influence.entry(empire).or_insert_with(|| vec![0; n]);
+        project_influence(&hypernet, field, system, strength);
+    };
+
+    for (star, _, _) in &star_query {
+        for colony in star
+            .orbiters
+            .iter()
+            .filter_map(|x| colony_query.get(*x).ok())
+        {
+            let strength = 100 * (colony.population.val().max(1).ilog10() as i32);
+            project(colony.owner, star.node_id, strength);
+        }
+    }
+    for (star, depot) in &depot_query {
+        project(depot.owner, star.node_id, STATION_INFLUENCE);
+    }
+
+    for (star, mut claim, mut system_influence) in star_query.iter_mut() {
+        let mut strengths = influence
+            .iter()
+            .map(|(empire, field)| (*empire, field[star.node_id as usize]))
+            .filter(|(_, s)| *s > 0)
+            .collect::<Vec<_>>();
+        strengths.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        system_influence.strengths = strengths;
+
+        let leader = system_influence.strengths.first().copied();
+
+        let Some(owner) = claim.owner else {
+            match leader {
+                Some((empire, strength)) if strength >= CLAIM_THRESHOLD => {
+                    if system_influence.advance_claim(empire) {
+                        claim.owner = Some(empire);
+                        claim.claimed_tick = sim_settings.current_tick;
+                    }
+                }
+                _ => system_influence.reset_claim(),
+            }
+            continue;
+        };
+
+        let owner_strength = system_influence.strength(owner);
+        let challenger = system_influence
+            .strengths
+            .iter()
+            .find(|(e, s)| *e != owner && *s * 100 > owner_strength * CONTEST_MARGIN_PERCENT)
+            .map(|(e, _)| *e);
+
+        let colonised = star
+            .orbiters
+            .iter()
+            .filter_map(|x| colony_query.get(*x).ok())
+            .any(|x| x.owner == owner);
+
+        if claim.contested_by != challenger {
+            claim.contested_by = challenger;
+        }
+
+        if colonised {
+            system_influence.reset_claim();
+        } else if let Some(challenger) = challenger {
+            if system_influence.advance_claim(challenger) {
+                claim.owner = Some(challenger);
+                claim.contested_by = None;
+                claim.claimed_tick = sim_settings.current_tick;
+            }
+        } else if owner_strength == 0 {
+            claim.owner = None;
+            system_influence.reset_claim();
+        } else {
+            system_influence.reset_claim();
+        }
+    }
+}
+
+// Adds influence around "system", halving with every jump
+// Overlapping sources of the same empire add up
+fn project_influence(hypernet: &Hypernet, field: &mut [i32], system: u32, strength: i32) {
+    let mut visited = vec![system];
+    let mut queue = VecDeque::from([(system, 0)]);
+    while let Some((top, jumps)) = queue.pop_front() {
+        field[top as usize] += strength >> jumps;
+        if jumps == INFLUENCE_JUMPS {
+            continue;
+        }
+        for next in hypernet.graph.neighbors(top.into()) {
+            let next = next.index() as u32;
+            if !visited.contains(&next) {
+                visited.push(next);
+                queue.push_back((next, jumps + 1));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Six systems in a row, 40 parsecs apart
+    fn chain() -> Hypernet {
+        let positions = (0..6).map(|x| (x as f32 * 40.0, 0.0)).collect::<Vec<_>>();
+        let lanes = (0..5).map(|x| (x, x + 1)).collect::<Vec<_>>();
+        Hypernet::from_lanes(&positions, &lanes)
+    }
+
+    #[test]
+    fn halves_with_every_jump() {
+        let hypernet = chain();
+        let mut field = vec![0; 6];
+        project_influence(&hypernet, &mut field, 0, 400);
+        assert_eq!(field, [400, 200, 100, 50, 0, 0]);
+        project_influence(&hypernet, &mut field, 5, 400);
+        assert_eq!(field, [400, 200, 150, 150, 200, 400]);
+    }
+
+    #[test]
+    fn claims_follow_influence() {
+        let mut hypernet = chain();
+        let mut world = World::new();
+        let stars = hypernet.spawn_stars(&mut world);
+        for star in &stars {
+            world.entity_mut(*star).insert(SystemInfluence::default());
+        }
+        world.insert_resource(hypernet);
+        world.insert_resource(SimTime::new());
+        world.insert_resource(SimulationSettings::at_tick(7));
+        let (a, b) = (world.spawn_empty().id(), world.spawn_empty().id());
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(update_influence_system);
+        let mut months = |world: &mut World, n| {
+            for _ in 0..n {
+                schedule.run(world);
+            }
+            stars
+                .iter()
+                .map(|x| world.get::<StarClaim>(*x).unwrap().owner)
+                .collect::<Vec<_>>()
+        };
+
+        // A station claims its own system and the next one, further out it's too weak
+        world.entity_mut(stars[0]).insert(SupplyDepot { owner: a });
+        assert_eq!(months(&mut world, 3), [None; 6]);
+        assert_eq!(
+            months(&mut world, 1),
+            [Some(a), Some(a), None, None, None, None]
+        );
+        assert_eq!(world.get::<StarClaim>(stars[0]).unwrap().claimed_tick, 7);
+
+        // Only a much stronger challenger contests a claim
+        world.entity_mut(stars[1]).insert(SupplyDepot { owner: b });
+        let owners = months(&mut world, 1);
+        assert_eq!(world.get::<StarClaim>(stars[0]).unwrap().contested_by, None);
+        assert_eq!(
+            world.get::<StarClaim>(stars[1]).unwrap().contested_by,
+            Some(b)
+        );
+        assert_eq!(owners[1], Some(a));
+        let owners = months(&mut world, 3);
+        assert_eq!(owners[..3], [Some(a), Some(b), Some(b)]);
+        assert_eq!(world.get::<StarClaim>(stars[1]).unwrap().contested_by, None);
+
+        // Systems nobody projects into anymore are released
+        world.entity_mut(stars[0]).remove::<SupplyDepot>();
+        world.entity_mut(stars[1]).remove::<SupplyDepot>();
+        assert_eq!(months(&mut world, 1), [None; 6]);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+// Colonies whose population falls below this are abandoned
+const ABANDON_POPULATION: i64 = 1000;
+
+// Placed on an empire once it has lost every colony. It keeps its entity (names, colours, history) but no longer takes part
+#[derive(Component)]
+pub struct Eliminated;
+
+#[derive(Event)]
+pub struct ColonyAbandonedEvent {
+    pub planet_entity: Entity,
+    pub owner: Entity,
+}
+
+#[derive(Event)]
+pub struct SystemReleasedEvent {
+    pub star_entity: Entity,
+    pub previous_owner: Entity,
+}
+
+#[derive(Event)]
+pub struct EmpireEliminatedEvent {
+    pub empire: Entity,
+}
+
+// Colonies that have dwindled away are abandoned, and their system released if the owner has nothing else left there
+pub fn abandon_colonies_system(
+    colony_query: Query<(Entity, &Colony, &ChildOf)>,
+    mut star_query: Query<(&Star, &mut StarClaim)>,
+    mut ev_abandoned: EventWriter<ColonyAbandonedEvent>,
+    mut ev_released: EventWriter<SystemReleasedEvent>,
+    mut commands: Commands,
+) {
+    let abandoned = colony_query
+        .iter()
+        .filter(|(_, colony, _)| colony.population.val() < ABANDON_POPULATION)
+        .map(|(planet, colony, parent)| (planet, colony.owner, parent.parent()))
+        .collect::<Vec<_>>();
+
+    for (planet_entity, owner, star_entity) in &abandoned {
+        info!("Colony {:?} abandoned", planet_entity);
+        commands.entity(*planet_entity).remove::<Colony>();
+        ev_abandoned.write(ColonyAbandonedEvent {
+            planet_entity: *planet_entity,
+            owner: *owner,
+        });
+
+        let Ok((star, mut claim)) = star_query.get_mut(*star_entity) else {
+            continue;
+        };
+        if claim.owner != Some(*owner) {
+            continue;
+        }
+        let colonies_remain = star
+            .orbiters
+            .iter()
+            .filter(|x| !abandoned.iter().any(|(planet, _, _)| planet == *x))
+            .filter_map(|x| colony_query.get(*x).ok())
+            .any(|(_, colony, _)| colony.owner == *owner);
+        if !colonies_remain {
+            claim.owner = None;
+            claim.contested_by = None;
+            ev_released.write(SystemReleasedEvent {
+                star_entity: *star_entity,
+                previous_owner: *owner,
+            });
+        }
+    }
+}
+
+// Empires without a single colony left are eliminated
+// Their fleets are disbanded, their remaining claims released and every other empire forgets about them
+#[allow(clippy::too_many_arguments)]
+pub fn eliminate_empires_system(
+    empire_query: Query<Entity, (With<Empire>, Without<Eliminated>)>,
+    mut diplomacy_query: Query<&mut Diplomacy>,
+    colony_query: Query<&Colony>,
+    fleet_query: Query<(Entity, &Fleet)>,
+    mut star_query: Query<(Entity, &mut StarClaim)>,
+    mut ev_released: EventWriter<SystemReleasedEvent>,
+    mut ev_eliminated: EventWriter<EmpireEliminatedEvent>,
+    mut commands: Commands,
+) {
+    let surviving = colony_query.iter().map(|x| x.owner).collect::<HashSet<_>>();
+
+    for empire in &empire_query {
+        if surviving.contains(&empire) {
+            continue;
+        }
+        info!("Empire {:?} has been eliminated", empire);
+
+        for (fleet_entity, fleet) in &fleet_query {
+            if fleet.owner == empire {
+                commands.entity(fleet_entity).despawn();
+            }
+        }
+
+        for (star_entity, mut claim) in star_query.iter_mut() {
+            if claim.contested_by == Some(empire) {
+                claim.contested_by = None;
+            }
+            if claim.owner == Some(empire) {
+                claim.owner = None;
+                claim.contested_by = None;
+                ev_released.write(SystemReleasedEvent {
+                    star_entity,
+                    previous_owner: empire,
+                });
+            }
+        }
+
+        // Only touch the ones that mention it, changing Diplomacy rebuilds the navigation masks
+        for mut diplomacy in diplomacy_query.iter_mut() {
+            if diplomacy.at_war.contains(&empire) || diplomacy.grants_access.contains(&empire) {
+                diplomacy.at_war.retain(|x| *x != empire);
+                diplomacy.grants_access.retain(|x| *x != empire);
+            }
+        }
+
+        commands.entity(empire).insert(Eliminated);
+        ev_eliminated.write(EmpireEliminatedEvent { empire });
+    }
+}
+
+// Game over for the player. Pause so it doesn't go unnoticed
+pub fn player_eliminated_system(
+    mut ev_eliminated: EventReader<EmpireEliminatedEvent>,
+    player_empire: Res<crate::galaxy::empire::PlayerEmpire>,
+    mut sim_settings: ResMut<SimulationSettings>,
+) {
+    for ev in ev_eliminated.read() {
+        if player_empire.empire == Some(ev.empire) {
+            info!("The player's empire has been eliminated");
+            sim_settings.paused = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_colony(world: &mut World, star: Entity, owner: Entity, population: i64) -> Entity {
+        let planet = world
+            .spawn((
+                ChildOf(star),
+                Colony {
+                    owner,
+                    claimed_tick: 0,
+                    population: Population::new(population),
+                    economy: Economy::new(),
+                },
+            ))
+            .id();
+        world.get_mut::<Star>(star).unwrap().orbiters.push(planet);
+        planet
+    }
+
+    fn events<E: Event>(world: &World) -> usize {
+        world.resource::<Events<E>>().len()
+    }
+
+    #[test]
+    fn releases_the_system_with_the_last_colony() {
+        let mut hypernet = Hypernet::from_lanes(&[(0.0, 0.0)], &[]);
+        let mut world = World::new();
+        let star = hypernet.spawn_stars(&mut world)[0];
+        let owner = world.spawn_empty().id();
+        world.get_mut::<StarClaim>(star).unwrap().owner = Some(owner);
+        let thriving = spawn_colony(&mut world, star, owner, 1_000_000);
+        let dwindling = spawn_colony(&mut world, star, owner, 999);
+        world.init_resource::<Events<ColonyAbandonedEvent>>();
+        world.init_resource::<Events<SystemReleasedEvent>>();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(abandon_colonies_system);
+        schedule.run(&mut world);
+        assert!(world.get::<Colony>(dwindling).is_none());
+        assert_eq!(world.get::<StarClaim>(star).unwrap().owner, Some(owner));
+        assert_eq!(events::<ColonyAbandonedEvent>(&world), 1);
+
+        world.get_mut::<Colony>(thriving).unwrap().population.set(0);
+        schedule.run(&mut world);
+        assert!(world.get::<Colony>(thriving).is_none());
+        assert_eq!(world.get::<StarClaim>(star).unwrap().owner, None);
+        assert_eq!(events::<SystemReleasedEvent>(&world), 1);
+    }
+
+    #[test]
+    fn eliminates_empires_without_colonies() {
+        let mut hypernet = Hypernet::from_lanes(&[(0.0, 0.0), (10.0, 0.0)], &[(0, 1)]);
+        let mut world = World::new();
+        let stars = hypernet.spawn_stars(&mut world);
+        let mut spawn_empire = |name| {
+            world
+                .spawn((Empire::named(name), Diplomacy::default()))
+                .id()
+        };
+        let (survivor, doomed) = (spawn_empire("Survivor"), spawn_empire("Doomed"));
+        spawn_colony(&mut world, stars[0], survivor, 1_000_000);
+        world.get_mut::<StarClaim>(stars[1]).unwrap().owner = Some(doomed);
+        world.get_mut::<StarClaim>(stars[0]).unwrap().contested_by = Some(doomed);
+        let mut diplomacy = world.get_mut::<Diplomacy>(survivor).unwrap();
+        diplomacy.at_war.push(doomed);
+        diplomacy.grants_access.push(doomed);
+        let fleet = world
+            .spawn(Fleet {
+                owner: doomed,
+                name: String::new(),
+                time_since_last_jump: 0,
+            })
+            .id();
+        world.init_resource::<Events<SystemReleasedEvent>>();
+        world.init_resource::<Events<EmpireEliminatedEvent>>();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(eliminate_empires_system);
+        schedule.run(&mut world);
+        assert!(world.get::<Eliminated>(doomed).is_some());
+        assert!(world.get::<Eliminated>(survivor).is_none());
+        assert!(world.get_entity(fleet).is_err());
+        assert_eq!(world.get::<StarClaim>(stars[1]).unwrap().owner, None);
+        assert_eq!(world.get::<StarClaim>(stars[0]).unwrap().contested_by, None);
+        let diplomacy = world.get::<Diplomacy>(survivor).unwrap();
+        assert!(diplomacy.at_war.is_empty() && diplomacy.grants_access.is_empty());
+
+        // Only once
+        schedule.run(&mut world);
+        assert_eq!(events::<EmpireEliminatedEvent>(&world), 1);
+        assert_eq!(events::<SystemReleasedEvent>(&world), 1);
+    }
+}
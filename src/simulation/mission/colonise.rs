@@ -1,42 +1,146 @@
-use bevy::prelude::*;
+use crate::galaxy::fleet::FleetColonyCrew;
 use crate::prelude::*;
+use crate::simulation::fleet_behaviour::colonisation::drop_target;
+use crate::simulation::fleet_behaviour::navigation::{Action, NavPosition, Navigator};
+use bevy::prelude::*;
 
-use super::MissionState;
+use super::planet_launch_colony::LaunchColonyMission;
+use super::{Mission, MissionState, OnMission};
 
 #[derive(Component)]
-struct ColoniseMission {
-    target : Entity,
-    origin_empire : Entity,
-    mission_state : MissionState
+pub struct ColoniseMission;
+
+// Pending missions get a colony ship of their owner that isn't on a mission yet, and hasn't started colonising somewhere else
+// The ship launched for the mission is preferred, then any other free ship
+// Failing that, the owner's most populous colony is asked to send its next ship there
+pub fn assign_colonise_missions_system(
+    mut mission_query: Query<&mut Mission, With<ColoniseMission>>,
+    mut fleet_query: Query<
+        (Entity, &Fleet, &mut FleetColonyCrew, &mut Navigator),
+        Without<OnMission>,
+    >,
+    mut launch_query: Query<(&Colony, &mut LaunchColonyMission)>,
+    empire_query: Query<&EmpireIndex>,
+    mut commands: Commands,
+) {
+    // commands are deferred, so ships assigned this tick still look free
+    let mut assigned = Vec::new();
+
+    for mut mission in mission_query.iter_mut() {
+        if mission.state != MissionState::Pending {
+            continue;
+        }
+
+        let free_ship = fleet_query
+            .iter()
+            .filter(|(entity, fleet, _, nav)| {
+                fleet.owner == mission.owner
+                    && !assigned.contains(entity)
+                    && !matches!(nav.action, Action::Colonise(_))
+            })
+            .max_by_key(|(entity, _, crew, _)| (crew.destination == Some(mission.target), *entity))
+            .map(|(entity, ..)| entity);
+
+        if let Some(fleet_entity) = free_ship {
+            let (_, _, mut crew, mut nav) = fleet_query.get_mut(fleet_entity).unwrap();
+            if crew.destination != Some(mission.target) {
+                drop_target(&mut crew, &mut nav);
+                crew.destination = Some(mission.target);
+            }
+            mission.assign(fleet_entity);
+            commands.entity(fleet_entity).insert(OnMission);
+            assigned.push(fleet_entity);
+            continue;
+        }
+
+        let Ok(index) = empire_query.get(mission.owner) else {
+            continue;
+        };
+        let reserved = index.colonies.iter().any(|x| {
+            launch_query
+                .get(*x)
+                .is_ok_and(|(_, launch)| launch.target() == Some(mission.target))
+        });
+        if reserved {
+            continue;
+        }
+        let origin = index
+            .colonies
+            .iter()
+            .filter(|x| {
+                launch_query
+                    .get(**x)
+                    .is_ok_and(|(_, launch)| launch.target().is_none())
+            })
+            .max_by_key(|x| launch_query.get(**x).unwrap().0.population.val())
+            .copied();
+        if let Some(origin) = origin {
+            launch_query
+                .get_mut(origin)
+                .unwrap()
+                .1
+                .set_target(Some(mission.target));
+        }
+    }
 }
 
-fn validate_missions_system(
-    mut mission_query : Query<&mut ColoniseMission>,
-    planet_query : Query<(&Planet,Option<&Colony>)>,
-    star_query : Query<&StarClaim>,
-    empires_query : Query<&Empire>,
-    hypernet : Res<Hypernet>
+pub fn validate_colonise_missions_system(
+    mut mission_query: Query<&mut Mission, With<ColoniseMission>>,
+    planet_query: Query<(&Planet, Option<&Colony>)>,
+    fleet_query: Query<(&NavPosition, &FleetColonyCrew)>,
+    star_query: Query<&StarClaim>,
+    empires_query: Query<&Empire>,
+    hypernet: Res<Hypernet>,
 ) {
     for mut mission in mission_query.iter_mut() {
-        if empires_query.get(mission.origin_empire).is_err() {
-            mission.mission_state = MissionState::FailedError;
+        if !mission.is_active() {
+            continue;
+        }
+        if empires_query.get(mission.owner).is_err() {
+            mission.state = MissionState::FailedError;
             continue;
         };
-        let Ok((planet,colony)) = planet_query.get(mission.target) else {
-            mission.mission_state = MissionState::FailedError;
+        let Ok((planet, colony)) = planet_query.get(mission.target) else {
+            mission.state = MissionState::FailedError;
             continue;
         };
 
-        let star= star_query.get(hypernet.star(planet.star_id).entity).unwrap(); // Stars do not simply disappear, so this is a crash
+        let star = star_query
+            .get(hypernet.star(planet.star_id).entity)
+            .unwrap(); // Stars do not simply disappear, so this is a crash
 
         if let Some(colony) = colony {
             // Mission fails if you are beaten to it by another empire
+            // The star claim doesn't matter here, influence can hand the system to someone else while the colony stays ours
             // POINT FOR IMPROVEMENT:
             // Should maybe check if the mission-originating empire ~knows~ that it's been beaten to it
-            if star.owner != Some(mission.origin_empire) || colony.owner != mission.origin_empire {
-                mission.mission_state = MissionState::Failed;
+            if colony.owner != mission.owner {
+                mission.state = MissionState::Failed;
             } else {
-                mission.mission_state = MissionState::Succeeded;
+                mission.state = MissionState::Succeeded;
+            }
+            continue;
+        }
+        // Colony ships can't settle in a system claimed by someone else, see process_colonise_events
+        if star.owner.is_some() && star.owner != Some(mission.owner) {
+            mission.state = MissionState::Failed;
+            continue;
+        }
+
+        let Some(fleet) = mission.fleet else {
+            continue;
+        };
+        match fleet_query.get(fleet) {
+            // lost on the way
+            Err(_) => mission.state = MissionState::Failed,
+            // the ship gave up on the target (eg. it can't be reached any more)
+            Ok((_, crew)) if crew.destination != Some(mission.target) => {
+                mission.state = MissionState::Failed
+            }
+            Ok((nav_pos, _)) => {
+                if nav_pos.root_system == planet.star_id {
+                    mission.state = MissionState::InProgress;
+                }
             }
         }
     }
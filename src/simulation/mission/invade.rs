@@ -0,0 +1,50 @@
+use crate::galaxy::fleet::FleetTroops;
+use crate::prelude::*;
+use crate::simulation::fleet_behaviour::navigation::NavPosition;
+use bevy::prelude::*;
+
+use super::{Mission, MissionState};
+
+// Troop ships are assigned when they're raised, see launch_invasions_system
+#[derive(Component)]
+pub struct InvadeMission;
+
+pub fn validate_invade_missions_system(
+    mut mission_query: Query<&mut Mission, With<InvadeMission>>,
+    planet_query: Query<(&Planet, Option<&Colony>)>,
+    fleet_query: Query<&NavPosition, With<FleetTroops>>,
+    empires_query: Query<&Empire>,
+) {
+    for mut mission in mission_query.iter_mut() {
+        if !mission.is_active() {
+            continue;
+        }
+        if empires_query.get(mission.owner).is_err() {
+            mission.state = MissionState::FailedError;
+            continue;
+        };
+        // nothing left to invade if the colony was abandoned
+        let Ok((planet, Some(colony))) = planet_query.get(mission.target) else {
+            mission.state = MissionState::FailedError;
+            continue;
+        };
+
+        if colony.owner == mission.owner {
+            mission.state = MissionState::Succeeded;
+            continue;
+        }
+
+        let Some(fleet) = mission.fleet else {
+            continue;
+        };
+        match fleet_query.get(fleet) {
+            // repelled, or lost on the way
+            Err(_) => mission.state = MissionState::Failed,
+            Ok(nav_pos) => {
+                if nav_pos.root_system == planet.star_id {
+                    mission.state = MissionState::InProgress;
+                }
+            }
+        }
+    }
+}
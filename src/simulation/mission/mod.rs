@@ -1 +1,320 @@
+use crate::galaxy::fleet::{FleetBundle, FleetStation, StationDuty};
+use crate::prelude::*;
+use bevy::prelude::*;
+
+pub mod colonise;
+pub mod invade;
+pub mod patrol;
 pub mod planet_launch_colony;
+pub mod survey;
+
+use patrol::PatrolMission;
+use survey::SurveyMission;
+
+use super::SimTick;
+
+// MISSIONS
+// A mission is an entity of its own: a Mission component (owner, target, state, assigned fleet) plus a marker for its type
+// Missions are created by whoever wants something done (the AI planner, the invasion system...),
+// each type validates its own missions every tick, and finished ones are reported through MissionCompletedEvent then despawned
+
+// Days a mission can wait for a fleet before it's given up on
+const PENDING_EXPIRY: i64 = 360;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MissionState {
+    Pending,    // Waiting for a fleet
+    Assigned,   // Fleet on its way
+    InProgress, // Fleet at the target, carrying it out
+    Succeeded,
+    Failed,      // Beaten to it, or the fleet was lost
+    FailedError, // Target or owner no longer exists
+}
+
+impl MissionState {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            MissionState::Succeeded | MissionState::Failed | MissionState::FailedError
+        )
+    }
+}
+
+#[derive(Component)]
+pub struct Mission {
+    pub owner: Entity,
+    pub target: Entity, // Planet, or star for surveys and patrols
+    pub state: MissionState,
+    pub fleet: Option<Entity>,
+    created_tick: i64,
+    started_tick: i64, // When it went in progress
+}
+
+impl Mission {
+    pub fn new(owner: Entity, target: Entity, tick: i64) -> Self {
+        Self {
+            owner,
+            target,
+            state: MissionState::Pending,
+            fleet: None,
+            created_tick: tick,
+            started_tick: tick,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.state.is_finished()
+    }
+
+    // The fleet also needs an OnMission marker
+    pub fn assign(&mut self, fleet: Entity) {
+        self.fleet = Some(fleet);
+        self.state = MissionState::Assigned;
+    }
+
+    // Once the fleet has reached the target. The mission stays in progress from the first tick this is called on
+    pub fn start(&mut self, tick: i64) {
+        if self.state != MissionState::InProgress {
+            self.state = MissionState::InProgress;
+            self.started_tick = tick;
+        }
+    }
+
+    pub fn days_in_progress(&self, tick: i64) -> i64 {
+        tick - self.started_tick
+    }
+}
+
+// On fleets that have been assigned a mission, so they aren't handed another one
+#[derive(Component)]
+pub struct OnMission;
+
+#[derive(Event)]
+pub struct MissionCompletedEvent {
+    pub owner: Entity,
+    pub target: Entity,
+    pub state: MissionState,
+}
+
+fn expire_pending_missions_system(
+    mut mission_query: Query<&mut Mission>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    for mut mission in mission_query.iter_mut() {
+        if mission.state == MissionState::Pending
+            && sim_settings.current_tick - mission.created_tick > PENDING_EXPIRY
+        {
+            mission.state = MissionState::Failed;
+        }
+    }
+}
+
+// Surveys and patrols raise a fleet of their own at the owner's most populous colony
+fn assign_station_missions_system(
+    mut mission_query: Query<(&mut Mission, Has<SurveyMission>, Has<PatrolMission>)>,
+    empire_query: Query<&EmpireIndex>,
+    colony_query: Query<(&Planet, &Colony)>,
+    star_query: Query<&Star>,
+    mut commands: Commands,
+) {
+    for (mut mission, survey, patrol) in mission_query.iter_mut() {
+        if !(survey || patrol) || mission.state != MissionState::Pending {
+            continue;
+        }
+        let Ok(star) = star_query.get(mission.target) else {
+            continue;
+        };
+        let Some((planet, _)) = empire_query.get(mission.owner).ok().and_then(|index| {
+            index
+                .colonies
+                .iter()
+                .filter_map(|x| colony_query.get(*x).ok())
+                .max_by_key(|(_, colony)| colony.population.val())
+        }) else {
+            continue;
+        };
+
+        let fleet = commands
+            .spawn((
+                FleetBundle::new(mission.owner, planet.system_local_pos(), planet.star_id),
+                FleetStation {
+                    system: star.node_id,
+                    duty: if survey {
+                        StationDuty::Survey
+                    } else {
+                        StationDuty::Patrol
+                    },
+                },
+                OnMission,
+            ))
+            .id();
+        mission.assign(fleet);
+    }
+}
+
+fn finish_missions_system(
+    mission_query: Query<(Entity, &Mission)>,
+    station_query: Query<(), With<FleetStation>>,
+    mut ev_completed: EventWriter<MissionCompletedEvent>,
+    mut commands: Commands,
+) {
+    for (entity, mission) in &mission_query {
+        if !mission.state.is_finished() {
+            continue;
+        }
+
+        ev_completed.write(MissionCompletedEvent {
+            owner: mission.owner,
+            target: mission.target,
+            state: mission.state,
+        });
+        // the fleet is often gone by now (colony ships are used up, troops land)
+        // Scouts and patrols were only raised for this mission, so they stand down with it
+        if let Some(fleet) = mission.fleet {
+            if station_query.contains(fleet) {
+                commands.entity(fleet).try_despawn();
+            } else {
+                commands.entity(fleet).try_remove::<OnMission>();
+            }
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+pub struct MissionPlugin;
+
+impl Plugin for MissionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(planet_launch_colony::PlanetAutoColonyMissionPlugin)
+            .add_event::<MissionCompletedEvent>()
+            .add_systems(
+                SimTick,
+                (
+                    colonise::assign_colonise_missions_system,
+                    colonise::validate_colonise_missions_system,
+                    invade::validate_invade_missions_system,
+                    assign_station_missions_system,
+                    survey::validate_survey_missions_system,
+                    patrol::validate_patrol_missions_system,
+                    expire_pending_missions_system,
+                    finish_missions_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::fleet_behaviour::navigation::{NavOffset, NavPosition};
+
+    struct Setup {
+        world: World,
+        schedule: Schedule,
+        empire: Entity,
+        stars: Vec<Entity>,
+    }
+
+    // An empire holding the first of two systems
+    fn setup() -> Setup {
+        let mut hypernet = Hypernet::from_lanes(&[(0.0, 0.0), (10.0, 0.0)], &[(0, 1)]);
+        let mut world = World::new();
+        let stars = hypernet.spawn_stars(&mut world);
+        let empire = world.spawn(Empire::named("Us")).id();
+        world.get_mut::<StarClaim>(stars[0]).unwrap().owner = Some(empire);
+        world.insert_resource(hypernet);
+        world.init_resource::<Events<MissionCompletedEvent>>();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                patrol::validate_patrol_missions_system,
+                expire_pending_missions_system,
+                finish_missions_system,
+            )
+                .chain(),
+        );
+        Setup {
+            world,
+            schedule,
+            empire,
+            stars,
+        }
+    }
+
+    impl Setup {
+        fn run(&mut self, tick: i64) {
+            self.world
+                .insert_resource(SimulationSettings::at_tick(tick));
+            self.schedule.run(&mut self.world);
+        }
+
+        fn completed(&self) -> Vec<MissionState> {
+            let events = self.world.resource::<Events<MissionCompletedEvent>>();
+            events
+                .iter_current_update_events()
+                .map(|x| x.state)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn patrols_stand_down_once_done() {
+        let mut setup = setup();
+        let patrol = setup
+            .world
+            .spawn((
+                NavPosition {
+                    root_system: 0,
+                    offset: NavOffset::Star(Vec3::ZERO),
+                },
+                FleetStation {
+                    system: 0,
+                    duty: StationDuty::Patrol,
+                },
+                OnMission,
+            ))
+            .id();
+        let mut mission = Mission::new(setup.empire, setup.stars[0], 0);
+        mission.assign(patrol);
+        let mission = setup.world.spawn((mission, PatrolMission)).id();
+
+        // Patrols stay 180 days
+        setup.run(10);
+        let state = setup.world.get::<Mission>(mission).unwrap().state;
+        assert_eq!(state, MissionState::InProgress);
+        setup.run(189);
+        assert!(setup.world.get::<Mission>(mission).is_some());
+
+        setup.run(190);
+        assert!(setup.world.get_entity(mission).is_err());
+        assert!(setup.world.get_entity(patrol).is_err());
+        assert_eq!(setup.completed(), [MissionState::Succeeded]);
+    }
+
+    #[test]
+    fn gives_up_on_missions() {
+        let mut setup = setup();
+        // Nobody picked it up
+        let waiting = setup
+            .world
+            .spawn((Mission::new(setup.empire, setup.stars[0], 0), PatrolMission))
+            .id();
+        // Not ours to guard
+        let lost = setup
+            .world
+            .spawn((Mission::new(setup.empire, setup.stars[1], 0), PatrolMission))
+            .id();
+
+        setup.run(PENDING_EXPIRY);
+        assert!(setup.world.get_entity(waiting).is_ok());
+        assert!(setup.world.get_entity(lost).is_err());
+        setup.run(PENDING_EXPIRY + 1);
+        assert!(setup.world.get_entity(waiting).is_err());
+        assert_eq!(
+            setup.completed(),
+            [MissionState::Failed, MissionState::Failed]
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use crate::galaxy::fleet::FleetStation;
+use crate::prelude::*;
+use crate::simulation::fleet_behaviour::navigation::NavPosition;
+use bevy::prelude::*;
+
+use super::{Mission, MissionState};
+
+// Days a patrol stays in the system it guards. It adds to the system's defence in the threat maps meanwhile
+const PATROL_DAYS: i64 = 180;
+
+// Target is a star the owner holds. The patrol is raised when the mission is assigned, see assign_station_missions_system
+#[derive(Component)]
+pub struct PatrolMission;
+
+pub fn validate_patrol_missions_system(
+    mut mission_query: Query<&mut Mission, With<PatrolMission>>,
+    star_query: Query<(&Star, &StarClaim)>,
+    fleet_query: Query<&NavPosition, With<FleetStation>>,
+    empires_query: Query<&Empire>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    for mut mission in mission_query.iter_mut() {
+        if !mission.is_active() {
+            continue;
+        }
+        if empires_query.get(mission.owner).is_err() {
+            mission.state = MissionState::FailedError;
+            continue;
+        };
+        let Ok((star, claim)) = star_query.get(mission.target) else {
+            mission.state = MissionState::FailedError;
+            continue;
+        };
+
+        // nothing left to guard
+        if claim.owner != Some(mission.owner) {
+            mission.state = MissionState::Failed;
+            continue;
+        }
+
+        let Some(fleet) = mission.fleet else {
+            continue;
+        };
+        match fleet_query.get(fleet) {
+            // lost on the way
+            Err(_) => mission.state = MissionState::Failed,
+            Ok(nav_pos) => {
+                if nav_pos.root_system == star.node_id {
+                    mission.start(sim_settings.current_tick);
+                    if mission.days_in_progress(sim_settings.current_tick) >= PATROL_DAYS {
+                        mission.state = MissionState::Succeeded;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -29,6 +29,22 @@ pub struct LaunchColonyMission {
     current_capital: i32,
 }
 
+impl LaunchColonyMission {
+    pub fn target(&self) -> Option<Entity> {
+        self.target
+    }
+
+    // The target only applies to the next colony ship
+    pub fn set_target(&mut self, target: Option<Entity>) {
+        self.target = target;
+    }
+
+    // Priority missions gather crew twice as fast
+    pub fn set_priority(&mut self, priority: bool) {
+        self.efficiency_factor = if priority { 2 } else { 1 };
+    }
+}
+
 impl Default for LaunchColonyMission {
     fn default() -> Self {
         Self {
@@ -62,7 +78,8 @@ fn update_mission_system(
         */
 
         if target_valid {
-            mission.current_crew += origin_colony.get_daily_colonists() as i32;
+            mission.current_crew +=
+                origin_colony.get_daily_colonists() as i32 * mission.efficiency_factor;
             mission.current_capital += origin_colony.get_daily_colony_ship_construction() as i32;
 
             // LAUNCH THE COLONY SHIP
@@ -76,8 +93,9 @@ fn update_mission_system(
                         origin_planet.star_id,
                     ),
                     crate::galaxy::fleet::FleetColonyCrew {
-                        destination: mission.target,
+                        destination: mission.target.take(),
                         colonists: mission.current_crew as i64,
+                        rejected: Vec::new(),
                     },
                 ));
                 mission.current_crew = 0;
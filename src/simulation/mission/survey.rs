@@ -0,0 +1,84 @@
+use crate::galaxy::fleet::FleetStation;
+use crate::prelude::*;
+use crate::simulation::fleet_behaviour::navigation::NavPosition;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use super::{Mission, MissionState};
+
+// Days a scout has to spend in a system to survey it
+const SURVEY_DAYS: i64 = 30;
+
+// Target is a star. The scout is raised when the mission is assigned, see assign_station_missions_system
+#[derive(Component)]
+pub struct SurveyMission;
+
+// Per empire. Systems it has sent scouts to
+#[derive(Component, Default)]
+pub struct SurveyedSystems {
+    systems: HashSet<u32>,
+}
+
+impl SurveyedSystems {
+    // Known well enough to plan around: the empire's own systems, their neighbours, and anything it has surveyed
+    pub fn is_known(&self, system: u32, mask: &NavigationMask, hypernet: &Hypernet) -> bool {
+        let owned = |x: u32| mask.access(x) == SystemAccess::Owned;
+        self.systems.contains(&system)
+            || owned(system)
+            || hypernet
+                .graph
+                .neighbors(system.into())
+                .any(|x| owned(x.index() as u32))
+    }
+}
+
+pub fn validate_survey_missions_system(
+    mut mission_query: Query<&mut Mission, With<SurveyMission>>,
+    mut empire_query: Query<(&mut SurveyedSystems, &NavigationMask)>,
+    star_query: Query<&Star>,
+    fleet_query: Query<&NavPosition, With<FleetStation>>,
+    hypernet: Res<Hypernet>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    for mut mission in mission_query.iter_mut() {
+        if !mission.is_active() {
+            continue;
+        }
+        let Ok((mut surveyed, mask)) = empire_query.get_mut(mission.owner) else {
+            mission.state = MissionState::FailedError;
+            continue;
+        };
+        let Ok(star) = star_query.get(mission.target) else {
+            mission.state = MissionState::FailedError;
+            continue;
+        };
+
+        // eg. the system was settled next to in the meantime
+        if surveyed.is_known(star.node_id, mask, &hypernet) {
+            mission.state = MissionState::Succeeded;
+            continue;
+        }
+        // closed to us, the scout would never get there
+        if !mask.access(star.node_id).is_passable() {
+            mission.state = MissionState::Failed;
+            continue;
+        }
+
+        let Some(fleet) = mission.fleet else {
+            continue;
+        };
+        match fleet_query.get(fleet) {
+            // lost on the way
+            Err(_) => mission.state = MissionState::Failed,
+            Ok(nav_pos) => {
+                if nav_pos.root_system == star.node_id {
+                    mission.start(sim_settings.current_tick);
+                    if mission.days_in_progress(sim_settings.current_tick) >= SURVEY_DAYS {
+                        surveyed.systems.insert(star.node_id);
+                        mission.state = MissionState::Succeeded;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,26 @@
 use bevy::prelude::*;
-mod economy;
+mod diplomacy;
+pub mod economy;
+mod gateways;
+pub mod history;
+pub mod influence;
+pub mod lifecycle;
 mod orbits;
+pub mod policy;
 mod schedule;
+pub mod stats_export;
+pub mod supply;
+pub mod threat;
 mod time;
 
-mod mission;
+pub mod mission;
+pub mod vizier;
 
 pub mod fleet_behaviour;
 
 pub mod data;
 
-pub use time::SimTime;
+pub use time::{DateFormat, SimTime};
 
 pub use schedule::{BuildGalaxyGraphics, SimPostTick, SimPreTick, SimStart, SimTick};
 
@@ -71,6 +81,18 @@ impl SimulationSettings {
     }
 }
 
+#[cfg(test)]
+impl SimulationSettings {
+    pub fn at_tick(current_tick: i64) -> Self {
+        Self {
+            paused: true,
+            mode: SimulationMode::Normal,
+            current_tick,
+            time_since_tick: 0.0,
+        }
+    }
+}
+
 pub struct SimulationPlugin;
 
 fn simulation_start_system(world: &mut World) {
@@ -97,7 +119,7 @@ fn simulation_tick_system(world: &mut World) {
     }
 }
 
-use fleet_behaviour::colonisation;
+use fleet_behaviour::{colonisation, invasion};
 
 impl Plugin for SimulationPlugin {
     fn build(&self, app: &mut App) {
@@ -114,10 +136,26 @@ impl Plugin for SimulationPlugin {
                 (
                     simulation_tick_system,
                     crate::galaxy::fleet::fleet_preview_gizmos,
+                    history::export_history_system,
+                    stats_export::export_stats_system.after(simulation_tick_system),
                 ),
             )
             .add_plugins(schedule::SchedulePlugin)
-            .add_plugins(mission::planet_launch_colony::PlanetAutoColonyMissionPlugin)
-            .add_event::<colonisation::ColonisePlanetEvent>();
+            .add_plugins(mission::MissionPlugin)
+            .add_plugins(vizier::VizierPlugin)
+            .init_resource::<colonisation::ColonyTargetReservations>()
+            .init_resource::<history::GalaxyHistory>()
+            .insert_resource(
+                stats_export::StatsExportConfig::default()
+                    .with_args(&std::env::args().collect::<Vec<_>>()),
+            )
+            .add_event::<stats_export::ExportStatsEvent>()
+            .add_event::<history::ExportHistoryEvent>()
+            .add_event::<colonisation::ColonisePlanetEvent>()
+            .add_event::<invasion::InvadePlanetEvent>()
+            .add_event::<invasion::ColonyConqueredEvent>()
+            .add_event::<lifecycle::ColonyAbandonedEvent>()
+            .add_event::<lifecycle::SystemReleasedEvent>()
+            .add_event::<lifecycle::EmpireEliminatedEvent>();
     }
 }
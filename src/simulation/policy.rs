@@ -0,0 +1,352 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+use super::SimTime;
+
+// ECONOMIC POLICIES
+// Empire-wide levers over its colonies' economies. Reforms take POLICY_TRANSITION days to come into force,
+// and only one can be under way at a time, so policies can't be flipped back and forth freely
+
+const POLICY_TRANSITION: i64 = 180;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvestmentPriority {
+    Balanced,
+    Light,
+    Heavy,
+    Advanced,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PolicyLevel {
+    Low,
+    Standard,
+    High,
+}
+
+impl PolicyLevel {
+    fn pick<T>(self, low: T, standard: T, high: T) -> T {
+        match self {
+            Self::Low => low,
+            Self::Standard => standard,
+            Self::High => high,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        self.pick(Self::Standard, Self::High, Self::Low)
+    }
+}
+
+impl InvestmentPriority {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Balanced => Self::Light,
+            Self::Light => Self::Heavy,
+            Self::Heavy => Self::Advanced,
+            Self::Advanced => Self::Balanced,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PolicyKind {
+    Investment,
+    Education,
+    Taxation,
+    Conscription,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PolicyChange {
+    Investment(InvestmentPriority),
+    Education(PolicyLevel),
+    Taxation(PolicyLevel),
+    Conscription(PolicyLevel),
+}
+
+impl PolicyChange {
+    pub fn kind(&self) -> PolicyKind {
+        match self {
+            Self::Investment(_) => PolicyKind::Investment,
+            Self::Education(_) => PolicyKind::Education,
+            Self::Taxation(_) => PolicyKind::Taxation,
+            Self::Conscription(_) => PolicyKind::Conscription,
+        }
+    }
+
+    // The next option for the same policy, for cycling through them in the interface
+    pub fn next(self) -> Self {
+        match self {
+            Self::Investment(x) => Self::Investment(x.next()),
+            Self::Education(x) => Self::Education(x.next()),
+            Self::Taxation(x) => Self::Taxation(x.next()),
+            Self::Conscription(x) => Self::Conscription(x.next()),
+        }
+    }
+
+    // Message id for the name of the option on its own
+    pub fn option_message(&self) -> &'static str {
+        match self {
+            Self::Investment(x) => match x {
+                InvestmentPriority::Balanced => "policy-priority-balanced",
+                InvestmentPriority::Light => "policy-priority-light",
+                InvestmentPriority::Heavy => "policy-priority-heavy",
+                InvestmentPriority::Advanced => "policy-priority-advanced",
+            },
+            Self::Education(x) | Self::Taxation(x) | Self::Conscription(x) => x.pick(
+                "policy-level-low",
+                "policy-level-standard",
+                "policy-level-high",
+            ),
+        }
+    }
+}
+
+impl PolicyKind {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Investment => "policy-investment",
+            Self::Education => "policy-education",
+            Self::Taxation => "policy-taxation",
+            Self::Conscription => "policy-conscription",
+        }
+    }
+}
+
+// What the policies do to each colony's economy
+#[derive(Clone, Copy)]
+pub struct PolicyEffects {
+    pub investment: InvestmentPriority,
+    pub training_rate: IPercent,  // When education is fully funded
+    pub education_cost: IPercent, // Share of profits needed to fund education
+    pub tax_rate: IPercent,       // Share of profits taken in taxes, which pay for education first
+    pub conscription: IPercent,   // Share of the population kept under arms, and out of work
+    pub troops: IPercent,         // Share of the population raised for each invasion
+}
+
+impl Default for PolicyEffects {
+    fn default() -> Self {
+        EconomicPolicies::default().effects()
+    }
+}
+
+#[derive(Component)]
+pub struct EconomicPolicies {
+    pub investment: InvestmentPriority,
+    pub education: PolicyLevel,
+    pub taxation: PolicyLevel,
+    pub conscription: PolicyLevel,
+    reform: Option<(PolicyChange, i64)>, // Days left until it comes into force
+}
+
+impl Default for EconomicPolicies {
+    fn default() -> Self {
+        Self {
+            investment: InvestmentPriority::Balanced,
+            education: PolicyLevel::Standard,
+            taxation: PolicyLevel::Standard,
+            conscription: PolicyLevel::Standard,
+            reform: None,
+        }
+    }
+}
+
+impl EconomicPolicies {
+    pub fn effects(&self) -> PolicyEffects {
+        PolicyEffects {
+            investment: self.investment,
+            training_rate: IPercent::new(self.education.pick(100, 150, 250)),
+            education_cost: IPercent::new(self.education.pick(0, 100, 250)),
+            tax_rate: IPercent::new(self.taxation.pick(50, 150, 300)),
+            conscription: IPercent::new(self.conscription.pick(0, 10, 30)),
+            troops: IPercent::new(self.conscription.pick(2, 5, 15)),
+        }
+    }
+
+    pub fn reform(&self) -> Option<(PolicyChange, i64)> {
+        self.reform
+    }
+
+    // The policy of that kind in force
+    pub fn current(&self, kind: PolicyKind) -> PolicyChange {
+        match kind {
+            PolicyKind::Investment => PolicyChange::Investment(self.investment),
+            PolicyKind::Education => PolicyChange::Education(self.education),
+            PolicyKind::Taxation => PolicyChange::Taxation(self.taxation),
+            PolicyKind::Conscription => PolicyChange::Conscription(self.conscription),
+        }
+    }
+
+    // Starts a reform. Refused while another one is under way, or if nothing would change
+    pub fn request(&mut self, change: PolicyChange) -> bool {
+        if self.reform.is_some() || self.current(change.kind()) == change {
+            return false;
+        }
+        self.reform = Some((change, POLICY_TRANSITION));
+        true
+    }
+
+    fn apply(&mut self, change: PolicyChange) {
+        match change {
+            PolicyChange::Investment(x) => self.investment = x,
+            PolicyChange::Education(x) => self.education = x,
+            PolicyChange::Taxation(x) => self.taxation = x,
+            PolicyChange::Conscription(x) => self.conscription = x,
+        }
+    }
+}
+
+// Reforms come into force here. Colonies get their empire's policies monthly, or as soon as a reform goes through
+pub fn update_policies_system(
+    mut empire_query: Query<(Entity, &mut EconomicPolicies)>,
+    mut colony_query: Query<&mut Colony>,
+    sim_time: Res<SimTime>,
+) {
+    let mut reformed = Vec::new();
+    for (empire, mut policies) in empire_query.iter_mut() {
+        let Some((change, days)) = policies.reform else {
+            continue;
+        };
+        if days <= 1 {
+            policies.apply(change);
+            policies.reform = None;
+            reformed.push(empire);
+        } else {
+            policies.reform = Some((change, days - 1));
+        }
+    }
+
+    if !sim_time.is_new_month() && reformed.is_empty() {
+        return;
+    }
+    for mut colony in colony_query.iter_mut() {
+        if !sim_time.is_new_month() && !reformed.contains(&colony.owner) {
+            continue;
+        }
+        if let Ok((_, policies)) = empire_query.get(colony.owner) {
+            colony.economy.set_policy(policies.effects());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::time::tick_date_system;
+
+    fn stat(economy: &Economy, name: &str) -> i64 {
+        economy.stats().iter().find(|(x, _)| *x == name).unwrap().1
+    }
+
+    fn schedule() -> Schedule {
+        let mut schedule = Schedule::default();
+        schedule.add_systems((update_policies_system, tick_date_system).chain());
+        schedule
+    }
+
+    #[test]
+    fn reforms_take_time_and_go_one_at_a_time() {
+        let mut world = World::new();
+        world.insert_resource(SimTime::new());
+        let empire = world.spawn(EconomicPolicies::default()).id();
+        let mut policies = world.get_mut::<EconomicPolicies>(empire).unwrap();
+        assert!(!policies.request(PolicyChange::Taxation(PolicyLevel::Standard)));
+        assert!(policies.request(PolicyChange::Taxation(PolicyLevel::High)));
+        assert!(!policies.request(PolicyChange::Education(PolicyLevel::High)));
+
+        let mut schedule = schedule();
+        for _ in 1..POLICY_TRANSITION {
+            schedule.run(&mut world);
+        }
+        let policies = world.get::<EconomicPolicies>(empire).unwrap();
+        assert_eq!(policies.taxation, PolicyLevel::Standard);
+        assert_eq!(
+            policies.reform(),
+            Some((PolicyChange::Taxation(PolicyLevel::High), 1))
+        );
+
+        schedule.run(&mut world);
+        let mut policies = world.get_mut::<EconomicPolicies>(empire).unwrap();
+        assert_eq!(policies.taxation, PolicyLevel::High);
+        assert!(policies.reform().is_none());
+        assert!(policies.request(PolicyChange::Education(PolicyLevel::High)));
+    }
+
+    fn colony(world: &mut World, owner: Entity) -> Entity {
+        world
+            .spawn(Colony {
+                owner,
+                claimed_tick: 0,
+                population: Population::new(1_000_000),
+                economy: Economy::new(),
+            })
+            .id()
+    }
+
+    // Workers left once the colony's economy runs under the policies it was last given
+    fn workers(world: &mut World, colony: Entity) -> i64 {
+        let mut colony = world.get_mut::<Colony>(colony).unwrap();
+        colony.economy.update_dynamic_params(1_000_000);
+        stat(&colony.economy, "basic_workers")
+    }
+
+    // Colonies hear of policies at the start of each month, or straight away when their own empire reforms
+    #[test]
+    fn colonies_follow_their_empires_policies() {
+        let mut world = World::new();
+        world.insert_resource(SimTime::new());
+        let reforming = world.spawn(EconomicPolicies::default()).id();
+        let drafted = colony(&mut world, reforming);
+        world
+            .get_mut::<EconomicPolicies>(reforming)
+            .unwrap()
+            .request(PolicyChange::Conscription(PolicyLevel::Low));
+        let mut schedule = schedule();
+        schedule.run(&mut world);
+        // Standard conscription keeps 1% under arms
+        assert_eq!(workers(&mut world, drafted), 990_000);
+
+        for _ in 2..POLICY_TRANSITION {
+            schedule.run(&mut world);
+        }
+        let settled = world
+            .spawn(EconomicPolicies {
+                conscription: PolicyLevel::High,
+                ..default()
+            })
+            .id();
+        let waiting = colony(&mut world, settled);
+        schedule.run(&mut world);
+        // Mid-month, only the reformed empire's colonies are updated
+        assert_eq!(workers(&mut world, drafted), 1_000_000);
+        assert_eq!(workers(&mut world, waiting), 990_000);
+
+        while !world.resource::<SimTime>().is_new_month() {
+            schedule.run(&mut world);
+        }
+        schedule.run(&mut world);
+        assert_eq!(workers(&mut world, waiting), 970_000);
+    }
+
+    // Education trains engineers faster, as long as taxes pay for it
+    #[test]
+    fn education_needs_funding() {
+        let training = |education, taxation| {
+            let policies = EconomicPolicies {
+                education,
+                taxation,
+                ..default()
+            };
+            let mut economy = Economy::new();
+            economy.set_policy(policies.effects());
+            economy.update_dynamic_params(1_000_000);
+            economy.update_dynamic_params(1_000_000);
+            stat(&economy, "training_rate")
+        };
+        assert_eq!(training(PolicyLevel::Low, PolicyLevel::Low), 100);
+        assert_eq!(training(PolicyLevel::High, PolicyLevel::High), 250);
+        let underfunded = training(PolicyLevel::High, PolicyLevel::Low);
+        assert!(underfunded > 100 && underfunded < 250);
+    }
+}
@@ -2,9 +2,17 @@ use bevy::prelude::*;
 
 use bevy::ecs::schedule::ScheduleLabel;
 
-use super::economy::demography_system;
-use super::fleet_behaviour::{colonisation, navigation};
+use super::diplomacy;
+use super::economy::{demography_system, market};
+use super::fleet_behaviour::{colonisation, invasion, navigation, station};
+use super::gateways;
+use super::history;
+use super::influence;
+use super::lifecycle;
 use super::orbits;
+use super::policy;
+use super::supply;
+use super::threat;
 use super::time;
 
 #[derive(ScheduleLabel, Debug, Hash, PartialEq, Eq, Clone)]
@@ -30,19 +38,51 @@ impl Plugin for SchedulePlugin {
         simulation_schedule.add_systems((
             time::tick_date_system,
             orbits::update_orbiters,
-            demography_system::update_population,
+            (
+                policy::update_policies_system,
+                demography_system::update_population,
+                market::update_markets_system,
+                history::record_history_system,
+            )
+                .chain(),
+            gateways::construct_gateways_system,
+            supply::construct_supply_depots_system,
+            influence::update_influence_system,
+            diplomacy::update_wars_system,
+            invasion::launch_invasions_system,
             (
                 navigation::navigation_update_nav_system,
+                navigation::hazardous_lane_attrition_system,
+                supply::update_fleet_readiness_system,
                 colonisation::nav_find_colony_target_system,
+                colonisation::reserve_colony_targets_system,
                 colonisation::nav_update_task_system,
                 colonisation::process_colonise_events,
+                invasion::nav_update_invasion_task_system,
+                invasion::process_invasion_events,
+                station::nav_update_station_task_system,
+            )
+                .chain(),
+            (
+                lifecycle::abandon_colonies_system,
+                lifecycle::eliminate_empires_system,
+                lifecycle::player_eliminated_system,
             )
                 .chain(),
         ));
 
         let mut pre_tick_schedule = Schedule::new(SimPreTick);
-        pre_tick_schedule
-            .add_systems(crate::galaxy::navigation_filter::update_empire_navigation_masks);
+        pre_tick_schedule.add_systems(
+            (
+                crate::galaxy::navigation_filter::update_empire_navigation_masks,
+                crate::galaxy::navigation_cost::update_navigation_danger_system,
+                supply::update_supply_system,
+                threat::update_threat_maps_system,
+                crate::galaxy::hypernet_hierarchy::rebuild_hypernet_clusters_system,
+                crate::galaxy::path_cache::invalidate_path_cache_system,
+            )
+                .chain(),
+        );
 
         let post_tick_schedule = Schedule::new(SimPostTick);
         let sim_start_schedule = Schedule::new(SimStart);
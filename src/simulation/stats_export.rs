@@ -0,0 +1,366 @@
+use crate::galaxy::fleet::{FleetColonyCrew, FleetTroops};
+use crate::galaxy::Description;
+use crate::prelude::*;
+use bevy::prelude::*;
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use super::SimTime;
+
+// STATS EXPORT
+// Dumps the state of every empire and colony for balance analysis, either every few days or on demand (F10)
+// Each kind of record is appended to its own CSV file, and all of them to one JSON Lines file
+// File names start with a random id for the run, so runs sharing a directory don't mix
+//   --stats-interval <days>
+//   --stats-dir <directory> (default "stats")
+
+#[derive(Resource)]
+pub struct StatsExportConfig {
+    pub interval: Option<i64>, // Days between dumps
+    pub dir: String,
+    pub run: String, // Also names the history export
+}
+
+impl Default for StatsExportConfig {
+    fn default() -> Self {
+        Self {
+            interval: None,
+            dir: "stats".to_string(),
+            run: format!("{:08x}", rand::random::<u32>()),
+        }
+    }
+}
+
+impl StatsExportConfig {
+    pub fn with_args(mut self, args: &[String]) -> Self {
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--stats-interval" => {
+                    self.interval = iter
+                        .next()
+                        .and_then(|x| x.parse::<i64>().ok())
+                        .filter(|x| *x > 0);
+                    if self.interval.is_none() {
+                        warn!("--stats-interval: expected a number of days");
+                    }
+                }
+                "--stats-dir" => {
+                    if let Some(dir) = iter.next() {
+                        self.dir = dir.clone();
+                    }
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+}
+
+#[derive(Event)]
+pub struct ExportStatsEvent;
+
+enum Value {
+    Int(i64),
+    Text(String),
+}
+
+struct Record {
+    kind: &'static str,
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl Record {
+    fn csv_header(&self) -> String {
+        self.fields
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn csv_row(&self) -> String {
+        self.fields
+            .iter()
+            .map(|(_, value)| match value {
+                Value::Int(x) => x.to_string(),
+                Value::Text(x) if x.contains([',', '"']) => {
+                    format!("\"{}\"", x.replace('"', "\"\""))
+                }
+                Value::Text(x) => x.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn json(&self) -> String {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, value)| match value {
+                Value::Int(x) => format!("\"{}\":{}", name, x),
+                Value::Text(x) => format!("\"{}\":{}", name, json_string(x)),
+            })
+            .collect::<Vec<_>>();
+        format!("{{\"kind\":\"{}\",{}}}", self.kind, fields.join(","))
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn write_records(dir: &str, run: &str, records: &[Record]) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let open = |name: String| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(format!("{}/{}-{}", dir, run, name))
+    };
+
+    let mut jsonl = open("stats.jsonl".to_string())?;
+    for record in records {
+        writeln!(jsonl, "{}", record.json())?;
+    }
+
+    let mut kinds = records.iter().map(|x| x.kind).collect::<Vec<_>>();
+    kinds.dedup();
+    for kind in kinds {
+        let mut csv = open(format!("{}s.csv", kind))?;
+        let mut rows = records.iter().filter(|x| x.kind == kind).peekable();
+        if csv.metadata()?.len() == 0 {
+            if let Some(first) = rows.peek() {
+                writeln!(csv, "{}", first.csv_header())?;
+            }
+        }
+        for record in rows {
+            writeln!(csv, "{}", record.csv_row())?;
+        }
+    }
+    Ok(())
+}
+
+// Runs after the tick, so a dump is the state at the end of it
+#[allow(clippy::too_many_arguments)]
+pub fn export_stats_system(
+    mut ev_export: EventReader<ExportStatsEvent>,
+    empire_query: Query<(Entity, &Empire, &EmpireIndex)>,
+    colony_query: Query<(&Colony, &Description)>,
+    fleet_query: Query<(&Fleet, Has<FleetColonyCrew>, Has<FleetTroops>)>,
+    config: Res<StatsExportConfig>,
+    sim_settings: Res<SimulationSettings>,
+    sim_time: Res<SimTime>,
+    mut last_export: Local<Option<i64>>,
+) {
+    let tick = sim_settings.current_tick;
+    let requested = ev_export.read().count() > 0;
+    let scheduled = config
+        .interval
+        .is_some_and(|x| tick % x == 0 && *last_export != Some(tick));
+    if !requested && !scheduled {
+        return;
+    }
+    *last_export = Some(tick);
+
+    let (day, month, year) = sim_time.to_daymonthyear();
+    let record = |kind: &'static str| Record {
+        kind,
+        fields: vec![
+            ("tick", Value::Int(tick)),
+            ("year", Value::Int(year as i64)),
+            ("month", Value::Int(month as i64)),
+            ("day", Value::Int(day as i64)),
+        ],
+    };
+    let empire_name = |x: Entity| {
+        empire_query
+            .get(x)
+            .map(|(_, empire, _)| empire.name.clone())
+            .unwrap_or_default()
+    };
+
+    let mut records = Vec::new();
+    for (entity, empire, index) in &empire_query {
+        let fleets = fleet_query.iter().filter(|(x, ..)| x.owner == entity);
+        let (mut colony_ships, mut troop_ships, mut other) = (0, 0, 0);
+        for (_, colony_crew, troops) in fleets {
+            match (colony_crew, troops) {
+                (true, _) => colony_ships += 1,
+                (_, true) => troop_ships += 1,
+                _ => other += 1,
+            }
+        }
+
+        let mut x = record("empire");
+        x.fields.extend([
+            ("empire", Value::Text(empire.name.clone())),
+            ("population", Value::Int(index.population)),
+            ("colonies", Value::Int(index.colonies.len() as i64)),
+            ("systems", Value::Int(index.systems.len() as i64)),
+            ("colony_ships", Value::Int(colony_ships)),
+            ("troop_ships", Value::Int(troop_ships)),
+            ("other_fleets", Value::Int(other)),
+        ]);
+        records.push(x);
+    }
+
+    for (colony, description) in &colony_query {
+        let mut x = record("colony");
+        x.fields.extend([
+            ("planet", Value::Text(description.name.clone())),
+            ("owner", Value::Text(empire_name(colony.owner))),
+            ("population", Value::Int(colony.population.val())),
+        ]);
+        x.fields.extend(
+            colony
+                .economy
+                .stats()
+                .into_iter()
+                .map(|(name, value)| (name, Value::Int(value))),
+        );
+        records.push(x);
+    }
+
+    match write_records(&config.dir, &config.run, &records) {
+        Ok(()) => info!(
+            "Stats for tick {} written to {}/{}-*",
+            tick, config.dir, config.run
+        ),
+        Err(e) => warn!("Stats export failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::galaxy::fleet::FleetBundle;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_args() {
+        let config = StatsExportConfig::default()
+            .with_args(&args("game --stats-interval 30 --stats-dir out"));
+        assert_eq!(config.interval, Some(30));
+        assert_eq!(config.dir, "out");
+        for bad in [
+            "--stats-interval 0",
+            "--stats-interval soon",
+            "--stats-interval",
+        ] {
+            let config = StatsExportConfig::default().with_args(&args(bad));
+            assert_eq!(config.interval, None);
+        }
+        // Each run gets its own files
+        assert_ne!(
+            StatsExportConfig::default().run,
+            StatsExportConfig::default().run
+        );
+    }
+
+    #[test]
+    fn escapes_text() {
+        let record = Record {
+            kind: "colony",
+            fields: vec![
+                ("planet", Value::Text("Vega \"Prime\", b".to_string())),
+                ("owner", Value::Text("Tab\there\\".to_string())),
+                ("population", Value::Int(-5)),
+            ],
+        };
+        assert_eq!(record.csv_header(), "planet,owner,population");
+        assert_eq!(record.csv_row(), "\"Vega \"\"Prime\"\", b\",Tab\there\\,-5");
+        assert_eq!(
+            record.json(),
+            r#"{"kind":"colony","planet":"Vega \"Prime\", b","owner":"Tab\u0009here\\","population":-5}"#
+        );
+    }
+
+    fn read(dir: &std::path::Path, run: &str, name: &str) -> String {
+        std::fs::read_to_string(dir.join(format!("{}-{}", run, name))).unwrap()
+    }
+
+    // Dumps every `interval` ticks or on request, appending to the run's files with a single header each
+    #[test]
+    fn appends_scheduled_and_requested_dumps() {
+        let dir = std::env::temp_dir().join(format!("stats-test-{}", std::process::id()));
+        let config = StatsExportConfig {
+            interval: Some(10),
+            dir: dir.to_string_lossy().into_owned(),
+            ..default()
+        };
+        let run = config.run.clone();
+
+        let mut world = World::new();
+        world.insert_resource(config);
+        world.insert_resource(SimTime::new());
+        world.insert_resource(SimulationSettings::at_tick(0));
+        world.init_resource::<Events<ExportStatsEvent>>();
+        let empire = world
+            .spawn((
+                Empire::named("Vega, Prime"),
+                EmpireIndex {
+                    population: 1000,
+                    ..default()
+                },
+            ))
+            .id();
+        world.spawn((
+            Colony {
+                owner: empire,
+                claimed_tick: 0,
+                population: Population::new(1000),
+                economy: Economy::new(),
+            },
+            Description::planet("Andromeda".to_string()),
+        ));
+        world.spawn(FleetBundle::new(empire, Vec3::ZERO, 0));
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(export_stats_system);
+        for tick in 0..25 {
+            world.resource_mut::<SimulationSettings>().current_tick = tick;
+            if tick == 13 {
+                world.send_event(ExportStatsEvent);
+            }
+            schedule.run(&mut world);
+        }
+        // Runs twice in a tick while paused, without dumping twice
+        schedule.run(&mut world);
+
+        let empires = read(&dir, &run, "empires.csv");
+        let lines = empires.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "tick,year,month,day,empire,population,colonies,systems,colony_ships,troop_ships,other_fleets");
+        assert_eq!(lines[1], "0,0,1,0,\"Vega, Prime\",1000,0,0,0,0,1");
+        let ticks = lines[1..]
+            .iter()
+            .map(|x| x.split(',').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ticks, ["0", "10", "13", "20"]);
+
+        let colonies = read(&dir, &run, "colonys.csv");
+        assert_eq!(colonies.lines().count(), 5);
+        assert!(colonies
+            .lines()
+            .nth(1)
+            .unwrap()
+            .contains(",Andromeda,\"Vega, Prime\",1000,"));
+        assert_eq!(read(&dir, &run, "stats.jsonl").lines().count(), 8);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
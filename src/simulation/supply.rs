@@ -0,0 +1,251 @@
+use crate::galaxy::fleet::FleetSupply;
+use crate::prelude::*;
+use crate::simulation::fleet_behaviour::navigation::NavPosition;
+use crate::simulation::vizier::AiEmpire;
+use bevy::prelude::*;
+
+use super::SimTime;
+
+// Supply ranges are in lane traversal cost, ie. galactic integer units at standard lane speed
+const COLONY_SUPPLY_RANGE: i32 = 100 * GalaxyConfig::GALACTIC_INTEGER_SCALE; // 100 parsecs
+const DEPOT_SUPPLY_RANGE: i32 = 200 * GalaxyConfig::GALACTIC_INTEGER_SCALE;
+const MAX_SUPPLY_RANGE: i32 = DEPOT_SUPPLY_RANGE;
+
+// Readiness change per tick, on the 0-1000 scale
+const READINESS_RECOVERY: i32 = 5;
+const READINESS_DECAY: i32 = 1;
+
+// Placed on a star. Extends the owner's supply network further than a colony does
+#[derive(Component)]
+pub struct SupplyDepot {
+    pub owner: Entity,
+}
+
+// Per empire. Remaining supply range at each star, 0 if it's outside the network
+#[derive(Component, Default)]
+pub struct SupplyNetwork {
+    range: Vec<i32>,
+    mask_version: Option<u32>,
+}
+
+impl SupplyNetwork {
+    pub fn is_supplied(&self, star: u32) -> bool {
+        self.range.get(star as usize).is_some_and(|x| *x > 0)
+    }
+
+    // How deep inside the network a star is, shown by the supply overlay
+    pub fn coverage(&self, star: u32) -> IPercent {
+        let range = self.range.get(star as usize).copied().unwrap_or(0);
+        IPercent::new((range as i64 * 1000 / MAX_SUPPLY_RANGE as i64) as i32)
+    }
+}
+
+// Supply propagates along lanes the empire can use, from every populated system it owns and every depot it has
+// Recomputed monthly (supply doesn't need to be exact), or straight away when the empire's territory changes
+pub fn update_supply_system(
+    mut empire_query: Query<(Entity, &mut SupplyNetwork, &mut NavigationMask), With<Empire>>,
+    system_query: Query<&SystemIndex>,
+    depot_query: Query<(&Star, &SupplyDepot)>,
+    sim_time: Res<SimTime>,
+    hypernet: Res<Hypernet>,
+) {
+    for (empire, mut network, mut mask) in empire_query.iter_mut() {
+        if !sim_time.is_new_month() && network.mask_version == Some(mask.version()) {
+            continue;
+        }
+
+        // Every source starts at MAX_SUPPLY_RANGE - its own range, so a single capped search covers them all
+        let mut sources = mask
+            .owned_systems
+            .iter()
+            .filter(|x| {
+                system_query
+                    .get(hypernet.star(**x).entity)
+                    .is_ok_and(|index| index.population > 0)
+            })
+            .map(|x| (*x, MAX_SUPPLY_RANGE - COLONY_SUPPLY_RANGE))
+            .collect::<Vec<_>>();
+        sources.extend(
+            depot_query
+                .iter()
+                .filter(|(star, depot)| {
+                    depot.owner == empire && mask.access(star.node_id) == SystemAccess::Owned
+                })
+                .map(|(star, _)| (star.node_id, MAX_SUPPLY_RANGE - DEPOT_SUPPLY_RANGE)),
+        );
+
+        let distances = mask
+            .to_filter(&hypernet)
+            .dijkstra_within(&sources, MAX_SUPPLY_RANGE);
+        network.range = distances
+            .iter()
+            .map(|x| x.map(|d| (MAX_SUPPLY_RANGE - d).max(1)).unwrap_or(0))
+            .collect();
+
+        let supplied = (0..network.range.len() as u32)
+            .map(|x| network.is_supplied(x))
+            .collect::<Vec<_>>();
+        mask.set_supplied(&supplied);
+        network.mask_version = Some(mask.version());
+    }
+}
+
+pub fn update_fleet_readiness_system(
+    mut fleet_query: Query<(&NavPosition, &Fleet, &mut FleetSupply)>,
+    network_query: Query<&SupplyNetwork>,
+) {
+    for (nav_pos, fleet, mut supply) in fleet_query.iter_mut() {
+        let supplied = network_query
+            .get(fleet.owner)
+            .is_ok_and(|x| x.is_supplied(nav_pos.root_system));
+        supply.readiness = if supplied {
+            (supply.readiness + READINESS_RECOVERY).min(FleetSupply::MAX_READINESS)
+        } else {
+            (supply.readiness - READINESS_DECAY).max(0)
+        };
+    }
+}
+
+// Once a year, each empire builds a depot in the owned system on the thinnest edge of its network
+// AI empires decide where their depots go themselves
+pub fn construct_supply_depots_system(
+    empire_query: Query<(Entity, &SupplyNetwork, &NavigationMask), Without<AiEmpire>>,
+    depot_query: Query<(), With<SupplyDepot>>,
+    sim_time: Res<SimTime>,
+    hypernet: Res<Hypernet>,
+    mut commands: Commands,
+) {
+    if !sim_time.is_new_year() {
+        return;
+    }
+
+    for (empire, network, mask) in &empire_query {
+        let candidate = mask
+            .owned_systems
+            .iter()
+            .filter(|x| !depot_query.contains(hypernet.star(**x).entity))
+            .map(|x| {
+                let shortfall: i32 = hypernet
+                    .graph
+                    .neighbors((*x).into())
+                    .map(|n| 1000 - network.coverage(n.index() as u32).value())
+                    .sum();
+                (*x, shortfall)
+            })
+            .max_by_key(|(_, shortfall)| *shortfall);
+
+        if let Some((system, _)) = candidate {
+            commands
+                .entity(hypernet.star(system).entity)
+                .insert(SupplyDepot { owner: empire });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::galaxy::navigation_filter::update_empire_navigation_masks;
+    use crate::simulation::fleet_behaviour::navigation::NavOffset;
+
+    // Six systems in a row, 40 parsecs apart
+    fn setup() -> (World, Entity, Vec<Entity>) {
+        let positions = (0..6).map(|x| (x as f32 * 40.0, 0.0)).collect::<Vec<_>>();
+        let lanes = (0..5).map(|x| (x, x + 1)).collect::<Vec<_>>();
+        let mut hypernet = Hypernet::from_lanes(&positions, &lanes);
+        let mut world = World::new();
+        let stars = hypernet.spawn_stars(&mut world);
+        let empire = world
+            .spawn((
+                Empire::named("Us"),
+                Diplomacy::default(),
+                NavigationMask::new(&hypernet, true),
+                SupplyNetwork::default(),
+            ))
+            .id();
+        world.insert_resource(hypernet);
+        world.insert_resource(SimTime::new());
+        (world, empire, stars)
+    }
+
+    fn supplied(world: &mut World, empire: Entity) -> Vec<bool> {
+        let mut schedule = Schedule::default();
+        schedule.add_systems((update_empire_navigation_masks, update_supply_system).chain());
+        schedule.run(world);
+        let network = world.get::<SupplyNetwork>(empire).unwrap();
+        (0..6).map(|x| network.is_supplied(x)).collect()
+    }
+
+    fn claim(world: &mut World, star: Entity, empire: Entity, population: i64) {
+        world.get_mut::<StarClaim>(star).unwrap().owner = Some(empire);
+        world.get_mut::<SystemIndex>(star).unwrap().population = population;
+    }
+
+    #[test]
+    fn colonies_and_depots_project_supply() {
+        let (mut world, empire, stars) = setup();
+        assert_eq!(supplied(&mut world, empire), [false; 6]);
+
+        // 100 parsecs from a populated system
+        claim(&mut world, stars[0], empire, 1000);
+        assert_eq!(
+            supplied(&mut world, empire),
+            [true, true, true, false, false, false]
+        );
+        let network = world.get::<SupplyNetwork>(empire).unwrap();
+        assert_eq!(network.coverage(0), IPercent::new(500));
+        assert!(network.coverage(2) < network.coverage(1));
+
+        // Empty systems don't supply anything, and depots only work in our own systems
+        claim(&mut world, stars[4], empire, 0);
+        world
+            .entity_mut(stars[5])
+            .insert(SupplyDepot { owner: empire });
+        assert_eq!(
+            supplied(&mut world, empire),
+            [true, true, true, false, false, false]
+        );
+
+        // 200 parsecs from a depot
+        claim(&mut world, stars[5], empire, 0);
+        assert_eq!(supplied(&mut world, empire), [true; 6]);
+        let network = world.get::<SupplyNetwork>(empire).unwrap();
+        assert_eq!(network.coverage(5), IPercent::FULL);
+    }
+
+    #[test]
+    fn fleets_recover_inside_the_network_and_wear_down_outside() {
+        let (mut world, empire, stars) = setup();
+        claim(&mut world, stars[0], empire, 1000);
+        supplied(&mut world, empire);
+
+        let mut spawn_fleet = |system| {
+            world
+                .spawn((
+                    NavPosition {
+                        root_system: system,
+                        offset: NavOffset::Star(Vec3::ZERO),
+                    },
+                    Fleet {
+                        owner: empire,
+                        name: String::new(),
+                        time_since_last_jump: 0,
+                    },
+                    FleetSupply { readiness: 500 },
+                ))
+                .id()
+        };
+        let (inside, outside) = (spawn_fleet(1), spawn_fleet(4));
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(update_fleet_readiness_system);
+        for _ in 0..200 {
+            schedule.run(&mut world);
+        }
+        assert_eq!(
+            world.get::<FleetSupply>(inside).unwrap().readiness,
+            FleetSupply::MAX_READINESS
+        );
+        assert_eq!(world.get::<FleetSupply>(outside).unwrap().readiness, 300);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::galaxy::fleet::{FleetStation, FleetTroops, StationDuty};
+use crate::prelude::*;
+use crate::simulation::fleet_behaviour::navigation::NavPosition;
+use crate::simulation::supply::SupplyDepot;
+use crate::util::graph::{self, SearchGraph};
+use bevy::prelude::*;
+use petgraph::visit::EdgeRef;
+
+use super::SimTime;
+
+// THREAT MAPS
+// Each attacker threatens a system based on its power, its intent, and its "distance" from its bases to the system
+// Treated as a pathfinding problem: every colony and troop fleet of the attacker is a source, stronger ones start closer,
+// and the defender's colonies and depots make lanes into its systems more expensive to push through
+
+// Lane traversal cost at which an attacker stops being a threat at all
+const THREAT_RANGE: i32 = 300 * GalaxyConfig::GALACTIC_INTEGER_SCALE; // 300 parsecs
+
+// Extra cost to enter a system the defender holds, per colony and per depot
+const COLONY_DEFENCE: i32 = 30 * GalaxyConfig::GALACTIC_INTEGER_SCALE;
+const DEPOT_DEFENCE: i32 = 60 * GalaxyConfig::GALACTIC_INTEGER_SCALE;
+const PATROL_DEFENCE: i32 = 40 * GalaxyConfig::GALACTIC_INTEGER_SCALE; // Per patrol in the system it guards
+
+// How likely the attacker is to act on its power
+const INTENT_AT_WAR: IPercent = IPercent::new(1000);
+const INTENT_CONTESTING: IPercent = IPercent::new(500); // Its influence outweighs ours somewhere
+const INTENT_PEACE: IPercent = IPercent::new(150);
+
+// Per empire. Threat from every other empire at each star, 0 to 1000
+#[derive(Component, Default)]
+pub struct ThreatMap {
+    threat: Vec<i32>,
+}
+
+impl ThreatMap {
+    pub fn threat(&self, star: u32) -> IPercent {
+        IPercent::new(self.threat.get(star as usize).copied().unwrap_or(0))
+    }
+
+    // 100% for a system with no threats around it, never below 10%
+    pub fn security(&self, star: u32) -> IPercent {
+        IPercent::new((1000 - self.threat(star).value()).max(100))
+    }
+}
+
+// The hypernet as seen by an attacker, with the defender's forces added to the cost of entering its systems
+struct ThreatGraph<'a> {
+    hypernet: &'a Hypernet,
+    attacker: Entity,
+    defence: &'a [i32],
+}
+
+impl SearchGraph for ThreatGraph<'_> {
+    fn node_capacity(&self) -> usize {
+        self.hypernet.graph.capacity().0
+    }
+
+    fn for_each_neighbour(&self, node: u32, mut visit: impl FnMut(u32, u32, i32)) {
+        for lane in self.hypernet.graph.edges(node.into()) {
+            let next = if lane.source().index() as u32 == node {
+                lane.target()
+            } else {
+                lane.source()
+            };
+            let next = next.index() as u32;
+            visit(
+                next,
+                lane.id().index() as u32,
+                lane.weight()
+                    .traversal_cost(Some(self.attacker))
+                    .saturating_add(self.defence[next as usize]),
+            );
+        }
+    }
+
+    // Only used for multi-source searches, so no estimate needed
+    fn heuristic(&self, _node: u32, _dest: u32) -> i32 {
+        0
+    }
+}
+
+// 0 to 1000, by orders of magnitude. A billion people is full strength
+fn strength(amount: i64) -> i32 {
+    (amount.max(1).ilog10() as i32 * 100).min(1000)
+}
+
+// Cost a base starts the threat search at, the strongest ones start right in their system and the weakest out of range
+// In i64, THREAT_RANGE * 1000 doesn't fit in an i32
+fn head_start(strength: i32) -> i32 {
+    (THREAT_RANGE as i64 * (1000 - strength) as i64 / 1000) as i32
+}
+
+// Recomputed monthly, the AI only looks at it on its reviews anyway
+#[allow(clippy::too_many_arguments)]
+pub fn update_threat_maps_system(
+    mut empire_query: Query<(Entity, &mut ThreatMap, &Diplomacy), With<Empire>>,
+    colony_query: Query<(&Planet, &Colony)>,
+    fleet_query: Query<(&Fleet, &NavPosition, &FleetTroops)>,
+    station_query: Query<(&Fleet, &NavPosition, &FleetStation)>,
+    depot_query: Query<(&Star, &SupplyDepot)>,
+    claim_query: Query<&StarClaim>,
+    sim_time: Res<SimTime>,
+    hypernet: Res<Hypernet>,
+) {
+    if !sim_time.is_new_month()
+        && empire_query
+            .iter()
+            .all(|(_, map, _)| !map.threat.is_empty())
+    {
+        return;
+    }
+
+    // (owner, system, strength) of everything that can project power
+    let mut bases = colony_query
+        .iter()
+        .map(|(planet, colony)| {
+            (
+                colony.owner,
+                planet.star_id,
+                strength(colony.population.val()),
+            )
+        })
+        .collect::<Vec<_>>();
+    bases.extend(fleet_query.iter().map(|(fleet, nav_pos, troops)| {
+        (fleet.owner, nav_pos.root_system, strength(troops.troops))
+    }));
+    // (owner, challenger) of every contested system
+    let mut contests = claim_query
+        .iter()
+        .filter_map(|x| Some((x.owner?, x.contested_by?)))
+        .collect::<Vec<_>>();
+    contests.sort();
+    contests.dedup();
+    let empires = empire_query.iter().map(|(x, _, _)| x).collect::<Vec<_>>();
+    let capacity = hypernet.graph.capacity().0;
+
+    empire_query
+        .par_iter_mut()
+        .for_each(|(defender, mut map, diplomacy)| {
+            let mut defence = vec![0; capacity];
+            for (owner, system, _) in &bases {
+                if *owner == defender {
+                    defence[*system as usize] += COLONY_DEFENCE;
+                }
+            }
+            for (star, depot) in &depot_query {
+                if depot.owner == defender {
+                    defence[star.node_id as usize] += DEPOT_DEFENCE;
+                }
+            }
+            for (fleet, nav_pos, station) in &station_query {
+                if fleet.owner == defender
+                    && station.duty == StationDuty::Patrol
+                    && nav_pos.root_system == station.system
+                {
+                    defence[station.system as usize] += PATROL_DEFENCE;
+                }
+            }
+
+            let mut threat = vec![0; capacity];
+            for attacker in empires.iter().copied().filter(|x| *x != defender) {
+                let sources = bases
+                    .iter()
+                    .filter(|(owner, _, _)| *owner == attacker)
+                    .map(|(_, system, strength)| (*system, head_start(*strength)))
+                    .collect::<Vec<_>>();
+                if sources.is_empty() {
+                    continue;
+                }
+
+                let intent = if diplomacy.at_war.contains(&attacker) {
+                    INTENT_AT_WAR
+                } else if contests.contains(&(defender, attacker)) {
+                    INTENT_CONTESTING
+                } else {
+                    INTENT_PEACE
+                };
+
+                let graph = ThreatGraph {
+                    hypernet: &hypernet,
+                    attacker,
+                    defence: &defence,
+                };
+                let distances = graph::dijkstra_within(&graph, &sources, THREAT_RANGE);
+                for (x, distance) in distances.iter().enumerate() {
+                    if let Some(distance) = distance {
+                        let reach = ((THREAT_RANGE - distance) as i64 * 1000 / THREAT_RANGE as i64)
+                            * intent;
+                        threat[x] += reach as i32;
+                    }
+                }
+            }
+            map.threat = threat.into_iter().map(|x| x.min(1000)).collect();
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_by_orders_of_magnitude() {
+        assert_eq!(strength(0), 0);
+        assert_eq!(strength(9), 0);
+        assert_eq!(strength(10), 100);
+        assert_eq!(strength(999_999_999), 800);
+        assert_eq!(strength(1_000_000_000), 900);
+        assert_eq!(strength(i64::MAX), 1000);
+
+        assert_eq!(head_start(1000), 0);
+        assert_eq!(head_start(500), THREAT_RANGE / 2);
+        assert_eq!(head_start(0), THREAT_RANGE);
+    }
+
+    struct Setup {
+        world: World,
+        schedule: Schedule,
+        attacker: Entity,
+        defender: Entity,
+    }
+
+    // Six systems in a row, 40 parsecs apart. A billion people at one end, a hundred at the other
+    fn setup() -> Setup {
+        let positions = (0..6).map(|x| (x as f32 * 40.0, 0.0)).collect::<Vec<_>>();
+        let lanes = (0..5).map(|x| (x, x + 1)).collect::<Vec<_>>();
+        let hypernet = Hypernet::from_lanes(&positions, &lanes);
+        let mut world = World::new();
+        let mut spawn_empire = |name| {
+            world
+                .spawn((
+                    Empire::named(name),
+                    Diplomacy::default(),
+                    ThreatMap::default(),
+                ))
+                .id()
+        };
+        let (attacker, defender) = (spawn_empire("Attacker"), spawn_empire("Defender"));
+        for (owner, system, population) in [(attacker, 0, 1_000_000_000), (defender, 5, 100)] {
+            world.spawn((
+                Planet::new(Vec3::ZERO, system, 1.0, 360, 0, 0.1, 1.0),
+                Colony {
+                    owner,
+                    claimed_tick: 0,
+                    population: Population::new(population),
+                    economy: Economy::new(),
+                },
+            ));
+        }
+        world.insert_resource(hypernet);
+        world.insert_resource(SimTime::new());
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(update_threat_maps_system);
+        Setup {
+            world,
+            schedule,
+            attacker,
+            defender,
+        }
+    }
+
+    impl Setup {
+        fn threat(&mut self) -> Vec<i32> {
+            self.schedule.run(&mut self.world);
+            let map = self.world.get::<ThreatMap>(self.defender).unwrap();
+            (0..6).map(|x| map.threat(x).value()).collect()
+        }
+    }
+
+    #[test]
+    fn fades_with_distance_and_defences() {
+        let mut setup = setup();
+        let attacker = setup.attacker;
+        setup
+            .world
+            .get_mut::<Diplomacy>(setup.defender)
+            .unwrap()
+            .at_war
+            .push(attacker);
+
+        // Starting 30 parsecs in, out of 300, then 40 per jump and 30 more into the defender's colony
+        let at_war = setup.threat();
+        assert_eq!(at_war, [900, 766, 633, 500, 366, 133]);
+        let map = setup.world.get::<ThreatMap>(setup.defender).unwrap();
+        assert_eq!(map.security(0), IPercent::new(100));
+        assert_eq!(map.security(5), IPercent::new(867));
+        // A hundred people don't project power as far as the attacker's home
+        assert_eq!(
+            setup.world.get::<ThreatMap>(attacker).unwrap().threat(0),
+            IPercent::ZERO
+        );
+
+        setup
+            .world
+            .get_mut::<Diplomacy>(setup.defender)
+            .unwrap()
+            .at_war
+            .clear();
+        let at_peace = setup.threat();
+        assert!((0..6).all(|x| at_peace[x] as i64 == at_war[x] as i64 * INTENT_PEACE));
+    }
+}
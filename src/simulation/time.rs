@@ -1,5 +1,37 @@
+use crate::util::number::Locale;
 use bevy::prelude::*;
 
+// How dates are written out. Years have 12 months of 30 days
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DateFormat {
+    #[default]
+    DayMonthYear, // 03/12/0104
+    MonthDayYear, // 12/03/0104
+    Iso,          // 0104-12-03
+    Long,         // 3 Dec 104
+}
+
+impl DateFormat {
+    pub fn next(self) -> Self {
+        match self {
+            Self::DayMonthYear => Self::MonthDayYear,
+            Self::MonthDayYear => Self::Iso,
+            Self::Iso => Self::Long,
+            Self::Long => Self::DayMonthYear,
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "dmy" => Some(Self::DayMonthYear),
+            "mdy" => Some(Self::MonthDayYear),
+            "iso" => Some(Self::Iso),
+            "long" => Some(Self::Long),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct SimTime {
     raw_date: u32,
@@ -19,6 +51,31 @@ impl SimTime {
             self.raw_date / 360,
         )
     }
+
+    // Days are counted from 1 here, unlike to_daymonthyear
+    pub fn format(&self, format: DateFormat, locale: &Locale) -> String {
+        let (day, month, year) = self.to_daymonthyear();
+        let day = day + 1;
+        match format {
+            DateFormat::DayMonthYear => format!("{:02}/{:02}/{:04}", day, month, year),
+            DateFormat::MonthDayYear => format!("{:02}/{:02}/{:04}", month, day, year),
+            DateFormat::Iso => format!("{:04}-{:02}-{:02}", year, month, day),
+            DateFormat::Long => format!(
+                "{} {} {}",
+                day,
+                locale.month_names[month as usize - 1],
+                year
+            ),
+        }
+    }
+
+    pub fn is_new_month(&self) -> bool {
+        self.raw_date.is_multiple_of(30)
+    }
+
+    pub fn is_new_year(&self) -> bool {
+        self.raw_date.is_multiple_of(360)
+    }
 }
 
 pub fn tick_date_system(mut sim_time: ResMut<SimTime>) {
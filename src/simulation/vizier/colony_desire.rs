@@ -1,34 +1,51 @@
-use bevy::prelude::*;
 use crate::prelude::*;
+use crate::simulation::mission::survey::SurveyedSystems;
+use crate::simulation::threat::ThreatMap;
+use bevy::prelude::*;
 
 use bevy::ecs::entity::EntityHashMap;
 
+use super::{AiEmpire, GoalKind, GOAL_SCALE};
+
 // SIMPLE COLONY DESIRE SCHEME
 
 // COLONY RATING SCORE
 // Steps
-//   Base Rating is 10 000 000
-//   1. Unreachable system rating is set to 0
-//   2. SUBTRACT distance to the Capital
+//   Base Rating is GOAL_SCALE (10 000 000)
+//   1. Unreachable planets are not rated at all
+//   2. SUBTRACT half the distance to the Capital
 //   3. SUBTRACT distance to nearest owned system
 //   4. If there are no owned planets in the system, multiply the score by EXPANSION factor
 //      Else, multiply the score by CONSOLIDATE factor
+//   5. Multiply by security, and add a bonus for chokepoints
+// Planets in systems the empire doesn't know yet (see SurveyedSystems) aren't proposed for colonisation,
+// the best of those systems is proposed for a survey instead, at SURVEY_FACTOR of its score
+
+const EXPANSION_FACTOR: IPercent = IPercent::new(1000);
+const CONSOLIDATE_FACTOR: IPercent = IPercent::new(1200);
+const CHOKEPOINT_BONUS: IPercent = IPercent::new(200);
+const SURVEY_FACTOR: IPercent = IPercent::new(500);
+
+// Only the best few targets are proposed each review
+const COLONY_GOALS: usize = 3;
 
-struct ColonyRating {
-    valid : bool,
-    distance : Option<i32>, // distance from nearest planet that can supply a colony ship
+pub struct ColonyRating {
+    pub distance: i32,         // distance from the nearest owned system
+    pub capital_distance: i32, // distance from the capital
+    pub consolidate: bool,     // the empire already has a colony in the system
 
-    security : Option<f32> // Vulnerability to attack
+    pub security: IPercent, // 100% for a system with no threats around it
     // Treat it as a pathfinding problem -
     // Each attacker threatens a system based on a function of their power, their likelihood to attack, and their "distance" from their core bases to the system
     // Distance: edge weights account for both the actual distance (reduced weight), and your defensive forces in the system. Defensive forces of your own allies may also be accounted for.
     // -- Could also account for defensive forces of a neutral power who is assumed to never ally/grant military access to the potential attacker. (This is a relatively common situation -
     //  granting military access requires a lot of trust & is typicaly equivalent to alliance, because there will be no arbtrary mechanics to prevent declaring war while accessing an empire)
-
+    // This is the empire's ThreatMap, plus hostile fleets already in the system
 
     // Special qualities
 
     // CHOKEPOINT / DEFENSIVE BASE
+    pub chokepoint: bool,
     // TRADE VALUE
     // MILITARY / OFFENSIVE BASE
     // -- Rated based on , probably similar to the inversion of the security calculation above
@@ -53,55 +70,153 @@ struct ColonyRating {
     // if an appropriate one is available
 }
 
-#[derive(Component)]
-struct ColonyVizier {
-    owned_planets : Vec<(Entity,u32)>,
-    map : EntityHashMap<ColonyRating>
-}
-
-impl Default for ColonyVizier {
-    fn default() -> Self {
-        Self {
-            owned_planets : Vec::new(),
-            map : default()
+impl ColonyRating {
+    pub fn score(&self) -> i64 {
+        let mut score = GOAL_SCALE - self.capital_distance as i64 / 2 - self.distance as i64;
+        score = score
+            * if self.consolidate {
+                CONSOLIDATE_FACTOR
+            } else {
+                EXPANSION_FACTOR
+            };
+        score = score * self.security;
+        if self.chokepoint {
+            score += score * CHOKEPOINT_BONUS;
         }
+        score.max(0)
     }
 }
 
-fn rate_colonies_system(
-    planet_query : Query<(Entity,&Planet,Option<&Colony>),Without<Empire>>,
-    star_query : Query<(&Star,&StarClaim),Without<Empire>>,
-    mut empire_query : Query<(&Empire,&mut ColonyVizier)>,
-    hypernet : Res<Hypernet>
-) {
-    for (_empire, mut viz) in empire_query.iter_mut() {
-        viz.owned_planets.clear();
-    }
-
-    for (entity,planet,colony) in &planet_query {
-        if let Some(owner) = colony.and_then(|x| Some(x.owner)) {
-            let (_empire,mut viz) = empire_query.get_mut(owner).unwrap();
-            viz.owned_planets.push((entity,planet.star_id));
-        }
-    }
-
-
-    empire_query.par_iter_mut().for_each(|(empire,mut vizier)| {
-        // array of distances to stars (indexed by hypernet node id)
-        // Currently the source points are any planet owned by the empire
-        // This needs to be improved
-        // - at a bare minimum, restricting the sources to planets which are specifically capable of building and filling a colony ship
-
-        // really, you want a more complex reachability score, where a planet is 
-        // This might require projecting multiple different dijkstra maps from different source points
-        let dijkstra = hypernet.dijkstra(&vizier.owned_planets.iter().map(|x| x.1).collect::<Vec<_>>() );
+// The expansion vizier. Keeps the latest rating of every planet it could colonise
+#[derive(Component, Default)]
+pub struct ColonyVizier {
+    pub ratings: EntityHashMap<ColonyRating>,
+}
 
-        for (entity,planet,colony) in &planet_query {
-            vizier.map.insert(entity, ColonyRating {
-                distance : dijkstra[planet.star_id as usize],
-                security : None,
-                valid : colony.is_none()
-            });
-        }
-    });
-}
\ No newline at end of file
+#[allow(clippy::too_many_arguments)]
+pub fn rate_colonies_system(
+    mut empire_query: Query<(
+        Entity,
+        &mut AiEmpire,
+        &mut ColonyVizier,
+        &NavigationMask,
+        &EmpireIndex,
+        &ThreatMap,
+        &SurveyedSystems,
+    )>,
+    star_query: Query<(&Star, &StarClaim)>,
+    planet_query: Query<(&Planet, Option<&Colony>)>,
+    hypernet: Res<Hypernet>,
+    analysis: Res<crate::galaxy::HypernetAnalysis>,
+    path_cache: Res<PathCache>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    empire_query.par_iter_mut().for_each(
+        |(empire, mut ai, mut vizier, mask, index, threat, surveyed)| {
+            if !ai.is_reviewing(empire, sim_settings.current_tick) {
+                return;
+            }
+            vizier.ratings.clear();
+
+            let Some(capital) = index
+                .colonies
+                .first()
+                .and_then(|x| planet_query.get(*x).ok())
+                .map(|(planet, _)| planet.star_id)
+            else {
+                return;
+            };
+
+            // colony ships are civilian, so distances go around danger
+            let filter = mask.to_filter(&hypernet).with_profile(CostProfile::Safe);
+            let capital_distances = path_cache.distances(&filter, capital);
+            let sources = index
+                .colonies
+                .iter()
+                .filter_map(|x| planet_query.get(*x).ok())
+                .map(|(planet, _)| planet.star_id)
+                .collect::<Vec<_>>();
+            let distances = filter.dijkstra(&sources);
+
+            let mut best_survey: Option<(u32, i64)> = None;
+            for (star, claim) in &star_query {
+                if claim.owner.is_some() && claim.owner != Some(empire) {
+                    continue;
+                }
+                let (Some(distance), Some(capital_distance)) = (
+                    distances[star.node_id as usize],
+                    capital_distances[star.node_id as usize],
+                ) else {
+                    continue;
+                };
+
+                let consolidate = star
+                    .orbiters
+                    .iter()
+                    .filter_map(|x| planet_query.get(*x).ok())
+                    .any(|(_, colony)| colony.is_some_and(|x| x.owner == empire));
+                let security = IPercent::new(
+                    (threat.security(star.node_id).value() - 250 * mask.danger(star.node_id))
+                        .max(100),
+                );
+                let chokepoint = analysis.is_chokepoint(star.node_id);
+
+                if !surveyed.is_known(star.node_id, mask, &hypernet) {
+                    let uncolonised = star
+                        .orbiters
+                        .iter()
+                        .any(|x| matches!(planet_query.get(*x), Ok((_, None))));
+                    if !uncolonised || ai.is_avoiding(hypernet.star(star.node_id).entity) {
+                        continue;
+                    }
+                    let rating = ColonyRating {
+                        distance,
+                        capital_distance,
+                        consolidate,
+                        security,
+                        chokepoint,
+                    };
+                    let score = rating.score() * SURVEY_FACTOR;
+                    if best_survey.is_none_or(|(system, s)| (score, star.node_id) > (s, system)) {
+                        best_survey = Some((star.node_id, score));
+                    }
+                    continue;
+                }
+
+                for planet_entity in &star.orbiters {
+                    let Ok((_, None)) = planet_query.get(*planet_entity) else {
+                        continue;
+                    };
+                    if ai.is_avoiding(*planet_entity) {
+                        continue;
+                    }
+                    vizier.ratings.insert(
+                        *planet_entity,
+                        ColonyRating {
+                            distance,
+                            capital_distance,
+                            consolidate,
+                            security,
+                            chokepoint,
+                        },
+                    );
+                }
+            }
+
+            let mut best = vizier
+                .ratings
+                .iter()
+                .map(|(planet, rating)| (*planet, rating.score()))
+                .collect::<Vec<_>>();
+            best.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+            let weight = ai.personality.expansion;
+            for (planet, score) in best.into_iter().take(COLONY_GOALS) {
+                ai.propose(GoalKind::Colonise(planet), score, weight);
+            }
+            if let Some((system, score)) = best_survey {
+                ai.propose(GoalKind::Survey(system), score, weight);
+            }
+        },
+    );
+}
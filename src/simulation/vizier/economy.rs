@@ -0,0 +1,85 @@
+use crate::prelude::*;
+use crate::simulation::economy::market::Market;
+use crate::simulation::policy::{EconomicPolicies, InvestmentPriority, PolicyChange, PolicyLevel};
+use bevy::prelude::*;
+
+use super::{AiEmpire, GoalKind, GOAL_SCALE};
+
+// Colonies filled beyond this share of what their planet supports want to send people elsewhere
+const CROWDED_PERCENT: i64 = 80;
+// Goods this much pricier than base are worth investing in
+const EXPENSIVE_PRICE: i32 = 1300;
+
+// The economy vizier. Looks after living space and economic policy
+// Crowded colonies ask to fill their colony ships faster, so the excess population goes towards new colonies
+// Policies follow the personality: expansionist empires invest in advanced industry (which builds colony ships),
+// economy-minded ones pay for education with higher taxes, and the rest invest wherever the market is short
+pub fn economy_vizier_system(
+    mut empire_query: Query<(
+        Entity,
+        &mut AiEmpire,
+        &EmpireIndex,
+        &Market,
+        &EconomicPolicies,
+    )>,
+    colony_query: Query<(&Planet, &Colony)>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    for (empire, mut ai, index, market, policies) in empire_query.iter_mut() {
+        if !ai.is_reviewing(empire, sim_settings.current_tick) {
+            continue;
+        }
+
+        let weight = ai.personality.economy;
+        if policies.reform().is_none() {
+            let personality = ai.personality;
+            let investment = if personality.expansion.value() >= 1200 {
+                InvestmentPriority::Advanced
+            } else if market.prices.heavy.value() >= EXPENSIVE_PRICE {
+                InvestmentPriority::Heavy
+            } else if market.prices.light.value() >= EXPENSIVE_PRICE {
+                InvestmentPriority::Light
+            } else {
+                InvestmentPriority::Balanced
+            };
+            let education = if personality.economy.value() >= 1000 {
+                PolicyLevel::High
+            } else {
+                PolicyLevel::Standard
+            };
+            // Taxes have to cover education
+            let taxation = education;
+
+            let wanted = [
+                PolicyChange::Investment(investment),
+                PolicyChange::Education(education),
+                PolicyChange::Taxation(taxation),
+            ];
+            if let Some(change) = wanted
+                .into_iter()
+                .find(|x| policies.current(x.kind()) != *x)
+            {
+                ai.propose(GoalKind::Reform(change), GOAL_SCALE / 2, weight);
+            }
+        }
+
+        for colony_entity in &index.colonies {
+            let Ok((planet, colony)) = colony_query.get(*colony_entity) else {
+                continue;
+            };
+            let capacity = planet.get_population_support() as i64;
+            if capacity == 0 {
+                continue;
+            }
+
+            let crowding = colony.population.val() * 100 / capacity;
+            if crowding >= CROWDED_PERCENT {
+                ai.propose(
+                    GoalKind::Relieve(*colony_entity),
+                    GOAL_SCALE * crowding.min(100) / 100,
+                    weight,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::prelude::*;
+use crate::simulation::policy::{EconomicPolicies, PolicyChange, PolicyKind, PolicyLevel};
+use bevy::prelude::*;
+
+use super::{AiEmpire, GoalKind, GOAL_SCALE};
+
+const CHOKEPOINT_BONUS: IPercent = IPercent::new(200);
+
+// The military vizier. At war, it rates every enemy colony it can reach
+// Close, lightly populated (so lightly garrisoned) colonies come first, and chokepoints are worth a little extra
+// It also wants full conscription at war, and as little as possible at peace
+pub fn military_vizier_system(
+    mut empire_query: Query<(
+        Entity,
+        &mut AiEmpire,
+        &Diplomacy,
+        &NavigationMask,
+        &EmpireIndex,
+        &EconomicPolicies,
+    )>,
+    star_query: Query<&Star>,
+    colony_query: Query<(&Planet, &Colony)>,
+    hypernet: Res<Hypernet>,
+    analysis: Res<crate::galaxy::HypernetAnalysis>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    for (empire, mut ai, diplomacy, mask, index, policies) in empire_query.iter_mut() {
+        if !ai.is_reviewing(empire, sim_settings.current_tick) {
+            continue;
+        }
+
+        let conscription = PolicyChange::Conscription(if diplomacy.at_war.is_empty() {
+            PolicyLevel::Low
+        } else {
+            PolicyLevel::High
+        });
+        if policies.reform().is_none() && policies.current(PolicyKind::Conscription) != conscription
+        {
+            let weight = ai.personality.aggression;
+            ai.propose(GoalKind::Reform(conscription), GOAL_SCALE / 2, weight);
+        }
+        if diplomacy.at_war.is_empty() {
+            continue;
+        }
+
+        let sources = index
+            .colonies
+            .iter()
+            .filter_map(|x| colony_query.get(*x).ok())
+            .map(|(planet, _)| planet.star_id)
+            .collect::<Vec<_>>();
+        let distances = mask
+            .to_filter(&hypernet)
+            .with_profile(CostProfile::Offensive)
+            .dijkstra(&sources);
+
+        let mut best: Option<(Entity, i64)> = None;
+        for star in &star_query {
+            let Some(distance) = distances[star.node_id as usize] else {
+                continue;
+            };
+            for planet_entity in &star.orbiters {
+                let Ok((_, colony)) = colony_query.get(*planet_entity) else {
+                    continue;
+                };
+                if !diplomacy.at_war.contains(&colony.owner) || ai.is_avoiding(*planet_entity) {
+                    continue;
+                }
+
+                // 1e9 people halve the score
+                let mut score = (GOAL_SCALE - distance as i64) * 1000
+                    / (1000 + colony.population.val() / 1000000);
+                if analysis.is_chokepoint(star.node_id) {
+                    score += score * CHOKEPOINT_BONUS;
+                }
+                if best.is_none_or(|(_, s)| score > s) {
+                    best = Some((*planet_entity, score));
+                }
+            }
+        }
+
+        let weight = ai.personality.aggression;
+        if let Some((planet, score)) = best {
+            ai.propose(GoalKind::Invade(planet), score, weight);
+        }
+    }
+}
@@ -1,2 +1,213 @@
+use crate::prelude::*;
+use crate::simulation::policy::PolicyChange;
+use bevy::prelude::*;
+use rand::prelude::*;
+
 mod colony_desire;
-mod security;
\ No newline at end of file
+mod economy;
+mod military;
+mod planner;
+mod security;
+
+pub use colony_desire::ColonyVizier;
+pub use security::SecurityVizier;
+
+use super::SimTick;
+
+// EMPIRE AI
+// Every so often, each AI empire holds a review. Each vizier looks after one concern (expansion, economy, security, military)
+// and proposes scored goals, then the planner acts on the best of them.
+// Scores share one scale (GOAL_SCALE), the personality decides how much weight each concern gets
+
+// A goal that's as good as it gets, before personality weighting
+const GOAL_SCALE: i64 = 10000000; // 1e7
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AiDifficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl AiDifficulty {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "easy" => Some(Self::Easy),
+            "normal" => Some(Self::Normal),
+            "hard" => Some(Self::Hard),
+            _ => None,
+        }
+    }
+
+    // Days between reviews
+    fn review_interval(self) -> i64 {
+        match self {
+            Self::Easy => 90,
+            Self::Normal => 30,
+            Self::Hard => 10,
+        }
+    }
+
+    // How many goals the planner acts on per review
+    fn goals_per_review(self) -> usize {
+        match self {
+            Self::Easy => 1,
+            Self::Normal => 2,
+            Self::Hard => 4,
+        }
+    }
+}
+
+// Weight of each concern, 100% being an even-handed empire
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AiPersonality {
+    pub expansion: IPercent,
+    pub economy: IPercent,
+    pub security: IPercent,
+    pub aggression: IPercent,
+}
+
+impl AiPersonality {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let mut weight = || IPercent::new(rng.random_range(500..=1500));
+        Self {
+            expansion: weight(),
+            economy: weight(),
+            security: weight(),
+            aggression: weight(),
+        }
+    }
+
+    // balanced, expansionist, mercantile, cautious or aggressive
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (expansion, economy, security, aggression) = match name {
+            "balanced" => (1000, 1000, 1000, 1000),
+            "expansionist" => (1500, 1000, 750, 750),
+            "mercantile" => (1000, 1500, 1000, 500),
+            "cautious" => (750, 1000, 1500, 500),
+            "aggressive" => (1000, 750, 1000, 1500),
+            _ => return None,
+        };
+        Some(Self {
+            expansion: IPercent::new(expansion),
+            economy: IPercent::new(economy),
+            security: IPercent::new(security),
+            aggression: IPercent::new(aggression),
+        })
+    }
+}
+
+// Settings for one AI empire, overriding the global difficulty and the random personality
+//   --ai <empire>=<difficulty>[,<personality>]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AiOverride {
+    pub empire: usize, // In placement order, the player's empire being 0
+    pub difficulty: AiDifficulty,
+    pub personality: Option<AiPersonality>,
+}
+
+impl AiOverride {
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        let (empire, settings) = arg.split_once('=')?;
+        let (difficulty, personality) = match settings.split_once(',') {
+            Some((difficulty, personality)) => {
+                (difficulty, Some(AiPersonality::from_name(personality)?))
+            }
+            None => (settings, None),
+        };
+        Some(Self {
+            empire: empire.parse().ok()?,
+            difficulty: AiDifficulty::from_name(difficulty)?,
+            personality,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum GoalKind {
+    Colonise(Entity), // Planet
+    Survey(u32), // System with planets worth colonising, that the empire doesn't know well enough yet
+    Relieve(Entity), // Overcrowded colony, should send its people elsewhere
+    Fortify(u32), // Owned system that needs a supply depot
+    Patrol(u32), // Owned system under threat
+    Invade(Entity), // Enemy colony
+    Reform(PolicyChange),
+}
+
+#[derive(Clone, Copy)]
+pub struct Goal {
+    pub kind: GoalKind,
+    pub score: i64,
+}
+
+// Marks an empire as AI controlled
+#[derive(Component)]
+pub struct AiEmpire {
+    pub difficulty: AiDifficulty,
+    pub personality: AiPersonality,
+    goals: Vec<Goal>,
+    avoid: Vec<(Entity, i64)>, // Targets of failed missions, until the given tick
+}
+
+impl AiEmpire {
+    pub fn new(difficulty: AiDifficulty, personality: AiPersonality) -> Self {
+        Self {
+            difficulty,
+            personality,
+            goals: Vec::new(),
+            avoid: Vec::new(),
+        }
+    }
+
+    // Empires are offset by their entity index so they don't all review on the same tick
+    fn is_reviewing(&self, empire: Entity, tick: i64) -> bool {
+        (tick + empire.index() as i64) % self.difficulty.review_interval() == 0
+    }
+
+    fn is_avoiding(&self, target: Entity) -> bool {
+        self.avoid.iter().any(|(x, _)| *x == target)
+    }
+
+    fn propose(&mut self, kind: GoalKind, score: i64, weight: IPercent) {
+        let score = score * weight;
+        if score > 0 {
+            self.goals.push(Goal { kind, score });
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct AiBundle {
+    ai: AiEmpire,
+    colony_vizier: ColonyVizier,
+    security_vizier: SecurityVizier,
+}
+
+impl AiBundle {
+    pub fn new(difficulty: AiDifficulty, personality: AiPersonality) -> Self {
+        Self {
+            ai: AiEmpire::new(difficulty, personality),
+            colony_vizier: ColonyVizier::default(),
+            security_vizier: SecurityVizier::default(),
+        }
+    }
+}
+
+pub struct VizierPlugin;
+
+impl Plugin for VizierPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            SimTick,
+            (
+                colony_desire::rate_colonies_system,
+                economy::economy_vizier_system,
+                security::security_vizier_system,
+                military::military_vizier_system,
+                planner::plan_missions_system,
+                planner::remember_failed_missions_system,
+            )
+                .chain(),
+        );
+    }
+}
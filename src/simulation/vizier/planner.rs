@@ -0,0 +1,192 @@
+use crate::prelude::*;
+use crate::simulation::mission::colonise::ColoniseMission;
+use crate::simulation::mission::invade::InvadeMission;
+use crate::simulation::mission::patrol::PatrolMission;
+use crate::simulation::mission::planet_launch_colony::LaunchColonyMission;
+use crate::simulation::mission::survey::SurveyMission;
+use crate::simulation::mission::{Mission, MissionCompletedEvent, MissionState};
+use crate::simulation::policy::EconomicPolicies;
+use crate::simulation::supply::SupplyDepot;
+use bevy::prelude::*;
+
+use super::{AiEmpire, GoalKind};
+
+// Failed targets are left alone for this many days
+const AVOID_DURATION: i64 = 360;
+
+// Turns the best goals of each review into missions
+//  - Colonise: a colonisation mission, which finds or orders a colony ship
+//  - Survey: a survey mission, which raises a scout
+//  - Relieve: the colony fills its colony ships faster
+//  - Fortify: a supply depot is built in the system
+//  - Patrol: a patrol mission, which raises a patrol to guard the system
+//  - Invade: an invasion mission, picked up by the next troop ship raised
+//  - Reform: the empire starts changing one of its economic policies
+pub fn plan_missions_system(
+    mut empire_query: Query<(Entity, &mut AiEmpire, &EmpireIndex, &mut EconomicPolicies)>,
+    mut launch_query: Query<&mut LaunchColonyMission>,
+    mission_query: Query<&Mission>,
+    hypernet: Res<Hypernet>,
+    sim_settings: Res<SimulationSettings>,
+    mut commands: Commands,
+) {
+    for (empire, mut ai, index, mut policies) in empire_query.iter_mut() {
+        if !ai.is_reviewing(empire, sim_settings.current_tick) {
+            continue;
+        }
+
+        let mut goals = std::mem::take(&mut ai.goals);
+        goals.sort_by_key(|x| std::cmp::Reverse(x.score));
+
+        // Priorities only last until the next review
+        for colony_entity in &index.colonies {
+            if let Ok(mut launch) = launch_query.get_mut(*colony_entity) {
+                launch.set_priority(false);
+            }
+        }
+
+        let has_mission = |target: Entity| {
+            mission_query
+                .iter()
+                .any(|x| x.owner == empire && x.target == target && x.is_active())
+        };
+        let tick = sim_settings.current_tick;
+
+        for goal in goals.iter().take(ai.difficulty.goals_per_review()) {
+            match goal.kind {
+                GoalKind::Colonise(planet) => {
+                    if !has_mission(planet) {
+                        commands.spawn((Mission::new(empire, planet, tick), ColoniseMission));
+                    }
+                }
+                GoalKind::Survey(system) => {
+                    let star = hypernet.star(system).entity;
+                    if !has_mission(star) {
+                        commands.spawn((Mission::new(empire, star, tick), SurveyMission));
+                    }
+                }
+                GoalKind::Relieve(colony) => {
+                    if let Ok(mut launch) = launch_query.get_mut(colony) {
+                        launch.set_priority(true);
+                    }
+                }
+                GoalKind::Fortify(system) => {
+                    commands
+                        .entity(hypernet.star(system).entity)
+                        .insert(SupplyDepot { owner: empire });
+                }
+                GoalKind::Patrol(system) => {
+                    let star = hypernet.star(system).entity;
+                    if !has_mission(star) {
+                        commands.spawn((Mission::new(empire, star, tick), PatrolMission));
+                    }
+                }
+                GoalKind::Invade(planet) => {
+                    if !has_mission(planet) {
+                        commands.spawn((Mission::new(empire, planet, tick), InvadeMission));
+                    }
+                }
+                GoalKind::Reform(change) => {
+                    policies.request(change);
+                }
+            }
+        }
+    }
+}
+
+// Remembers failed targets, so the viziers don't keep proposing them
+pub fn remember_failed_missions_system(
+    mut empire_query: Query<&mut AiEmpire>,
+    mut ev_completed: EventReader<MissionCompletedEvent>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    let tick = sim_settings.current_tick;
+    for mut ai in empire_query.iter_mut() {
+        ai.avoid.retain(|(_, until)| *until > tick);
+    }
+
+    for ev in ev_completed.read() {
+        if ev.state != MissionState::Failed {
+            continue;
+        }
+        if let Ok(mut ai) = empire_query.get_mut(ev.owner) {
+            ai.avoid.push((ev.target, tick + AVOID_DURATION));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{AiDifficulty, AiPersonality};
+    use super::*;
+
+    #[test]
+    fn acts_on_the_best_weighted_goals() {
+        let mut world = World::new();
+        let personality = AiPersonality::from_name("aggressive").unwrap();
+        let mut ai = AiEmpire::new(AiDifficulty::Normal, personality);
+        let (colony_target, invasion_target) = (world.spawn_empty().id(), world.spawn_empty().id());
+        ai.propose(GoalKind::Colonise(colony_target), 8, personality.expansion);
+        ai.propose(GoalKind::Invade(invasion_target), 6, personality.aggression);
+        ai.propose(GoalKind::Patrol(0), 7, personality.economy);
+        let empire = world
+            .spawn((ai, EmpireIndex::default(), EconomicPolicies::default()))
+            .id();
+        // On this empire's review day
+        let tick = 30 - empire.index() as i64 % 30;
+        world.insert_resource(SimulationSettings::at_tick(tick));
+        world.insert_resource(Hypernet::from_lanes(&[(0.0, 0.0)], &[]));
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(plan_missions_system);
+        schedule.run(&mut world);
+
+        // Normal empires act on two goals per review, aggression puts the invasion first
+        let mut missions = world.query::<(&Mission, Has<ColoniseMission>, Has<InvadeMission>)>();
+        let mut targets = missions
+            .iter(&world)
+            .map(|(mission, colonise, invade)| {
+                assert_eq!(mission.owner, empire);
+                assert!(colonise != invade);
+                mission.target
+            })
+            .collect::<Vec<_>>();
+        targets.sort();
+        assert_eq!(targets, [colony_target, invasion_target]);
+        assert!(world.get::<AiEmpire>(empire).unwrap().goals.is_empty());
+    }
+
+    #[test]
+    fn avoids_failed_targets_for_a_while() {
+        let mut world = World::new();
+        let empire = world
+            .spawn(AiEmpire::new(
+                AiDifficulty::Normal,
+                AiPersonality::from_name("balanced").unwrap(),
+            ))
+            .id();
+        let (failed, succeeded) = (world.spawn_empty().id(), world.spawn_empty().id());
+        world.insert_resource(SimulationSettings::at_tick(100));
+        world.init_resource::<Events<MissionCompletedEvent>>();
+        for (target, state) in [
+            (failed, MissionState::Failed),
+            (succeeded, MissionState::Succeeded),
+        ] {
+            world.send_event(MissionCompletedEvent {
+                owner: empire,
+                target,
+                state,
+            });
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(remember_failed_missions_system);
+        schedule.run(&mut world);
+        let ai = world.get::<AiEmpire>(empire).unwrap();
+        assert!(ai.is_avoiding(failed) && !ai.is_avoiding(succeeded));
+
+        world.insert_resource(SimulationSettings::at_tick(100 + AVOID_DURATION));
+        schedule.run(&mut world);
+        assert!(!world.get::<AiEmpire>(empire).unwrap().is_avoiding(failed));
+    }
+}
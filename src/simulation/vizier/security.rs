@@ -1,13 +1,123 @@
-use bevy::prelude::*;
+use crate::galaxy::hypernet_analysis::SystemRole;
 use crate::prelude::*;
+use crate::simulation::mission::patrol::PatrolMission;
+use crate::simulation::mission::Mission;
+use crate::simulation::supply::SupplyDepot;
+use crate::simulation::threat::ThreatMap;
+use bevy::prelude::*;
+
+use std::collections::HashMap;
 
-use bevy::ecs::entity::EntityHashMap;
+use super::{AiEmpire, GoalKind, GOAL_SCALE};
 
-struct SecurityRating {
-    
+// Patrols only go to systems at least this threatened (out of 1000), at PATROL_FACTOR of the threat's score
+const PATROL_MIN_THREAT: i32 = 500;
+const PATROL_FACTOR: IPercent = IPercent::new(500);
+
+pub struct SecurityRating {
+    pub exposure: i32, // Neighbouring systems the empire doesn't own, hostile ones count double
+    pub danger: i32,   // Hostile fleets in the system
+    pub chokepoint: bool,
+    pub threat: IPercent, // From the empire's threat map
 }
 
-#[derive(Component)]
-struct SecurityVizier {
+impl SecurityRating {
+    // 0-1000, how badly the system needs defending
+    pub fn threat(&self) -> i32 {
+        let threat = self.threat.value()
+            + 150 * self.exposure
+            + 250 * self.danger
+            + if self.chokepoint { 300 } else { 0 };
+        threat.min(1000)
+    }
+}
 
-}
\ No newline at end of file
+// The security vizier. Keeps the latest rating of every owned system
+#[derive(Component, Default)]
+pub struct SecurityVizier {
+    pub ratings: HashMap<u32, SecurityRating>,
+}
+
+// Proposes supply depots in exposed systems that don't have one yet, and patrols for the most threatened system that isn't guarded yet
+pub fn security_vizier_system(
+    mut empire_query: Query<(
+        Entity,
+        &mut AiEmpire,
+        &mut SecurityVizier,
+        &NavigationMask,
+        &ThreatMap,
+    )>,
+    depot_query: Query<(), With<SupplyDepot>>,
+    patrol_query: Query<&Mission, With<PatrolMission>>,
+    hypernet: Res<Hypernet>,
+    analysis: Res<crate::galaxy::HypernetAnalysis>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    for (empire, mut ai, mut vizier, mask, threat_map) in empire_query.iter_mut() {
+        if !ai.is_reviewing(empire, sim_settings.current_tick) {
+            continue;
+        }
+        vizier.ratings.clear();
+
+        // Core systems are covered by the frontier around them
+        for system in mask
+            .owned_systems
+            .iter()
+            .filter(|x| analysis.role(empire, **x) != Some(SystemRole::Core))
+        {
+            let exposure = hypernet
+                .graph
+                .neighbors((*system).into())
+                .map(|x| match mask.access(x.index() as u32) {
+                    SystemAccess::Owned | SystemAccess::AlliedAccess => 0,
+                    SystemAccess::Hostile | SystemAccess::ClosedBorders => 2,
+                    _ => 1,
+                })
+                .sum();
+            vizier.ratings.insert(
+                *system,
+                SecurityRating {
+                    exposure,
+                    danger: mask.danger(*system),
+                    chokepoint: analysis.is_chokepoint(*system),
+                    threat: threat_map.threat(*system),
+                },
+            );
+        }
+
+        let weight = ai.personality.security;
+        let best = vizier
+            .ratings
+            .iter()
+            .filter(|(system, _)| !depot_query.contains(hypernet.star(**system).entity))
+            .map(|(system, rating)| (*system, rating.threat()))
+            .max_by_key(|(system, threat)| (*threat, *system));
+        if let Some((system, threat)) = best {
+            ai.propose(
+                GoalKind::Fortify(system),
+                GOAL_SCALE * threat as i64 / 1000,
+                weight,
+            );
+        }
+
+        let patrolled = patrol_query
+            .iter()
+            .filter(|x| x.owner == empire && x.is_active())
+            .map(|x| x.target)
+            .collect::<Vec<_>>();
+        let best = vizier
+            .ratings
+            .iter()
+            .filter(|(system, _)| !patrolled.contains(&hypernet.star(**system).entity))
+            .map(|(system, rating)| (*system, rating.threat()))
+            .filter(|(_, threat)| *threat >= PATROL_MIN_THREAT)
+            .max_by_key(|(system, threat)| (*threat, *system));
+        if let Some((system, threat)) = best {
+            ai.propose(
+                GoalKind::Patrol(system),
+                GOAL_SCALE * threat as i64 / 1000 * PATROL_FACTOR,
+                weight,
+            );
+        }
+    }
+}
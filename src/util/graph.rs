@@ -0,0 +1,306 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+// Generic shortest path searches, shared by the raw hypernet and the empire navigation filters
+// Node and edge ids are plain u32 indices, so anything that can be viewed as an indexed graph can be searched
+
+pub struct Path {
+    pub nodes: Vec<u32>,
+    pub edges: Vec<u32>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn reverse(&mut self) {
+        self.nodes.reverse();
+        self.edges.reverse();
+    }
+}
+
+// What a search needs to know about a graph: which neighbours can be entered, and at what cost
+pub trait SearchGraph {
+    // Upper bound on node ids
+    fn node_capacity(&self) -> usize;
+
+    // Calls "visit" with (neighbour, edge id, cost) for every neighbour that can be entered from "node"
+    // Costs must not be negative
+    fn for_each_neighbour(&self, node: u32, visit: impl FnMut(u32, u32, i32));
+
+    // Estimate of the cost from "node" to "dest". Must never overestimate it, or A* stops being exact
+    fn heuristic(&self, node: u32, dest: u32) -> i32;
+}
+
+#[derive(PartialEq, Eq)]
+struct OpenNode {
+    node: u32,
+    cost: i32,
+    estimate: i32, // cost + heuristic
+}
+
+impl Ord for OpenNode {
+    // BinaryHeap is a max heap, so the comparison is reversed to pop the lowest estimate first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// A* from the closest of "sources" to "dest"
+// "excluded_edge" (from, to) forbids going straight from one node to the other, eg. to find the detour around a lane
+pub fn find_path<G: SearchGraph>(
+    graph: &G,
+    sources: &[u32],
+    dest: u32,
+    excluded_edge: Option<(u32, u32)>,
+) -> Option<Path> {
+    let n = graph.node_capacity();
+    let mut best = vec![None; n];
+    let mut parent: Vec<Option<(u32, u32)>> = vec![None; n]; // (parent node, edge to parent)
+    let mut closed = vec![false; n];
+    let mut open = BinaryHeap::new();
+
+    for source in sources {
+        best[*source as usize] = Some(0);
+        open.push(OpenNode {
+            node: *source,
+            cost: 0,
+            estimate: graph.heuristic(*source, dest),
+        });
+    }
+
+    while let Some(top) = open.pop() {
+        if closed[top.node as usize] {
+            continue;
+        }
+        closed[top.node as usize] = true;
+
+        if top.node == dest {
+            return Some(trace_path(&parent, dest));
+        }
+
+        graph.for_each_neighbour(top.node, |next, edge, cost| {
+            if excluded_edge == Some((top.node, next)) || closed[next as usize] {
+                return;
+            }
+            let cost = top.cost.saturating_add(cost);
+            if best[next as usize].is_none_or(|x| cost < x) {
+                best[next as usize] = Some(cost);
+                parent[next as usize] = Some((top.node, edge));
+                open.push(OpenNode {
+                    node: next,
+                    cost,
+                    estimate: cost.saturating_add(graph.heuristic(next, dest)),
+                });
+            }
+        });
+    }
+
+    None
+}
+
+fn trace_path(parent: &[Option<(u32, u32)>], dest: u32) -> Path {
+    let mut path = Path::new();
+    let mut curr = dest;
+    path.nodes.push(curr);
+    while let Some((node, edge)) = parent[curr as usize] {
+        path.edges.push(edge);
+        path.nodes.push(node);
+        curr = node;
+    }
+    path.reverse();
+    path
+}
+
+// Cost from the closest of "sources" to every node, indexed by node id. None for nodes that can't be reached
+pub fn dijkstra<G: SearchGraph>(graph: &G, sources: &[u32]) -> Vec<Option<i32>> {
+    let sources = sources.iter().map(|x| (*x, 0)).collect::<Vec<_>>();
+    dijkstra_within(graph, &sources, i32::MAX)
+}
+
+// Dijkstra that stops expanding past "max_cost", with a starting cost per source
+// A source starting at max_cost - r effectively has a range of r, which lets sources of different reach share one search
+pub fn dijkstra_within<G: SearchGraph>(
+    graph: &G,
+    sources: &[(u32, i32)],
+    max_cost: i32,
+) -> Vec<Option<i32>> {
+    let mut result: Vec<Option<i32>> = vec![None; graph.node_capacity()];
+    let mut open = BinaryHeap::new();
+
+    for (source, cost) in sources {
+        if *cost > max_cost || result[*source as usize].is_some_and(|x| x <= *cost) {
+            continue;
+        }
+        result[*source as usize] = Some(*cost);
+        open.push(OpenNode {
+            node: *source,
+            cost: *cost,
+            estimate: *cost,
+        });
+    }
+
+    while let Some(top) = open.pop() {
+        if result[top.node as usize].is_some_and(|x| x < top.cost) {
+            continue;
+        }
+        graph.for_each_neighbour(top.node, |next, _, cost| {
+            let cost = top.cost.saturating_add(cost);
+            if cost <= max_cost && result[next as usize].is_none_or(|x| cost < x) {
+                result[next as usize] = Some(cost);
+                open.push(OpenNode {
+                    node: next,
+                    cost,
+                    estimate: cost,
+                });
+            }
+        });
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    // Random points joined by random edges, costs are the distance rounded up (times a random multiplier)
+    // and the heuristic is the straight line distance rounded down, so it can never overestimate
+    struct TestGraph {
+        positions: Vec<(i32, i32)>,
+        edges: Vec<(u32, u32, i32)>,
+        neighbours: Vec<Vec<u32>>, // Edge ids
+    }
+
+    impl TestGraph {
+        fn random(rng: &mut StdRng) -> Self {
+            let n = rng.random_range(2..40);
+            let positions = (0..n)
+                .map(|_| (rng.random_range(0..1000), rng.random_range(0..1000)))
+                .collect::<Vec<_>>();
+            let mut graph = Self {
+                positions,
+                edges: Vec::new(),
+                neighbours: vec![Vec::new(); n],
+            };
+            for _ in 0..rng.random_range(0..n * 3) {
+                let (a, b) = (rng.random_range(0..n as u32), rng.random_range(0..n as u32));
+                if a != b {
+                    let cost = graph.distance(a, b).ceil() as i32 * rng.random_range(1..4);
+                    graph.neighbours[a as usize].push(graph.edges.len() as u32);
+                    graph.neighbours[b as usize].push(graph.edges.len() as u32);
+                    graph.edges.push((a, b, cost));
+                }
+            }
+            graph
+        }
+
+        fn distance(&self, a: u32, b: u32) -> f64 {
+            let (a, b) = (self.positions[a as usize], self.positions[b as usize]);
+            (((a.0 - b.0).pow(2) + (a.1 - b.1).pow(2)) as f64).sqrt()
+        }
+
+        fn path_cost(&self, path: &Path) -> i32 {
+            path.edges.iter().map(|x| self.edges[*x as usize].2).sum()
+        }
+    }
+
+    impl SearchGraph for TestGraph {
+        fn node_capacity(&self) -> usize {
+            self.positions.len()
+        }
+
+        fn for_each_neighbour(&self, node: u32, mut visit: impl FnMut(u32, u32, i32)) {
+            for edge in &self.neighbours[node as usize] {
+                let (a, b, cost) = self.edges[*edge as usize];
+                visit(if a == node { b } else { a }, *edge, cost);
+            }
+        }
+
+        fn heuristic(&self, node: u32, dest: u32) -> i32 {
+            self.distance(node, dest).floor() as i32
+        }
+    }
+
+    fn random_graphs() -> impl Iterator<Item = TestGraph> {
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..200).map(move |_| TestGraph::random(&mut rng))
+    }
+
+    #[test]
+    fn find_path_matches_dijkstra() {
+        for graph in random_graphs() {
+            let n = graph.node_capacity() as u32;
+            for a in 0..n {
+                let distances = dijkstra(&graph, &[a]);
+                for b in 0..n {
+                    let path = find_path(&graph, &[a], b, None);
+                    assert_eq!(
+                        path.as_ref().map(|x| graph.path_cost(x)),
+                        distances[b as usize]
+                    );
+                    if let Some(path) = path {
+                        assert_eq!((path.nodes[0], *path.nodes.last().unwrap()), (a, b));
+                        assert_eq!(path.edges.len() + 1, path.nodes.len());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn heuristic_never_overestimates() {
+        for graph in random_graphs() {
+            let n = graph.node_capacity() as u32;
+            for dest in 0..n {
+                // Edges go both ways, so distances from dest are distances to it
+                for (node, cost) in dijkstra(&graph, &[dest]).into_iter().enumerate() {
+                    if let Some(cost) = cost {
+                        assert!(graph.heuristic(node as u32, dest) <= cost);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dijkstra_within_returns_nodes_within_bound() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for graph in random_graphs() {
+            let n = graph.node_capacity() as u32;
+            let sources = (0..rng.random_range(1..4))
+                .map(|_| (rng.random_range(0..n), rng.random_range(0..500)))
+                .collect::<Vec<_>>();
+            let max_cost = rng.random_range(0..2000);
+
+            let expected = (0..n as usize)
+                .map(|node| {
+                    sources
+                        .iter()
+                        .filter(|(_, start)| *start <= max_cost)
+                        .filter_map(|(source, start)| {
+                            dijkstra(&graph, &[*source])[node].map(|x| x + start)
+                        })
+                        .min()
+                        .filter(|x| *x <= max_cost)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(dijkstra_within(&graph, &sources, max_cost), expected);
+        }
+    }
+}
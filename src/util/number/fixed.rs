@@ -0,0 +1,405 @@
+use std::fmt;
+use std::ops;
+
+// Signed fixed-point number, counted in millionths
+// Every operation rounds half away from zero and saturates instead of overflowing, with no floats involved,
+// so the simulation gets the same results on every platform
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Fixed {
+    raw: i64,
+}
+
+const SCALE: i64 = 1_000_000;
+const DIGITS: usize = 6;
+
+// a / b, rounded half away from zero. b must not be 0
+fn round_div(a: i128, b: i128) -> i128 {
+    let (a, b) = if b < 0 { (-a, -b) } else { (a, b) };
+    if a >= 0 {
+        (a + b / 2) / b
+    } else {
+        (a - b / 2) / b
+    }
+}
+
+fn saturate(x: i128) -> i64 {
+    x.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+fn fits(x: i128) -> Option<i64> {
+    i64::try_from(x).ok()
+}
+
+impl Fixed {
+    pub const ZERO: Self = Self { raw: 0 };
+    pub const ONE: Self = Self { raw: SCALE };
+    pub const MAX: Self = Self { raw: i64::MAX };
+    pub const MIN: Self = Self { raw: i64::MIN };
+
+    pub const fn from_raw(raw: i64) -> Self {
+        Self { raw }
+    }
+
+    pub const fn raw(self) -> i64 {
+        self.raw
+    }
+
+    pub const fn from_int(x: i64) -> Self {
+        Self {
+            raw: x.saturating_mul(SCALE),
+        }
+    }
+
+    // Thousandths, eg. per_mille(150) is 0.15
+    pub const fn per_mille(x: i64) -> Self {
+        Self {
+            raw: x.saturating_mul(SCALE / 1000),
+        }
+    }
+
+    // numerator / denominator. Dividing by zero saturates, except 0 / 0 which is 0
+    pub fn ratio(numerator: i64, denominator: i64) -> Self {
+        Self::checked_ratio(numerator, denominator).unwrap_or(if numerator == 0 {
+            Self::ZERO
+        } else if (numerator < 0) != (denominator < 0) {
+            Self::MIN
+        } else {
+            Self::MAX
+        })
+    }
+
+    pub fn checked_ratio(numerator: i64, denominator: i64) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        fits(round_div(
+            numerator as i128 * SCALE as i128,
+            denominator as i128,
+        ))
+        .map(Self::from_raw)
+    }
+
+    pub fn round(self) -> i64 {
+        round_div(self.raw as i128, SCALE as i128) as i64
+    }
+
+    pub fn floor(self) -> i64 {
+        self.raw.div_euclid(SCALE)
+    }
+
+    // Towards zero
+    pub fn trunc(self) -> i64 {
+        self.raw / SCALE
+    }
+
+    // What's left after trunc, with the same sign as self
+    pub fn fract(self) -> Self {
+        Self::from_raw(self.raw % SCALE)
+    }
+
+    pub fn abs(self) -> Self {
+        Self::from_raw(self.raw.saturating_abs())
+    }
+
+    pub fn signum(self) -> i64 {
+        self.raw.signum()
+    }
+
+    // x * self, rounded to a whole number
+    pub fn of(self, x: i64) -> i64 {
+        saturate(round_div(x as i128 * self.raw as i128, SCALE as i128))
+    }
+
+    pub fn checked_of(self, x: i64) -> Option<i64> {
+        fits(round_div(x as i128 * self.raw as i128, SCALE as i128))
+    }
+
+    // x / self, rounded to a whole number
+    pub fn divide(self, x: i64) -> i64 {
+        match self.raw {
+            0 => Self::ratio(x, 0).raw,
+            raw => saturate(round_div(x as i128 * SCALE as i128, raw as i128)),
+        }
+    }
+
+    // self * numerator / denominator, rounded once at the end
+    pub fn mul_div(self, numerator: i64, denominator: i64) -> Self {
+        if denominator == 0 {
+            return Self::ratio(self.raw.saturating_mul(numerator), 0);
+        }
+        Self::from_raw(saturate(round_div(
+            self.raw as i128 * numerator as i128,
+            denominator as i128,
+        )))
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.raw.checked_add(rhs.raw).map(Self::from_raw)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.raw.checked_sub(rhs.raw).map(Self::from_raw)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        fits(round_div(self.raw as i128 * rhs.raw as i128, SCALE as i128)).map(Self::from_raw)
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.raw == 0 {
+            return None;
+        }
+        fits(round_div(self.raw as i128 * SCALE as i128, rhs.raw as i128)).map(Self::from_raw)
+    }
+
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        Self::from_raw(self.raw.saturating_add(rhs.raw))
+    }
+
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self::from_raw(self.raw.saturating_sub(rhs.raw))
+    }
+
+    pub fn saturating_mul(self, rhs: Self) -> Self {
+        Self::from_raw(saturate(round_div(
+            self.raw as i128 * rhs.raw as i128,
+            SCALE as i128,
+        )))
+    }
+
+    pub fn saturating_div(self, rhs: Self) -> Self {
+        if rhs.raw == 0 {
+            return Self::ratio(self.raw, 0);
+        }
+        Self::from_raw(saturate(round_div(
+            self.raw as i128 * SCALE as i128,
+            rhs.raw as i128,
+        )))
+    }
+}
+
+// The operators saturate
+impl ops::Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, rhs: Fixed) -> Fixed {
+        self.saturating_add(rhs)
+    }
+}
+
+impl ops::Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, rhs: Fixed) -> Fixed {
+        self.saturating_sub(rhs)
+    }
+}
+
+impl ops::Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, rhs: Fixed) -> Fixed {
+        self.saturating_mul(rhs)
+    }
+}
+
+impl ops::Div for Fixed {
+    type Output = Fixed;
+
+    fn div(self, rhs: Fixed) -> Fixed {
+        self.saturating_div(rhs)
+    }
+}
+
+impl ops::Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Self::from_raw(self.raw.saturating_neg())
+    }
+}
+
+impl ops::AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Fixed) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Fixed) {
+        *self = *self - rhs;
+    }
+}
+
+impl ops::Mul<Fixed> for i64 {
+    type Output = i64;
+
+    fn mul(self, rhs: Fixed) -> i64 {
+        rhs.of(self)
+    }
+}
+
+impl ops::Div<Fixed> for i64 {
+    type Output = i64;
+
+    fn div(self, rhs: Fixed) -> i64 {
+        rhs.divide(self)
+    }
+}
+
+// Prints every digit it needs, or rounds to the precision if there is one, eg. {:.2}
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = f.precision().unwrap_or(DIGITS).min(DIGITS);
+        let unit = 10_i128.pow((DIGITS - digits) as u32);
+        let rounded = round_div(self.raw as i128, unit);
+        let magnitude = rounded.unsigned_abs();
+        let denominator = 10_u128.pow(digits as u32);
+        let whole = magnitude / denominator;
+        let mut fraction = match digits {
+            0 => String::new(),
+            _ => format!("{:0width$}", magnitude % denominator, width = digits),
+        };
+        if f.precision().is_none() {
+            fraction.truncate(fraction.trim_end_matches('0').len());
+        }
+
+        let text = if fraction.is_empty() {
+            whole.to_string()
+        } else {
+            format!("{}.{}", whole, fraction)
+        };
+        f.pad_integral(rounded >= 0, "", &text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    // Quotient and remainder, nudged one step away from zero when the remainder is at least half of b
+    fn expected_round_div(a: i128, b: i128) -> i128 {
+        let (quotient, remainder) = (a / b, a % b);
+        if 2 * remainder.abs() >= b.abs() {
+            quotient + a.signum() * b.signum()
+        } else {
+            quotient
+        }
+    }
+
+    #[test]
+    fn round_div_rounds_half_away_from_zero() {
+        assert_eq!(round_div(5, 2), 3);
+        assert_eq!(round_div(-5, 2), -3);
+        assert_eq!(round_div(5, -2), -3);
+        assert_eq!(round_div(-5, -2), 3);
+        assert_eq!(round_div(7, 3), 2);
+        assert_eq!(round_div(-7, 3), -2);
+        assert_eq!(round_div(8, -3), -3);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100000 {
+            let a = rng.random_range(-1_000_000_000_000i128..=1_000_000_000_000);
+            let b = match rng.random_range(-1000i128..=1000) {
+                0 => 1,
+                b => b,
+            };
+            assert_eq!(round_div(a, b), expected_round_div(a, b), "{} / {}", a, b);
+        }
+    }
+
+    #[test]
+    fn operators_saturate() {
+        let one = Fixed::ONE;
+        assert_eq!(Fixed::MAX + one, Fixed::MAX);
+        assert_eq!(Fixed::MIN - one, Fixed::MIN);
+        assert_eq!(Fixed::MIN + -one, Fixed::MIN);
+        assert_eq!(Fixed::MAX * Fixed::from_int(2), Fixed::MAX);
+        assert_eq!(Fixed::MIN * Fixed::from_int(2), Fixed::MIN);
+        assert_eq!(Fixed::MAX * Fixed::from_int(-2), Fixed::MIN);
+        assert_eq!(Fixed::MIN * Fixed::from_int(-2), Fixed::MAX);
+        assert_eq!(-Fixed::MIN, Fixed::MAX);
+        assert_eq!(Fixed::from_int(i64::MAX), Fixed::MAX);
+        assert_eq!(i64::MAX * Fixed::from_int(2), i64::MAX);
+        assert_eq!(i64::MIN * Fixed::from_int(2), i64::MIN);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..10000 {
+            let a = Fixed::from_raw(rng.random());
+            let b = Fixed::from_raw(rng.random());
+            assert_eq!(a + b, Fixed::from_raw(a.raw().saturating_add(b.raw())));
+            assert_eq!(a - b, Fixed::from_raw(a.raw().saturating_sub(b.raw())));
+            let product = round_div(a.raw() as i128 * b.raw() as i128, SCALE as i128);
+            assert_eq!((a * b).raw(), saturate(product));
+        }
+    }
+
+    #[test]
+    fn checked_operations_fail_on_overflow() {
+        let two = Fixed::from_int(2);
+        assert_eq!(Fixed::MAX.checked_add(Fixed::ONE), None);
+        assert_eq!(Fixed::MIN.checked_sub(Fixed::ONE), None);
+        assert_eq!(Fixed::MAX.checked_mul(two), None);
+        assert_eq!(Fixed::MIN.checked_mul(two), None);
+        assert_eq!(Fixed::MAX.checked_div(Fixed::per_mille(500)), None);
+        assert_eq!(Fixed::ONE.checked_div(Fixed::ZERO), None);
+        assert_eq!(Fixed::from_int(2).checked_of(i64::MAX), None);
+        assert_eq!(Fixed::checked_ratio(i64::MAX, 1), None);
+        assert_eq!(Fixed::checked_ratio(1, 0), None);
+
+        assert_eq!(Fixed::ONE.checked_add(Fixed::ONE), Some(two));
+        assert_eq!(two.checked_mul(two), Some(Fixed::from_int(4)));
+        assert_eq!(
+            two.checked_div(Fixed::from_int(4)),
+            Some(Fixed::per_mille(500))
+        );
+
+        // Whenever the checked operation works, it agrees with the saturating one
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..10000 {
+            let a = Fixed::from_raw(rng.random::<i64>() >> rng.random_range(0..64));
+            let b = Fixed::from_raw(rng.random::<i64>() >> rng.random_range(0..64));
+            let pairs = [
+                (a.checked_add(b), a + b),
+                (a.checked_sub(b), a - b),
+                (a.checked_mul(b), a * b),
+                (a.checked_div(b), a / b),
+            ];
+            for (checked, saturated) in pairs {
+                if let Some(x) = checked {
+                    assert_eq!(x, saturated);
+                } else {
+                    assert!(saturated == Fixed::MAX || saturated == Fixed::MIN || b == Fixed::ZERO);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ratio_by_zero_saturates() {
+        assert_eq!(Fixed::ratio(0, 0), Fixed::ZERO);
+        assert_eq!(Fixed::ratio(5, 0), Fixed::MAX);
+        assert_eq!(Fixed::ratio(-5, 0), Fixed::MIN);
+        assert_eq!(Fixed::ratio(1, 3).raw(), 333333);
+        assert_eq!(Fixed::ratio(2, 3).raw(), 666667);
+        assert_eq!(Fixed::ratio(-2, 3).raw(), -666667);
+        assert_eq!(Fixed::ONE / Fixed::ZERO, Fixed::MAX);
+        assert_eq!(-Fixed::ONE / Fixed::ZERO, Fixed::MIN);
+        assert_eq!(5 / Fixed::ZERO, i64::MAX);
+        assert_eq!(Fixed::ONE.mul_div(3, 0), Fixed::MAX);
+    }
+
+    #[test]
+    fn rounds_and_prints() {
+        assert_eq!(Fixed::ratio(5, 2).round(), 3);
+        assert_eq!(Fixed::ratio(-5, 2).round(), -3);
+        assert_eq!(Fixed::ratio(-5, 2).floor(), -3);
+        assert_eq!(Fixed::ratio(-5, 2).trunc(), -2);
+        assert_eq!(Fixed::ratio(-5, 2).fract(), Fixed::per_mille(-500));
+        assert_eq!(Fixed::ratio(1, 3).to_string(), "0.333333");
+        assert_eq!(format!("{:.2}", Fixed::ratio(-2, 3)), "-0.67");
+        assert_eq!(format!("{:.0}", Fixed::per_mille(2500)), "3");
+        assert_eq!(Fixed::from_int(12).to_string(), "12");
+    }
+}
@@ -1,36 +1,186 @@
+use std::fmt;
 use std::ops;
 
-#[derive(Copy, Clone)]
+use super::Fixed;
+
+// A share or multiplier, set in thousandths (1000 is 100%) but kept as a Fixed
+// Multiplying a whole number by it rounds half away from zero and saturates
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub struct IPercent {
-    // SCALE : 0 to 1000
-    value: i32,
+    value: Fixed,
 }
 
-use std::fmt;
-
+// One decimal unless a precision is given. Width, fill and sign flags work as they do for numbers
 impl fmt::Display for IPercent {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}.{}%", self.value / 10, self.value % 10)
+        let percent = self.value * Fixed::from_int(100);
+        let text = format!("{:.*}", f.precision().unwrap_or(1), percent);
+        let (is_nonnegative, magnitude) = match text.strip_prefix('-') {
+            Some(x) => (false, x),
+            None => (true, text.as_str()),
+        };
+        f.pad_integral(is_nonnegative, "", &format!("{}%", magnitude))
     }
 }
 
 impl IPercent {
-    pub fn new(value: i32) -> Self {
+    pub const ZERO: Self = Self::new(0);
+    pub const FULL: Self = Self::new(1000);
+
+    // SCALE : 0 to 1000
+    pub const fn new(value: i32) -> Self {
+        Self {
+            value: Fixed::per_mille(value as i64),
+        }
+    }
+
+    pub const fn from_fixed(value: Fixed) -> Self {
         Self { value }
     }
+
+    // numerator / denominator, eg. the share of a total
+    pub fn ratio(numerator: i64, denominator: i64) -> Self {
+        Self::from_fixed(Fixed::ratio(numerator, denominator))
+    }
+
+    // In thousandths, rounded
+    pub fn value(&self) -> i32 {
+        self.value
+            .mul_div(1000, 1)
+            .round()
+            .clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+
+    pub const fn fixed(&self) -> Fixed {
+        self.value
+    }
+}
+
+impl ops::Add for IPercent {
+    type Output = IPercent;
+
+    fn add(self, rhs: IPercent) -> IPercent {
+        Self::from_fixed(self.value + rhs.value)
+    }
+}
+
+impl ops::Sub for IPercent {
+    type Output = IPercent;
+
+    fn sub(self, rhs: IPercent) -> IPercent {
+        Self::from_fixed(self.value - rhs.value)
+    }
+}
+
+// Share of a share
+impl ops::Mul for IPercent {
+    type Output = IPercent;
+
+    fn mul(self, rhs: IPercent) -> IPercent {
+        Self::from_fixed(self.value * rhs.value)
+    }
 }
 
 impl ops::Mul<IPercent> for i64 {
     type Output = i64;
 
-    fn mul(self, _rhs: IPercent) -> i64 {
-        (_rhs.value as i64 * self) / 1000
+    fn mul(self, rhs: IPercent) -> i64 {
+        rhs.value.of(self)
     }
 }
+
 impl ops::Mul<IPercent> for u64 {
     type Output = u64;
 
-    fn mul(self, _rhs: IPercent) -> u64 {
-        (_rhs.value as u64 * self) / 1000
+    fn mul(self, rhs: IPercent) -> u64 {
+        let x = (self.min(i64::MAX as u64) as i64) * rhs;
+        x.max(0) as u64
+    }
+}
+
+// Whole number divided by the share, eg. what a value buys at a price
+impl ops::Div<IPercent> for i64 {
+    type Output = i64;
+
+    fn div(self, rhs: IPercent) -> i64 {
+        rhs.value.divide(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    // IPercent as it was before it moved onto Fixed: thousandths, truncated towards zero
+    fn truncated(x: i64, value: i32) -> i64 {
+        value as i64 * x / 1000
+    }
+
+    #[test]
+    fn values_round_trip() {
+        for value in -2000..=2000 {
+            assert_eq!(IPercent::new(value).value(), value);
+        }
+    }
+
+    #[test]
+    fn displays_as_before() {
+        for value in 0..=2000 {
+            let before = format!("{}.{}%", value / 10, value % 10);
+            assert_eq!(IPercent::new(value).to_string(), before);
+        }
+        assert_eq!(IPercent::new(-1).to_string(), "-0.1%");
+        assert_eq!(IPercent::new(-53).to_string(), "-5.3%");
+        assert_eq!(IPercent::new(-1000).to_string(), "-100.0%");
+    }
+
+    #[test]
+    fn formats_like_a_number() {
+        let x = IPercent::new(53);
+        assert_eq!(format!("{:8}", x), "    5.3%");
+        assert_eq!(format!("{:<8}|", x), "5.3%    |");
+        assert_eq!(format!("{:*^8}", x), "**5.3%**");
+        assert_eq!(format!("{:+}", x), "+5.3%");
+        assert_eq!(format!("{:08}", IPercent::new(-53)), "-0005.3%");
+        // Precision is the number of decimals, never a cut
+        assert_eq!(format!("{:.2}", x), "5.30%");
+        assert_eq!(format!("{:.0}", IPercent::new(1000)), "100%");
+        assert_eq!(format!("{:7.0}", IPercent::new(-1000)), "  -100%");
+        assert_eq!(format!("{:.0}", IPercent::new(-4)), "0%");
+    }
+
+    // Products only differ from the truncated ones when they weren't whole, and then by rounding the last step
+    #[test]
+    fn multiplies_as_before() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100000 {
+            let x = rng.random_range(-1_000_000_000_000i64..=1_000_000_000_000);
+            let value = rng.random_range(0..=2000);
+            let before = truncated(x, value);
+            let after = x * IPercent::new(value);
+            if (value as i64 * x) % 1000 == 0 {
+                assert_eq!(after, before);
+            } else {
+                assert!((after - before).abs() <= 1, "{} * {}", x, value);
+                let exact = (value as i64 * x) as f64 / 1000.0;
+                assert_eq!(after, exact.round() as i64, "{} * {}", x, value);
+            }
+        }
+        assert_eq!(1000i64 * IPercent::new(150), 150);
+        assert_eq!(1999i64 * IPercent::new(500), 1000); // 999.5
+        assert_eq!(-1999i64 * IPercent::new(500), -1000);
+        assert_eq!(7u64 * IPercent::new(500), 4);
+        assert_eq!(u64::MAX * IPercent::FULL, i64::MAX as u64);
+    }
+
+    #[test]
+    fn ratio_by_zero_saturates() {
+        assert_eq!(IPercent::ratio(0, 0), IPercent::ZERO);
+        assert_eq!(IPercent::ratio(1, 4).value(), 250);
+        assert_eq!(IPercent::ratio(1, 3).value(), 333);
+        assert_eq!(IPercent::ratio(1, 0).fixed(), Fixed::MAX);
+        assert_eq!(IPercent::ratio(-1, 0).fixed(), Fixed::MIN);
+        assert_eq!(IPercent::ratio(1, 0).value(), i32::MAX);
     }
 }
@@ -1,8 +1,11 @@
+mod fixed;
 mod format_number;
-mod ifraction;
-
 mod ipercent;
+mod number_format;
+pub mod quantity;
 
+pub use fixed::Fixed;
 pub use format_number::FormatNumber;
-pub use ifraction::IFraction;
 pub use ipercent::IPercent;
+pub use number_format::{Locale, NumberFormat, NumberStyle};
+pub use quantity::{Credits, DecadeRate, People};
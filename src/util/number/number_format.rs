@@ -0,0 +1,250 @@
+// How big numbers are written out. Everything is done on integers so the same number always reads the same
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum NumberStyle {
+    #[default]
+    Abbreviated, // 1.234M
+    Si,         // 1.234 M, with k, M, G, T, P, E
+    Words,      // 1.234 million
+    Scientific, // 1.234e6
+    Exact,      // 1,234,567
+}
+
+impl NumberStyle {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Abbreviated => Self::Si,
+            Self::Si => Self::Words,
+            Self::Words => Self::Scientific,
+            Self::Scientific => Self::Exact,
+            Self::Exact => Self::Abbreviated,
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "abbreviated" => Some(Self::Abbreviated),
+            "si" => Some(Self::Si),
+            "words" => Some(Self::Words),
+            "scientific" => Some(Self::Scientific),
+            "exact" => Some(Self::Exact),
+            _ => None,
+        }
+    }
+}
+
+// Separators and the words for thousands, millions... up to quintillions
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Locale {
+    pub decimal: char,
+    pub group: char,
+    pub scale_words: [&'static str; 6],
+    pub month_names: [&'static str; 12],
+}
+
+impl Locale {
+    pub const ENGLISH: Self = Self {
+        decimal: '.',
+        group: ',',
+        scale_words: [
+            "thousand",
+            "million",
+            "billion",
+            "trillion",
+            "quadrillion",
+            "quintillion",
+        ],
+        month_names: [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ],
+    };
+
+    pub const GERMAN: Self = Self {
+        decimal: ',',
+        group: '.',
+        scale_words: ["Tsd.", "Mio.", "Mrd.", "Bio.", "Brd.", "Trio."],
+        month_names: [
+            "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+        ],
+    };
+
+    pub const FRENCH: Self = Self {
+        decimal: ',',
+        group: '\u{202f}', // Narrow no-break space
+        scale_words: ["k", "M", "Md", "Bn", "Bd", "Tn"],
+        month_names: [
+            "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.",
+            "nov.", "déc.",
+        ],
+    };
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "en" => Some(Self::ENGLISH),
+            "de" => Some(Self::GERMAN),
+            "fr" => Some(Self::FRENCH),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::ENGLISH
+    }
+}
+
+const ABBREVIATIONS: [&str; 6] = ["K", "M", "B", "T", "Qa", "Qi"];
+const SI_PREFIXES: [&str; 6] = ["k", "M", "G", "T", "P", "E"];
+const DECIMALS: u32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct NumberFormat {
+    pub style: NumberStyle,
+    pub locale: Locale,
+}
+
+impl NumberFormat {
+    pub fn format(&self, x: i64) -> String {
+        let sign = if x < 0 { "-" } else { "" };
+        format!("{}{}", sign, self.format_unsigned(x.unsigned_abs()))
+    }
+
+    pub fn format_unsigned(&self, x: u64) -> String {
+        if self.style == NumberStyle::Exact || x < 1000 {
+            return self.group_digits(x);
+        }
+
+        if self.style == NumberStyle::Scientific {
+            let exponent = x.ilog10();
+            let mantissa = self.decimal_part(x, 10_u64.pow(exponent));
+            return format!("{}e{}", mantissa, exponent);
+        }
+
+        // u64 tops out in the quintillions, so there's always a word for it
+        let scale = (x.ilog10() / 3) as usize;
+        let number = self.decimal_part(x, 1000_u64.pow(scale as u32));
+        match self.style {
+            NumberStyle::Si => format!("{} {}", number, SI_PREFIXES[scale - 1]),
+            NumberStyle::Words => format!("{} {}", number, self.locale.scale_words[scale - 1]),
+            _ => format!("{}{}", number, ABBREVIATIONS[scale - 1]),
+        }
+    }
+
+    // x / unit with DECIMALS digits, truncated
+    fn decimal_part(&self, x: u64, unit: u64) -> String {
+        let decimals = 10_u64.pow(DECIMALS).min(unit);
+        let digits = decimals.ilog10() as usize;
+        let fraction = (x % unit) / (unit / decimals);
+        if digits == 0 {
+            return (x / unit).to_string();
+        }
+        format!(
+            "{}{}{:0digits$}",
+            x / unit,
+            self.locale.decimal,
+            fraction,
+            digits = digits
+        )
+    }
+
+    fn group_digits(&self, x: u64) -> String {
+        let digits = x.to_string();
+        let mut out = String::with_capacity(digits.len() * 4 / 3);
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(self.locale.group);
+            }
+            out.push(c);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::number::FormatNumber;
+    use NumberStyle::*;
+
+    const LOCALES: [Locale; 3] = [Locale::ENGLISH, Locale::GERMAN, Locale::FRENCH];
+
+    fn format(style: NumberStyle, locale: Locale, x: i64) -> String {
+        NumberFormat { style, locale }.format(x)
+    }
+
+    // The same number in every style, for English, German and French
+    #[test]
+    fn formats_each_style_and_locale() {
+        let table = [
+            (Abbreviated, ["1.234M", "1,234M", "1,234M"]),
+            (Si, ["1.234 M", "1,234 M", "1,234 M"]),
+            (Words, ["1.234 million", "1,234 Mio.", "1,234 M"]),
+            (Scientific, ["1.234e6", "1,234e6", "1,234e6"]),
+            (Exact, ["1,234,567", "1.234.567", "1\u{202f}234\u{202f}567"]),
+        ];
+        for (style, expected) in table {
+            for (locale, expected) in LOCALES.into_iter().zip(expected) {
+                assert_eq!(format(style, locale, 1_234_567), expected);
+                assert_eq!(format(style, locale, -1_234_567), format!("-{}", expected));
+            }
+        }
+    }
+
+    // Below a thousand, every style writes the number out in full
+    #[test]
+    fn leaves_small_numbers_alone() {
+        let mut style = Abbreviated;
+        loop {
+            for locale in LOCALES {
+                for (x, expected) in [(0, "0"), (7, "7"), (999, "999"), (-999, "-999")] {
+                    assert_eq!(format(style, locale, x), expected);
+                }
+            }
+            style = style.next();
+            if style == Abbreviated {
+                break;
+            }
+        }
+    }
+
+    // Digits are cut rather than rounded, so a number never reads as a thousand of the unit below
+    #[test]
+    fn truncates_at_scale_boundaries() {
+        let en = Locale::ENGLISH;
+        let table = [
+            (Abbreviated, 999_950, "999.950K"),
+            (Abbreviated, 999_999, "999.999K"),
+            (Abbreviated, 1_000_000, "1.000M"),
+            (Abbreviated, 999_999_999, "999.999M"),
+            (Si, 1_000, "1.000 k"),
+            (Si, 999_999_999_999, "999.999 G"),
+            (Words, 1_999_999_999_999, "1.999 trillion"),
+            (Scientific, 9_999_999, "9.999e6"),
+            (Scientific, 1_000, "1.000e3"),
+            (Scientific, 12_000, "1.200e4"),
+        ];
+        for (style, x, expected) in table {
+            assert_eq!(format(style, en, x), expected, "{:?} {}", style, x);
+        }
+    }
+
+    #[test]
+    fn reaches_the_quintillions() {
+        let en = Locale::ENGLISH;
+        let quintillion = 1_000_000_000_000_000_000;
+        assert_eq!(format(Abbreviated, en, quintillion), "1.000Qi");
+        assert_eq!(format(Si, en, quintillion), "1.000 E");
+        assert_eq!(format(Words, en, quintillion), "1.000 quintillion");
+        assert_eq!(format(Words, Locale::GERMAN, quintillion), "1,000 Trio.");
+        assert_eq!(format(Words, en, quintillion - 1), "999.999 quadrillion");
+        assert_eq!(format(Abbreviated, en, i64::MAX), "9.223Qi");
+        assert_eq!(format(Abbreviated, en, i64::MIN), "-9.223Qi");
+        assert_eq!(format(Scientific, en, i64::MIN), "-9.223e18");
+        assert_eq!(format(Exact, en, i64::MIN), "-9,223,372,036,854,775,808");
+
+        let unsigned = NumberFormat::default();
+        assert_eq!(u64::MAX.format_with(&unsigned), "18.446Qi");
+        assert_eq!((-1500i64).format_big_number(), "-1.500K");
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops;
+
+use super::{Fixed, FormatNumber, NumberFormat};
+
+// Typed quantities and rates, so a yearly rate can't be applied to a daily flow by accident
+// All of them are whole numbers or Fixed underneath, and saturate instead of overflowing
+// Periods and units are only ever type parameters
+
+pub trait Period: Copy {
+    const DAYS: i64;
+    const NAME: &'static str;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Day {}
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Year {}
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Decade {}
+
+impl Period for Day {
+    const DAYS: i64 = 1;
+    const NAME: &'static str = "day";
+}
+
+impl Period for Year {
+    const DAYS: i64 = 365;
+    const NAME: &'static str = "year";
+}
+
+impl Period for Decade {
+    const DAYS: i64 = 3650;
+    const NAME: &'static str = "decade";
+}
+
+pub trait Unit: Copy {
+    const SYMBOL: &'static str;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Persons {}
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Credit {}
+
+impl Unit for Persons {
+    const SYMBOL: &'static str = "";
+}
+
+impl Unit for Credit {
+    const SYMBOL: &'static str = "cr";
+}
+
+// A whole amount of something
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Quantity<U: Unit> {
+    value: i64,
+    unit: PhantomData<U>,
+}
+
+pub type People = Quantity<Persons>;
+pub type Credits = Quantity<Credit>;
+
+impl<U: Unit> Quantity<U> {
+    pub const ZERO: Self = Self::new(0);
+
+    pub const fn new(value: i64) -> Self {
+        Self {
+            value,
+            unit: PhantomData,
+        }
+    }
+
+    pub const fn value(self) -> i64 {
+        self.value
+    }
+
+    // Adds a fractional amount, eg. a day's flow, and returns the fraction that didn't make a whole unit
+    pub fn add_fixed(&mut self, amount: Fixed) -> Fixed {
+        self.value = self.value.saturating_add(amount.trunc());
+        amount.fract()
+    }
+}
+
+impl<U: Unit> Default for Quantity<U> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<U: Unit> ops::Add for Quantity<U> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.value.saturating_add(rhs.value))
+    }
+}
+
+impl<U: Unit> ops::Sub for Quantity<U> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.value.saturating_sub(rhs.value))
+    }
+}
+
+impl<U: Unit> ops::Mul<i64> for Quantity<U> {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self {
+        Self::new(self.value.saturating_mul(rhs))
+    }
+}
+
+impl<U: Unit> ops::AddAssign for Quantity<U> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<U: Unit> ops::SubAssign for Quantity<U> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<U: Unit> FormatNumber for Quantity<U> {
+    fn format_with(&self, format: &NumberFormat) -> String {
+        format!("{}{}", self.value.format_with(format), U::SYMBOL)
+    }
+}
+
+impl<U: Unit> fmt::Display for Quantity<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.format_big_number())
+    }
+}
+
+// A share of a stock gained or lost every period, eg. a birth rate
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Rate<P: Period> {
+    share: Fixed,
+    period: PhantomData<P>,
+}
+
+pub type DecadeRate = Rate<Decade>;
+
+impl<P: Period> Rate<P> {
+    pub const ZERO: Self = Self::new(Fixed::ZERO);
+
+    pub const fn new(share: Fixed) -> Self {
+        Self {
+            share,
+            period: PhantomData,
+        }
+    }
+
+    pub const fn per_mille(x: i64) -> Self {
+        Self::new(Fixed::per_mille(x))
+    }
+
+    pub const fn share(self) -> Fixed {
+        self.share
+    }
+
+    // The same rate over another period, without compounding
+    pub fn to<Q: Period>(self) -> Rate<Q> {
+        Rate::new(self.share.mul_div(Q::DAYS, P::DAYS))
+    }
+
+    // Amount gained or lost over one period, with the fraction kept
+    pub fn flow<U: Unit>(self, stock: Quantity<U>) -> Fixed {
+        Fixed::from_raw(self.share.raw().saturating_mul(stock.value))
+    }
+}
+
+impl<P: Period> Default for Rate<P> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<P: Period> fmt::Display for Rate<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let percent = self.share * Fixed::from_int(100);
+        f.pad(&format!("{:.2}%/{}", percent, P::NAME))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantities_saturate() {
+        let most = People::new(i64::MAX);
+        assert_eq!(most + People::new(1), most);
+        assert_eq!(
+            People::new(i64::MIN) - People::new(1),
+            People::new(i64::MIN)
+        );
+        assert_eq!(most * 2, most);
+        assert_eq!(Credits::new(-3) * 2, Credits::new(-6));
+    }
+
+    #[test]
+    fn flows_keep_their_fractions() {
+        let mut people = People::new(100);
+        let rate = DecadeRate::per_mille(365).to::<Day>();
+        let mut carried = Fixed::ZERO;
+        for _ in 0..3650 {
+            carried = people.add_fixed(rate.flow(People::new(1000)) + carried);
+        }
+        // 36.5% of 1000 over a decade, although no single day adds a whole person
+        assert_eq!(people, People::new(465));
+        assert!(carried.abs() < Fixed::ONE);
+    }
+
+    #[test]
+    fn rates_convert_without_compounding() {
+        let yearly = Rate::<Year>::per_mille(365);
+        assert_eq!(yearly.to::<Day>().share(), Fixed::per_mille(1));
+        assert_eq!(yearly.to::<Decade>().share(), Fixed::per_mille(3650));
+        assert_eq!(
+            Rate::<Year>::new(Fixed::MAX).to::<Decade>().share(),
+            Fixed::MAX
+        );
+    }
+}